@@ -0,0 +1,168 @@
+// How far apart two floats may be while still counting as equal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Margin {
+    Absolute(f64),
+    // A fraction of the larger magnitude of the two values.
+    Relative(f64),
+    // Representable floats between the two values.
+    Ulps(u64),
+}
+
+impl Default for Margin {
+    fn default() -> Self {
+        Margin::Absolute(1e-9)
+    }
+}
+
+pub trait ApproxEq {
+    fn approx_eq(&self, other: &Self, margin: Margin) -> bool;
+
+    // Bitwise equality, so zero and negative zero differ while NaN equals itself.
+    fn exact_eq(&self, other: &Self) -> bool;
+}
+
+impl ApproxEq for f64 {
+    fn approx_eq(&self, other: &Self, margin: Margin) -> bool {
+        if self == other {
+            return true;
+        }
+        // Equal infinities were caught above, every other pairing is unbounded.
+        if !self.is_finite() || !other.is_finite() {
+            return false;
+        }
+
+        match margin {
+            Margin::Absolute(epsilon) => (self - other).abs() <= epsilon,
+            Margin::Relative(fraction) => {
+                (self - other).abs() <= self.abs().max(other.abs()) * fraction
+            }
+            Margin::Ulps(ulps) => {
+                if self.is_sign_positive() != other.is_sign_positive() {
+                    return false;
+                }
+                // Floats of the same sign are ordered like their bit patterns.
+                self.to_bits().abs_diff(other.to_bits()) <= ulps
+            }
+        }
+    }
+
+    fn exact_eq(&self, other: &Self) -> bool {
+        self.to_bits() == other.to_bits()
+    }
+}
+
+impl<T: ApproxEq, const N: usize> ApproxEq for [T; N] {
+    fn approx_eq(&self, other: &Self, margin: Margin) -> bool {
+        self.iter()
+            .zip(other)
+            .all(|(left, right)| left.approx_eq(right, margin))
+    }
+
+    fn exact_eq(&self, other: &Self) -> bool {
+        self.iter()
+            .zip(other)
+            .all(|(left, right)| left.exact_eq(right))
+    }
+}
+
+#[macro_export]
+macro_rules! assert_approx_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_approx_eq!($left, $right, $crate::approx::Margin::default())
+    };
+    ($left:expr, $right:expr, $margin:expr $(,)?) => {
+        match (&$left, &$right, $margin) {
+            (left, right, margin) => {
+                if !$crate::approx::ApproxEq::approx_eq(left, right, margin) {
+                    panic!(
+                        "assertion `left approx_eq right` failed with {:?}\n  left: {:?}\n right: {:?}",
+                        margin, left, right
+                    );
+                }
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! assert_approx_ne {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_approx_ne!($left, $right, $crate::approx::Margin::default())
+    };
+    ($left:expr, $right:expr, $margin:expr $(,)?) => {
+        match (&$left, &$right, $margin) {
+            (left, right, margin) => {
+                if $crate::approx::ApproxEq::approx_eq(left, right, margin) {
+                    panic!(
+                        "assertion `left approx_ne right` failed with {:?}\n  left: {:?}\n right: {:?}",
+                        margin, left, right
+                    );
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approx_eq_absolute() {
+        assert!(1.0.approx_eq(&1.05, Margin::Absolute(0.1)));
+        assert!(!1.0.approx_eq(&1.2, Margin::Absolute(0.1)));
+        assert!((0.1 + 0.2).approx_eq(&0.3, Margin::default()));
+    }
+
+    #[test]
+    fn approx_eq_relative() {
+        assert!(1000.0.approx_eq(&1001.0, Margin::Relative(0.01)));
+        assert!(!1.0.approx_eq(&1.1, Margin::Relative(0.01)));
+        assert!(!0.0.approx_eq(&1e-300, Margin::Relative(0.5)));
+    }
+
+    #[test]
+    fn approx_eq_ulps() {
+        let next = f64::from_bits(1.0_f64.to_bits() + 2);
+
+        assert!(1.0.approx_eq(&next, Margin::Ulps(2)));
+        assert!(!1.0.approx_eq(&next, Margin::Ulps(1)));
+        assert!(0.0.approx_eq(&-0.0, Margin::Ulps(0)));
+        assert!(!1e-300.approx_eq(&-1e-300, Margin::Ulps(u64::MAX)));
+    }
+
+    #[test]
+    fn approx_eq_special_values() {
+        assert!(f64::INFINITY.approx_eq(&f64::INFINITY, Margin::Absolute(0.0)));
+        assert!(!f64::NAN.approx_eq(&f64::NAN, Margin::Absolute(f64::INFINITY)));
+        assert!(!f64::INFINITY.approx_eq(&1.0, Margin::Relative(1.0)));
+    }
+
+    #[test]
+    fn exact_eq_bitwise() {
+        assert!(1.5.exact_eq(&1.5));
+        assert!(!0.0.exact_eq(&-0.0));
+        assert!(f64::NAN.exact_eq(&f64::NAN));
+        assert!(!(0.1 + 0.2).exact_eq(&0.3));
+    }
+
+    #[test]
+    fn approx_eq_array() {
+        assert!([1.0, 2.0].approx_eq(&[1.0, 2.0 + 1e-12], Margin::default()));
+        assert!(![1.0, 2.0].approx_eq(&[1.0, 2.1], Margin::default()));
+        assert!(![1.0, 2.0].exact_eq(&[1.0, 2.0 + 1e-12]));
+    }
+
+    #[test]
+    fn assert_approx_macros() {
+        assert_approx_eq!(0.1 + 0.2, 0.3);
+        assert_approx_eq!(100.0, 101.0, Margin::Relative(0.02));
+        assert_approx_ne!(1.0, 1.1);
+    }
+
+    #[test]
+    #[should_panic(expected = "approx_eq")]
+    fn assert_approx_eq_fails() {
+        assert_approx_eq!(1.0, 1.1);
+    }
+}
@@ -1,2 +1,4 @@
+pub mod approx;
 pub mod pixels;
+pub mod rendering;
 pub mod tuples;
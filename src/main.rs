@@ -31,14 +31,16 @@ fn main() {
     let environment = Environment { gravity, wind };
 
     while projectile.position.y() > 0.0 {
+        let previous = projectile.position;
         projectile = tick(environment, projectile);
-        canvas.set_pixel(
-            projectile.position.x() as usize,
-            canvas.height()
-                - (projectile.position.y() as usize)
-                    .max(0)
-                    .min(canvas.height())
-                - 1,
+
+        // Canvas rows grow downwards while the projectile height grows upwards.
+        let bottom = (canvas.height() - 1) as f64;
+        canvas.draw_line_antialiased(
+            previous.x(),
+            bottom - previous.y(),
+            projectile.position.x(),
+            bottom - projectile.position.y(),
             color,
         );
     }
@@ -0,0 +1,206 @@
+use crate::pixels::color::Color;
+
+const PLANCK: f64 = 6.626_070_15e-34;
+const LIGHT_SPEED: f64 = 299_792_458.0;
+const BOLTZMANN: f64 = 1.380_649e-23;
+const STEFAN_BOLTZMANN: f64 = 5.670_374_419e-8;
+// Lumens per watt of light at the peak of the photopic response.
+const MAX_EFFICACY: f64 = 683.0;
+// Colder bodies radiate too little visible light to have a colour in double
+// precision, so lower or invalid temperatures count as this one.
+const MIN_KELVIN: f64 = 100.0;
+
+// Wavelengths in nanometres the colour matching functions are integrated over.
+pub(crate) const VISIBLE: std::ops::RangeInclusive<u32> = 360..=830;
+
+// Spectral radiance of a blackbody in W / (sr m^3) at a wavelength in
+// nanometres, zero at or below absolute zero.
+pub fn planck(wavelength: f64, kelvin: f64) -> f64 {
+    if kelvin.is_nan() || kelvin <= 0.0 {
+        return 0.0;
+    }
+
+    let wavelength = wavelength * 1e-9;
+    let exponent = PLANCK * LIGHT_SPEED / (wavelength * BOLTZMANN * kelvin);
+
+    2.0 * PLANCK * LIGHT_SPEED.powi(2) / (wavelength.powi(5) * exponent.exp_m1())
+}
+
+// The CIE 1931 2° colour matching functions at a wavelength in nanometres,
+// using the multi-lobe Gaussian fit by Wyman, Sloan and Shirley.
+pub fn color_matching(wavelength: f64) -> (f64, f64, f64) {
+    let lobe = |mean: f64, below: f64, above: f64| {
+        let deviation = if wavelength < mean { below } else { above };
+        (-0.5 * ((wavelength - mean) / deviation).powi(2)).exp()
+    };
+
+    (
+        1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+            - 0.065 * lobe(501.1, 20.4, 26.2),
+        0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1),
+        1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8),
+    )
+}
+
+// Unnormalised CIE XYZ of a blackbody, summed in one nanometre steps.
+fn blackbody_xyz(kelvin: f64) -> (f64, f64, f64) {
+    let kelvin = kelvin.max(MIN_KELVIN);
+    VISIBLE.fold((0.0, 0.0, 0.0), |(x, y, z), wavelength| {
+        let wavelength = wavelength as f64;
+        let radiance = planck(wavelength, kelvin);
+        let (x_bar, y_bar, z_bar) = color_matching(wavelength);
+
+        (
+            x + x_bar * radiance,
+            y + y_bar * radiance,
+            z + z_bar * radiance,
+        )
+    })
+}
+
+// Lumens per watt radiated by a blackbody, around 12 for a 2700K filament and
+// at most about 95 near 6600K.
+pub fn luminous_efficacy(kelvin: f64) -> f64 {
+    let kelvin = kelvin.max(MIN_KELVIN);
+    // Radiance summed over every wavelength, in W / (sr m^2).
+    let total = STEFAN_BOLTZMANN * kelvin.powi(4) / std::f64::consts::PI;
+    // The visible sum is taken in one nanometre steps.
+    let visible = blackbody_xyz(kelvin).1 * 1e-9;
+
+    MAX_EFFICACY * visible / total
+}
+
+// Colours are scaled so their luminance matches the requested quantity,
+// making them usable directly as the radiance of an emitter. Temperatures
+// below 100K, including zero, negative and NaN ones, are taken as 100K.
+impl Color {
+    // The linear Rec.709 colour of a blackbody with a luminance of one.
+    pub fn from_kelvin(kelvin: f64) -> Color {
+        let (x, y, z) = blackbody_xyz(kelvin);
+        Color::from_xyz(x / y, 1.0, z / y)
+    }
+
+    // A luminance in nits, that is candela per square metre.
+    pub fn from_kelvin_nits(kelvin: f64, nits: f64) -> Color {
+        Color::from_kelvin(kelvin) * nits
+    }
+
+    // A flat emitter of `area` square metres radiating `lumens` evenly into
+    // the hemisphere in front of it, whose luminance is the flux divided by
+    // pi times the area.
+    pub fn from_kelvin_lumens(kelvin: f64, lumens: f64, area: f64) -> Color {
+        Color::from_kelvin_nits(kelvin, lumens / (std::f64::consts::PI * area))
+    }
+
+    // Like `from_kelvin_lumens` for a radiant flux in watts.
+    pub fn from_kelvin_watts(kelvin: f64, watts: f64, area: f64) -> Color {
+        Color::from_kelvin_lumens(kelvin, watts * luminous_efficacy(kelvin), area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+    use crate::pixels::rgb::Rgb;
+
+    fn chromaticity(color: Color) -> (f64, f64) {
+        let (x, y, z) = color.to_xyz();
+        (x / (x + y + z), y / (x + y + z))
+    }
+
+    #[test]
+    fn planck_peak() {
+        // Wien's displacement law puts the peak of a 5800K body near 500nm.
+        let peak = (300..800)
+            .max_by(|a, b| planck(*a as f64, 5800.0).total_cmp(&planck(*b as f64, 5800.0)))
+            .unwrap();
+
+        assert!((495..=505).contains(&peak));
+    }
+
+    #[test]
+    fn color_matching_peaks() {
+        let (_, y, _) = color_matching(555.0);
+        let (x, _, _) = color_matching(599.8);
+        let (_, _, z) = color_matching(445.0);
+
+        assert_approx_eq!(1.0, y, Margin::Absolute(0.01));
+        assert_approx_eq!(1.06, x, Margin::Absolute(0.01));
+        assert_approx_eq!(1.78, z, Margin::Absolute(0.05));
+    }
+
+    #[test]
+    fn from_kelvin_illuminant_a() {
+        // CIE illuminant A is a 2856K blackbody.
+        let (x, y) = chromaticity(Color::from_kelvin(2856.0));
+
+        assert_approx_eq!(0.4476, x, Margin::Absolute(2e-3));
+        assert_approx_eq!(0.4074, y, Margin::Absolute(2e-3));
+    }
+
+    #[test]
+    fn from_kelvin_luminance() {
+        for kelvin in [1000.0, 2700.0, 6500.0, 15000.0] {
+            assert_approx_eq!(
+                1.0,
+                Color::from_kelvin(kelvin).luminance(),
+                Margin::Absolute(1e-9)
+            );
+        }
+    }
+
+    #[test]
+    fn from_kelvin_warm_to_cool() {
+        let warm = Color::from_kelvin(2700.0);
+        let daylight = Color::from_kelvin(6500.0);
+        let cool = Color::from_kelvin(12000.0);
+
+        assert!(warm.r() > warm.g() && warm.g() > warm.b());
+        assert!(cool.b() > cool.g() && cool.g() > cool.r());
+        assert_approx_eq!(daylight.r(), daylight.b(), Margin::Absolute(0.1));
+    }
+
+    #[test]
+    fn luminous_efficacy_blackbody() {
+        assert_approx_eq!(12.4, luminous_efficacy(2700.0), Margin::Absolute(0.5));
+        assert_approx_eq!(54.0, luminous_efficacy(4000.0), Margin::Absolute(1.5));
+        assert_approx_eq!(95.0, luminous_efficacy(6600.0), Margin::Absolute(3.0));
+        assert!(luminous_efficacy(20000.0) < luminous_efficacy(6600.0));
+    }
+
+    #[test]
+    fn from_kelvin_photometric() {
+        let screen = Color::from_kelvin_nits(6500.0, 250.0);
+        // An 800 lumen panel of 0.1 square metres.
+        let panel = Color::from_kelvin_lumens(2700.0, 800.0, 0.1);
+        let watts = Color::from_kelvin_watts(2700.0, 10.0, 0.1);
+
+        assert_approx_eq!(250.0, screen.luminance(), Margin::Relative(1e-9));
+        assert_approx_eq!(
+            800.0 / (std::f64::consts::PI * 0.1),
+            panel.luminance(),
+            Margin::Relative(1e-9)
+        );
+        assert_approx_eq!(
+            10.0 * luminous_efficacy(2700.0) / (std::f64::consts::PI * 0.1),
+            watts.luminance(),
+            Margin::Relative(1e-9)
+        );
+    }
+
+    #[test]
+    fn from_kelvin_clamps_temperature() {
+        let coldest = Color::from_kelvin(MIN_KELVIN);
+
+        for kelvin in [0.0, -300.0, 50.0, f64::NAN] {
+            let color = Color::from_kelvin(kelvin);
+            assert_eq!(coldest, color);
+            assert!(luminous_efficacy(kelvin).is_finite());
+        }
+        assert!(coldest.r().is_finite() && coldest.g().is_finite() && coldest.b().is_finite());
+        assert_eq!(0.0, planck(550.0, 0.0));
+        assert_eq!(0.0, planck(550.0, -10.0));
+    }
+}
@@ -1,10 +1,18 @@
 use crate::pixels::color::Color;
+use crate::pixels::color_space::ColorSpace;
+use crate::pixels::error::CanvasError;
+use crate::pixels::rect::Rect;
 
+// Colours are stored premultiplied by their alpha, which starts at zero for
+// pixels that were never drawn. New canvases work in Rec.709.
 #[derive(Clone, Debug)]
 pub struct Canvas {
     width: usize,
     height: usize,
     content: Vec<Color>,
+    alpha: Vec<f64>,
+    clip: Option<Rect>,
+    color_space: ColorSpace,
 }
 
 impl Canvas {
@@ -13,6 +21,9 @@ impl Canvas {
             width,
             height,
             content: vec![Color::default(); width * height],
+            alpha: vec![0.0; width * height],
+            clip: None,
+            color_space: ColorSpace::default(),
         }
     }
 
@@ -24,8 +35,93 @@ impl Canvas {
         self.width
     }
 
-    pub fn content(&self) -> Vec<Color> {
-        self.content.clone()
+    // Restricts the drawing primitives, but not direct pixel access.
+    pub fn clip(&self) -> Option<Rect> {
+        self.clip
+    }
+
+    pub fn set_clip(&mut self, clip: Option<Rect>) {
+        self.clip = clip;
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    // Relabels the pixels without converting them, see `to_color_space`.
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.content
+    }
+
+    pub fn alphas(&self) -> &[f64] {
+        &self.alpha
+    }
+
+    // The mutable views pair each colour with its alpha, which stays zero
+    // unless the caller sets it.
+    pub fn pixels_mut(&mut self) -> impl Iterator<Item = (&mut Color, &mut f64)> {
+        self.content.iter_mut().zip(self.alpha.iter_mut())
+    }
+
+    // Each row with its y coordinate, one per line of the canvas even when
+    // the rows are empty.
+    pub fn rows(&self) -> impl Iterator<Item = (usize, &[Color])> {
+        let width = self.width;
+
+        (0..self.height).map(move |y| (y, &self.content[y * width..(y + 1) * width]))
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = (usize, &mut [Color], &mut [f64])> {
+        let width = self.width;
+        let mut content = self.content.as_mut_slice();
+        let mut alpha = self.alpha.as_mut_slice();
+
+        (0..self.height).map(move |y| {
+            let (colors, rest) = std::mem::take(&mut content).split_at_mut(width);
+            content = rest;
+            let (alphas, rest) = std::mem::take(&mut alpha).split_at_mut(width);
+            alpha = rest;
+            (y, colors, alphas)
+        })
+    }
+
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (usize, usize, &Color)> {
+        let width = self.width;
+
+        self.content
+            .iter()
+            .enumerate()
+            .map(move |(i, color)| (i % width, i / width, color))
+    }
+
+    pub fn enumerate_pixels_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (usize, usize, &mut Color, &mut f64)> {
+        let width = self.width;
+
+        self.pixels_mut()
+            .enumerate()
+            .map(move |(i, (color, alpha))| (i % width, i / width, color, alpha))
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn try_get(&self, x: usize, y: usize) -> Result<Color, CanvasError> {
+        let mapped_position = self.try_map_coordinates(x, y)?;
+        Ok(self.content[mapped_position])
+    }
+
+    pub fn try_set(&mut self, x: usize, y: usize, color: Color) -> Result<(), CanvasError> {
+        let mapped_position = self.try_map_coordinates(x, y)?;
+        self.content[mapped_position] = color;
+        self.alpha[mapped_position] = 1.0;
+        Ok(())
     }
 
     pub fn get_pixel(&self, x: usize, y: usize) -> Color {
@@ -34,12 +130,38 @@ impl Canvas {
     }
 
     pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        self.set_pixel_with_alpha(x, y, color, 1.0);
+    }
+
+    pub fn get_alpha(&self, x: usize, y: usize) -> f64 {
+        let mapped_position = self.map_coordinates(x, y);
+        self.alpha[mapped_position]
+    }
+
+    pub fn set_pixel_with_alpha(&mut self, x: usize, y: usize, color: Color, alpha: f64) {
         let mapped_position = self.map_coordinates(x, y);
         self.content[mapped_position] = color;
+        self.alpha[mapped_position] = alpha;
     }
 
     fn map_coordinates(&self, x: usize, y: usize) -> usize {
-        x + (y * self.width)
+        match self.try_map_coordinates(x, y) {
+            Ok(mapped_position) => mapped_position,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    fn try_map_coordinates(&self, x: usize, y: usize) -> Result<usize, CanvasError> {
+        if !self.contains(x, y) {
+            return Err(CanvasError::OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        Ok(x + (y * self.width))
     }
 }
 
@@ -56,9 +178,10 @@ mod tests {
         assert_eq!(10, canvas.width());
         assert_eq!(20, canvas.height());
 
-        for pixel in canvas.content().iter() {
+        for pixel in canvas.pixels().iter() {
             assert_eq!(empty_pixel, pixel);
         }
+        assert_eq!(0.0, canvas.get_alpha(9, 19));
     }
 
     #[test]
@@ -68,6 +191,108 @@ mod tests {
 
         canvas.set_pixel(2, 3, red_pixel);
 
-        assert_eq!(red_pixel, canvas.get_pixel(2, 3))
+        assert_eq!(red_pixel, canvas.get_pixel(2, 3));
+        assert_eq!(1.0, canvas.get_alpha(2, 3));
+        assert_eq!(0.0, canvas.get_alpha(3, 2));
+    }
+
+    #[test]
+    fn set_pixel_with_alpha_canvas() {
+        let mut canvas = Canvas::new(10, 20);
+        let half_red = Color::new(0.5, 0.0, 0.0);
+
+        canvas.set_pixel_with_alpha(4, 5, half_red, 0.5);
+
+        assert_eq!(half_red, canvas.get_pixel(4, 5));
+        assert_eq!(0.5, canvas.get_alpha(4, 5));
+    }
+
+    #[test]
+    fn contains_canvas() {
+        let canvas = Canvas::new(10, 20);
+
+        assert!(canvas.contains(0, 0));
+        assert!(canvas.contains(9, 19));
+        assert!(!canvas.contains(10, 0));
+        assert!(!canvas.contains(0, 20));
+    }
+
+    #[test]
+    fn try_get_and_set_canvas() {
+        let mut canvas = Canvas::new(10, 20);
+        let red_pixel = Color::new(1.0, 0.0, 0.0);
+
+        assert_eq!(Ok(()), canvas.try_set(9, 19, red_pixel));
+        assert_eq!(Ok(red_pixel), canvas.try_get(9, 19));
+        assert_eq!(1.0, canvas.get_alpha(9, 19));
+
+        let error = CanvasError::OutOfBounds {
+            x: 10,
+            y: 0,
+            width: 10,
+            height: 20,
+        };
+        assert_eq!(Err(error), canvas.try_set(10, 0, red_pixel));
+        assert_eq!(Err(error), canvas.try_get(10, 0));
+        assert_eq!(Color::default(), canvas.get_pixel(0, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "pixel (10, 0) is outside the 10x20 canvas")]
+    fn set_pixel_out_of_bounds() {
+        let mut canvas = Canvas::new(10, 20);
+
+        canvas.set_pixel(10, 0, Color::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn rows_canvas() {
+        let mut canvas = Canvas::new(3, 2);
+        let red_pixel = Color::new(1.0, 0.0, 0.0);
+
+        let (y, colors, alphas) = canvas.rows_mut().nth(1).unwrap();
+        assert_eq!(1, y);
+        colors.fill(red_pixel);
+        alphas.fill(1.0);
+
+        let rows: Vec<(usize, &[Color])> = canvas.rows().collect();
+        assert_eq!(2, rows.len());
+        assert_eq!((0, &[Color::default(); 3][..]), rows[0]);
+        assert_eq!((1, &[red_pixel; 3][..]), rows[1]);
+        assert_eq!(&[0.0, 0.0, 0.0, 1.0, 1.0, 1.0], canvas.alphas());
+    }
+
+    #[test]
+    fn rows_of_empty_canvas() {
+        let mut canvas = Canvas::new(0, 3);
+
+        assert_eq!(
+            vec![0, 1, 2],
+            canvas.rows().map(|(y, _)| y).collect::<Vec<_>>()
+        );
+        assert!(canvas
+            .rows_mut()
+            .all(|(_, colors, alphas)| colors.is_empty() && alphas.is_empty()));
+        assert_eq!(3, canvas.rows_mut().count());
+    }
+
+    #[test]
+    fn enumerate_pixels_canvas() {
+        let mut canvas = Canvas::new(3, 2);
+
+        for (x, y, pixel, alpha) in canvas.enumerate_pixels_mut() {
+            *pixel = Color::new(x as f64, y as f64, 0.0);
+            *alpha = 0.5;
+        }
+
+        let coordinates: Vec<(usize, usize)> =
+            canvas.enumerate_pixels().map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(
+            vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)],
+            coordinates
+        );
+        assert_eq!(Color::new(2.0, 1.0, 0.0), canvas.get_pixel(2, 1));
+        assert_eq!(&Color::new(1.0, 0.0, 0.0), &canvas.pixels()[1]);
+        assert_eq!(0.5, canvas.get_alpha(2, 1));
     }
 }
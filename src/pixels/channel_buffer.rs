@@ -0,0 +1,237 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::color_space::ColorSpace;
+use crate::pixels::rgb::Rgb;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelType {
+    Half,
+    Float,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+    name: String,
+    pixel_type: PixelType,
+    samples: Vec<f64>,
+}
+
+impl Channel {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn pixel_type(&self) -> PixelType {
+        self.pixel_type
+    }
+
+    pub fn samples(&self) -> &[f64] {
+        &self.samples
+    }
+}
+
+// Named planes of samples sharing one resolution, such as the colour, depth
+// and normal passes of a render. Colour channels are in the buffer's colour
+// space, Rec.709 unless set otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelBuffer {
+    width: usize,
+    height: usize,
+    channels: Vec<Channel>,
+    color_space: ColorSpace,
+}
+
+impl ChannelBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        ChannelBuffer {
+            width,
+            height,
+            channels: Vec::new(),
+            color_space: ColorSpace::default(),
+        }
+    }
+
+    pub fn from_canvas(canvas: &Canvas, pixel_type: PixelType) -> Self {
+        let mut buffer = ChannelBuffer::new(canvas.width(), canvas.height());
+        buffer.add_canvas("", canvas, pixel_type);
+
+        buffer
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    // Relabels the colour channels without converting them.
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+    }
+
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    pub fn channel(&self, name: &str) -> Option<&Channel> {
+        self.channels.iter().find(|channel| channel.name == name)
+    }
+
+    // Replaces any existing channel of the same name.
+    pub fn add_channel(&mut self, name: &str, pixel_type: PixelType, samples: Vec<f64>) {
+        assert_eq!(
+            self.width * self.height,
+            samples.len(),
+            "channel {} does not match the buffer resolution",
+            name
+        );
+
+        let channel = Channel {
+            name: name.to_string(),
+            pixel_type,
+            samples,
+        };
+
+        match self.channels.iter_mut().find(|other| other.name == name) {
+            Some(existing) => *existing = channel,
+            None => self.channels.push(channel),
+        }
+    }
+
+    // Adds the colour of every pixel as `R`, `G` and `B` channels and its
+    // coverage as `A`, prefixed with the layer name (`normal.R`) unless the
+    // layer is empty. Colours are stored premultiplied, as OpenEXR expects,
+    // and converted into the buffer's colour space.
+    pub fn add_canvas(&mut self, layer: &str, canvas: &Canvas, pixel_type: PixelType) {
+        assert_eq!(
+            (self.width, self.height),
+            (canvas.width(), canvas.height()),
+            "canvas does not match the buffer resolution"
+        );
+
+        let canvas = canvas.to_color_space(self.color_space);
+        let pixels = canvas.pixels();
+        let hues: [fn(&Color) -> f64; 3] = [Color::r, Color::g, Color::b];
+
+        for (suffix, hue) in ["R", "G", "B"].iter().zip(hues.iter()) {
+            let samples = pixels.iter().map(hue).collect();
+            self.add_channel(&layer_channel(layer, suffix), pixel_type, samples);
+        }
+        self.add_channel(
+            &layer_channel(layer, "A"),
+            pixel_type,
+            canvas.alphas().to_vec(),
+        );
+    }
+
+    pub fn to_canvas(&self, layer: &str) -> Option<Canvas> {
+        let r = self.channel(&layer_channel(layer, "R"))?;
+        let g = self.channel(&layer_channel(layer, "G"))?;
+        let b = self.channel(&layer_channel(layer, "B"))?;
+        // Layers without coverage are opaque.
+        let a = self.channel(&layer_channel(layer, "A"));
+
+        let mut canvas = Canvas::new(self.width, self.height);
+        canvas.set_color_space(self.color_space);
+        for i in 0..self.width * self.height {
+            let color = Color::new(r.samples[i], g.samples[i], b.samples[i]);
+            let alpha = a.map_or(1.0, |a| a.samples[i]);
+            canvas.set_pixel_with_alpha(i % self.width, i / self.width, color, alpha);
+        }
+
+        Some(canvas)
+    }
+}
+
+fn layer_channel(layer: &str, suffix: &str) -> String {
+    if layer.is_empty() {
+        suffix.to_string()
+    } else {
+        format!("{}.{}", layer, suffix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_channel_buffer() {
+        let buffer = ChannelBuffer::new(4, 2);
+
+        assert_eq!(4, buffer.width());
+        assert_eq!(2, buffer.height());
+        assert!(buffer.channels().is_empty());
+    }
+
+    #[test]
+    fn add_channel_replaces_existing() {
+        let mut buffer = ChannelBuffer::new(2, 1);
+        buffer.add_channel("Z", PixelType::Float, vec![1.0, 2.0]);
+        buffer.add_channel("Z", PixelType::Half, vec![3.0, 4.0]);
+
+        assert_eq!(1, buffer.channels().len());
+        let depth = buffer.channel("Z").unwrap();
+        assert_eq!("Z", depth.name());
+        assert_eq!(PixelType::Half, depth.pixel_type());
+        assert_eq!(&[3.0, 4.0], depth.samples());
+        assert_eq!(None, buffer.channel("A"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn add_channel_wrong_resolution() {
+        let mut buffer = ChannelBuffer::new(2, 2);
+        buffer.add_channel("Z", PixelType::Float, vec![1.0]);
+    }
+
+    #[test]
+    fn canvas_layers_round_trip() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.set_pixel(1, 0, Color::new(0.5, 2.0, -1.0));
+
+        let mut buffer = ChannelBuffer::from_canvas(&canvas, PixelType::Float);
+        buffer.add_canvas("normal", &canvas, PixelType::Half);
+
+        let names: Vec<&str> = buffer.channels().iter().map(Channel::name).collect();
+        assert_eq!(
+            vec!["R", "G", "B", "A", "normal.R", "normal.G", "normal.B", "normal.A"],
+            names
+        );
+        assert_eq!(
+            &[0.0, 1.0, 0.0, 0.0],
+            buffer.channel("A").unwrap().samples()
+        );
+        assert_eq!(
+            &[0.0, 2.0, 0.0, 0.0],
+            buffer.channel("G").unwrap().samples()
+        );
+
+        assert_eq!(canvas.pixels(), buffer.to_canvas("").unwrap().pixels());
+        assert_eq!(
+            canvas.pixels(),
+            buffer.to_canvas("normal").unwrap().pixels()
+        );
+        assert_eq!(
+            canvas.alphas(),
+            buffer.to_canvas("normal").unwrap().alphas()
+        );
+        assert!(buffer.to_canvas("albedo").is_none());
+    }
+
+    #[test]
+    fn canvas_without_alpha_is_opaque() {
+        let mut buffer = ChannelBuffer::new(1, 1);
+        for name in ["R", "G", "B"] {
+            buffer.add_channel(name, PixelType::Float, vec![0.5]);
+        }
+
+        assert_eq!(1.0, buffer.to_canvas("").unwrap().get_alpha(0, 0));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::approx::{ApproxEq, Margin};
 use crate::pixels::rgb::Rgb;
 
 #[derive(Clone, Copy, Debug)]
@@ -21,9 +22,9 @@ impl Color {
     }
 }
 
-fn into_u8(hue: f64) -> u8 {
-    let converted = (hue * 255.0) as u8;
-    converted.max(0).min(255)
+pub(crate) fn into_u8(hue: f64) -> u8 {
+    // Float to integer casts saturate, so out of range hues clamp to 0 or 255.
+    (hue * 255.0) as u8
 }
 
 impl Default for Color {
@@ -40,6 +41,20 @@ impl PartialEq for Color {
     }
 }
 
+impl ApproxEq for Color {
+    fn approx_eq(&self, other: &Self, margin: Margin) -> bool {
+        self.r().approx_eq(&other.r(), margin)
+            && self.g().approx_eq(&other.g(), margin)
+            && self.b().approx_eq(&other.b(), margin)
+    }
+
+    fn exact_eq(&self, other: &Self) -> bool {
+        self.r().exact_eq(&other.r())
+            && self.g().exact_eq(&other.g())
+            && self.b().exact_eq(&other.b())
+    }
+}
+
 impl Rgb for Color {
     fn r(&self) -> f64 {
         self.r
@@ -141,4 +156,13 @@ mod tests {
         let desired_result = Color::new(0.4, 0.6, 0.8);
         assert_eq!(desired_result, color * 2.0);
     }
+
+    #[test]
+    fn approx_eq_color() {
+        let color = Color::new(0.9, 0.6, 0.75) * 3.0;
+
+        crate::assert_approx_eq!(Color::new(2.7, 1.8, 2.25), color);
+        crate::assert_approx_ne!(Color::new(2.7, 1.8, 2.3), color, Margin::Absolute(0.01));
+        assert!(color.exact_eq(&color));
+    }
 }
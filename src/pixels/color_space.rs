@@ -0,0 +1,469 @@
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::rgb::Rgb;
+
+pub type Matrix3 = [[f64; 3]; 3];
+
+// xy chromaticities of the standard white points.
+pub const D65: (f64, f64) = (0.3127, 0.3290);
+pub const ACES_WHITE: (f64, f64) = (0.32168, 0.33767);
+
+// Cone response domain of the Bradford chromatic adaptation transform.
+const BRADFORD: Matrix3 = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+// Linear RGB working spaces, defined by the chromaticities of their primaries
+// and white point. Rec.709 shares its primaries with sRGB and is the space
+// the image writers output unless asked for another.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    #[default]
+    Rec709,
+    Rec2020,
+    AcesCg,
+    DisplayP3,
+}
+
+impl ColorSpace {
+    pub const ALL: [ColorSpace; 4] = [
+        ColorSpace::Rec709,
+        ColorSpace::Rec2020,
+        ColorSpace::AcesCg,
+        ColorSpace::DisplayP3,
+    ];
+
+    // The space with these chromaticities, if it is one of ours. Files store
+    // them rounded, so they only have to agree to three decimals.
+    pub fn from_chromaticities(
+        primaries: [(f64, f64); 3],
+        white_point: (f64, f64),
+    ) -> Option<ColorSpace> {
+        let close =
+            |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3;
+
+        ColorSpace::ALL.into_iter().find(|space| {
+            close(space.white_point(), white_point)
+                && space
+                    .primaries()
+                    .iter()
+                    .zip(primaries)
+                    .all(|(a, b)| close(*a, b))
+        })
+    }
+
+    pub fn primaries(&self) -> [(f64, f64); 3] {
+        match self {
+            ColorSpace::Rec709 => [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06)],
+            ColorSpace::Rec2020 => [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046)],
+            ColorSpace::AcesCg => [(0.713, 0.293), (0.165, 0.830), (0.128, 0.044)],
+            ColorSpace::DisplayP3 => [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060)],
+        }
+    }
+
+    pub fn white_point(&self) -> (f64, f64) {
+        match self {
+            ColorSpace::AcesCg => ACES_WHITE,
+            _ => D65,
+        }
+    }
+
+    // RGB to CIE XYZ relative to the space's own white point.
+    pub fn to_xyz(&self) -> Matrix3 {
+        self.matrices()[0]
+    }
+
+    pub fn from_xyz(&self) -> Matrix3 {
+        self.matrices()[1]
+    }
+
+    // Both XYZ matrices, worked out once for every space as luminance and
+    // L*a*b* need them for each pixel.
+    fn matrices(&self) -> &'static [Matrix3; 2] {
+        static MATRICES: OnceLock<[[Matrix3; 2]; 4]> = OnceLock::new();
+
+        let matrices = MATRICES.get_or_init(|| {
+            ColorSpace::ALL.map(|space| {
+                let to_xyz = space.derive_to_xyz();
+                [to_xyz, invert(&to_xyz)]
+            })
+        });
+        let index = ColorSpace::ALL.iter().position(|space| space == self);
+        &matrices[index.unwrap()]
+    }
+
+    fn derive_to_xyz(&self) -> Matrix3 {
+        let primaries = self.primaries().map(xy_to_xyz);
+        let columns = [
+            [primaries[0][0], primaries[1][0], primaries[2][0]],
+            [primaries[0][1], primaries[1][1], primaries[2][1]],
+            [primaries[0][2], primaries[1][2], primaries[2][2]],
+        ];
+
+        // Scale each primary so that equal RGB values produce the white point.
+        let scale = transform(&invert(&columns), xy_to_xyz(self.white_point()));
+        columns.map(|row| [row[0] * scale[0], row[1] * scale[1], row[2] * scale[2]])
+    }
+
+    // Maps RGB values in this space to the same colour in `target`, adapting
+    // between differing white points with Bradford.
+    pub fn conversion(&self, target: ColorSpace) -> Matrix3 {
+        let adaptation = bradford(self.white_point(), target.white_point());
+        multiply(&target.from_xyz(), &multiply(&adaptation, &self.to_xyz()))
+    }
+
+    pub fn convert(&self, color: Color, target: ColorSpace) -> Color {
+        apply(&self.conversion(target), color)
+    }
+
+    // The relative luminance of a colour in this space, one for its white.
+    pub fn luminance(&self, color: Color) -> f64 {
+        let [r, g, b] = self.to_xyz()[1];
+        r * color.r() + g * color.g() + b * color.b()
+    }
+
+    // CIE L*a*b* relative to the space's own white point, with a lightness
+    // from 0 to 100.
+    pub fn to_lab(&self, color: Color) -> (f64, f64, f64) {
+        let [x, y, z] = transform(&self.to_xyz(), [color.r(), color.g(), color.b()]);
+        let [white_x, white_y, white_z] = xy_to_xyz(self.white_point());
+
+        let forward = |t: f64| {
+            if t > 216.0 / 24389.0 {
+                t.cbrt()
+            } else {
+                841.0 / 108.0 * t + 4.0 / 29.0
+            }
+        };
+        let (fx, fy, fz) = (
+            forward(x / white_x),
+            forward(y / white_y),
+            forward(z / white_z),
+        );
+
+        (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+    }
+
+    pub fn from_lab(&self, lightness: f64, a: f64, b: f64) -> Color {
+        let fy = (lightness + 16.0) / 116.0;
+        let fx = fy + a / 500.0;
+        let fz = fy - b / 200.0;
+
+        let inverse = |f: f64| {
+            if f > 6.0 / 29.0 {
+                f.powi(3)
+            } else {
+                108.0 / 841.0 * (f - 4.0 / 29.0)
+            }
+        };
+        let [white_x, white_y, white_z] = xy_to_xyz(self.white_point());
+
+        apply(
+            &self.from_xyz(),
+            Color::new(
+                inverse(fx) * white_x,
+                inverse(fy) * white_y,
+                inverse(fz) * white_z,
+            ),
+        )
+    }
+}
+
+// Bradford transform from XYZ relative to one white point to XYZ relative to
+// another, both given as xy chromaticities.
+pub fn bradford(source: (f64, f64), target: (f64, f64)) -> Matrix3 {
+    let source_cone = transform(&BRADFORD, xy_to_xyz(source));
+    let target_cone = transform(&BRADFORD, xy_to_xyz(target));
+    let gain = [
+        [target_cone[0] / source_cone[0], 0.0, 0.0],
+        [0.0, target_cone[1] / source_cone[1], 0.0],
+        [0.0, 0.0, target_cone[2] / source_cone[2]],
+    ];
+
+    multiply(&invert(&BRADFORD), &multiply(&gain, &BRADFORD))
+}
+
+impl Canvas {
+    // The same image with its pixels converted into `color_space`, borrowed
+    // when it is already there.
+    pub fn to_color_space(&self, color_space: ColorSpace) -> Cow<'_, Canvas> {
+        if color_space == self.color_space() {
+            return Cow::Borrowed(self);
+        }
+
+        let mut converted = self.clone();
+        let matrix = self.color_space().conversion(color_space);
+        converted
+            .pixels_mut()
+            .for_each(|(color, _)| *color = apply(&matrix, *color));
+        converted.set_color_space(color_space);
+
+        Cow::Owned(converted)
+    }
+}
+
+// XYZ with a luminance of one for an xy chromaticity.
+pub(crate) fn xy_to_xyz((x, y): (f64, f64)) -> [f64; 3] {
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+fn apply(matrix: &Matrix3, color: Color) -> Color {
+    let [r, g, b] = transform(matrix, [color.r(), color.g(), color.b()]);
+    Color::new(r, g, b)
+}
+
+pub(crate) fn transform(matrix: &Matrix3, vector: [f64; 3]) -> [f64; 3] {
+    matrix.map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2])
+}
+
+fn multiply(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut product = [[0.0; 3]; 3];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    product
+}
+
+fn invert(m: &Matrix3) -> Matrix3 {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let adjugate = [
+        [
+            cofactor(1, 2, 1, 2),
+            -cofactor(0, 2, 1, 2),
+            cofactor(0, 1, 1, 2),
+        ],
+        [
+            -cofactor(1, 2, 0, 2),
+            cofactor(0, 2, 0, 2),
+            -cofactor(0, 1, 0, 2),
+        ],
+        [
+            cofactor(1, 2, 0, 1),
+            -cofactor(0, 2, 0, 1),
+            cofactor(0, 1, 0, 1),
+        ],
+    ];
+    let determinant =
+        m[0][0] * adjugate[0][0] + m[0][1] * adjugate[1][0] + m[0][2] * adjugate[2][0];
+
+    adjugate.map(|row| row.map(|value| value / determinant))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+    use crate::pixels::png::PNG;
+
+    #[test]
+    fn rec709_to_xyz() {
+        let (x, y, z) = Color::new(0.2, 0.5, 0.9).to_xyz();
+        let color = apply(&ColorSpace::Rec709.to_xyz(), Color::new(0.2, 0.5, 0.9));
+
+        assert_approx_eq!(Color::new(x, y, z), color);
+    }
+
+    #[test]
+    fn invert_matrix() {
+        let matrix = ColorSpace::Rec2020.to_xyz();
+        let identity = multiply(&matrix, &invert(&matrix));
+
+        assert_approx_eq!(
+            [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            identity
+        );
+    }
+
+    #[test]
+    fn rec2020_to_rec709() {
+        let desired_result = [
+            [1.6605, -0.5876, -0.0728],
+            [-0.1246, 1.1329, -0.0083],
+            [-0.0182, -0.1006, 1.1187],
+        ];
+
+        assert_approx_eq!(
+            desired_result,
+            ColorSpace::Rec2020.conversion(ColorSpace::Rec709),
+            Margin::Absolute(1e-4)
+        );
+    }
+
+    #[test]
+    fn display_p3_to_rec709() {
+        let desired_result = [
+            [1.2249, -0.2249, 0.0],
+            [-0.0421, 1.0421, 0.0],
+            [-0.0196, -0.0786, 1.0983],
+        ];
+
+        assert_approx_eq!(
+            desired_result,
+            ColorSpace::DisplayP3.conversion(ColorSpace::Rec709),
+            Margin::Absolute(1e-4)
+        );
+    }
+
+    #[test]
+    fn aces_cg_to_rec709() {
+        let desired_result = [
+            [1.70505, -0.62179, -0.08326],
+            [-0.13026, 1.14080, -0.01055],
+            [-0.02400, -0.12897, 1.15297],
+        ];
+
+        assert_approx_eq!(
+            desired_result,
+            ColorSpace::AcesCg.conversion(ColorSpace::Rec709),
+            Margin::Absolute(1e-4)
+        );
+    }
+
+    #[test]
+    fn convert_preserves_white() {
+        let white = Color::new(1.0, 1.0, 1.0);
+
+        for space in [
+            ColorSpace::Rec2020,
+            ColorSpace::AcesCg,
+            ColorSpace::DisplayP3,
+        ] {
+            assert_approx_eq!(white, space.convert(white, ColorSpace::Rec709));
+            assert_approx_eq!(white, ColorSpace::Rec709.convert(white, space));
+        }
+    }
+
+    #[test]
+    fn convert_round_trip() {
+        let color = Color::new(0.8, 0.3, 0.05);
+        let converted = ColorSpace::Rec709.convert(color, ColorSpace::AcesCg);
+
+        assert_approx_eq!(
+            color,
+            ColorSpace::AcesCg.convert(converted, ColorSpace::Rec709)
+        );
+    }
+
+    #[test]
+    fn bradford_same_white() {
+        assert_approx_eq!(
+            [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            bradford(D65, D65)
+        );
+    }
+
+    #[test]
+    fn canvas_to_color_space() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set_color_space(ColorSpace::Rec2020);
+        canvas.set_pixel_with_alpha(0, 0, Color::new(0.5, 0.0, 0.0), 0.5);
+
+        let converted = canvas.to_color_space(ColorSpace::Rec709);
+
+        assert_eq!(ColorSpace::Rec709, converted.color_space());
+        assert_approx_eq!(
+            Color::new(0.5 * 1.6605, 0.5 * -0.1246, 0.5 * -0.0182),
+            converted.get_pixel(0, 0),
+            Margin::Absolute(1e-4)
+        );
+        assert_eq!(0.5, converted.get_alpha(0, 0));
+    }
+
+    #[test]
+    fn canvas_to_same_color_space_borrows() {
+        let canvas = Canvas::new(2, 2);
+
+        assert!(matches!(
+            canvas.to_color_space(ColorSpace::Rec709),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            canvas.to_color_space(ColorSpace::AcesCg),
+            Cow::Owned(_)
+        ));
+    }
+
+    #[test]
+    fn luminance_per_color_space() {
+        for space in ColorSpace::ALL {
+            assert_approx_eq!(1.0, space.luminance(Color::new(1.0, 1.0, 1.0)));
+        }
+
+        let red = Color::new(1.0, 0.0, 0.0);
+        assert_approx_eq!(
+            0.2126,
+            ColorSpace::Rec709.luminance(red),
+            Margin::Absolute(1e-4)
+        );
+        assert_approx_eq!(
+            0.2627,
+            ColorSpace::Rec2020.luminance(red),
+            Margin::Absolute(1e-4)
+        );
+    }
+
+    #[test]
+    fn lab_per_color_space() {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let color = Color::new(0.3, 0.6, 0.1);
+
+        for space in ColorSpace::ALL {
+            let (lightness, a, b) = space.to_lab(white);
+            assert_approx_eq!([100.0, 0.0, 0.0], [lightness, a, b], Margin::Absolute(1e-9));
+
+            let (lightness, a, b) = space.to_lab(color);
+            assert_approx_eq!(color, space.from_lab(lightness, a, b));
+        }
+
+        let converted = ColorSpace::Rec709.convert(color, ColorSpace::DisplayP3);
+        let (lightness, _, _) = ColorSpace::DisplayP3.to_lab(converted);
+        assert_approx_eq!(color.to_lab().0, lightness);
+    }
+
+    #[test]
+    fn color_space_from_chromaticities() {
+        for space in ColorSpace::ALL {
+            assert_eq!(
+                Some(space),
+                ColorSpace::from_chromaticities(space.primaries(), space.white_point())
+            );
+        }
+
+        assert_eq!(
+            Some(ColorSpace::DisplayP3),
+            ColorSpace::from_chromaticities(
+                [(0.6800, 0.3200), (0.2650, 0.6900), (0.1500, 0.0600)],
+                (0.31271, 0.32902)
+            )
+        );
+        assert_eq!(
+            None,
+            ColorSpace::from_chromaticities(ColorSpace::Rec709.primaries(), ACES_WHITE)
+        );
+    }
+
+    #[test]
+    fn writers_output_rec709() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_color_space(ColorSpace::AcesCg);
+        canvas.set_pixel(0, 0, Color::new(0.2, 0.4, 0.1));
+        canvas.set_pixel(1, 0, Color::new(0.05, 0.1, 0.6));
+
+        let converted = canvas.to_color_space(ColorSpace::Rec709);
+
+        let mut relabeled = canvas.clone();
+        relabeled.set_color_space(ColorSpace::Rec709);
+
+        assert_eq!(PNG::new(&converted).get(), PNG::new(&canvas).get());
+        assert_ne!(PNG::new(&relabeled).get(), PNG::new(&canvas).get());
+    }
+}
@@ -0,0 +1,423 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::rgb::Rgb;
+
+// HyAB distance between pure green and pure blue, the largest colour
+// difference between displayable colours.
+const MAX_HYAB: f64 = 308.0;
+
+// Limits a comparison has to stay within. The default accepts anything.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    max_error: f64,
+    min_psnr: f64,
+    min_ssim: f64,
+    max_flip: f64,
+}
+
+impl Tolerance {
+    pub fn max_error(self, max_error: f64) -> Self {
+        Tolerance { max_error, ..self }
+    }
+
+    pub fn min_psnr(self, min_psnr: f64) -> Self {
+        Tolerance { min_psnr, ..self }
+    }
+
+    pub fn min_ssim(self, min_ssim: f64) -> Self {
+        Tolerance { min_ssim, ..self }
+    }
+
+    pub fn max_flip(self, max_flip: f64) -> Self {
+        Tolerance { max_flip, ..self }
+    }
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance {
+            max_error: f64::INFINITY,
+            min_psnr: f64::NEG_INFINITY,
+            min_ssim: f64::NEG_INFINITY,
+            max_flip: f64::INFINITY,
+        }
+    }
+}
+
+// Error metrics between a test canvas and a reference. Errors are taken over
+// the colour channels with a peak value of 1, alpha is not compared.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison {
+    max_error: f64,
+    mean_error: f64,
+    rmse: f64,
+    psnr: f64,
+    ssim: f64,
+    flip: f64,
+}
+
+impl Comparison {
+    pub fn new(reference: &Canvas, test: &Canvas) -> Comparison {
+        assert_same_size(reference, test);
+        let test = &test.to_color_space(reference.color_space());
+
+        let mut max_error: f64 = 0.0;
+        let mut error_sum = 0.0;
+        let mut squared_error_sum = 0.0;
+        for (expected, actual) in reference.pixels().iter().zip(test.pixels()) {
+            for error in channels(*expected - *actual) {
+                max_error = max_error.max(error.abs());
+                error_sum += error.abs();
+                squared_error_sum += error * error;
+            }
+        }
+
+        let samples = (reference.pixels().len() * 3).max(1) as f64;
+        let rmse = (squared_error_sum / samples).sqrt();
+        let psnr = if rmse == 0.0 {
+            f64::INFINITY
+        } else {
+            -20.0 * rmse.log10()
+        };
+
+        Comparison {
+            max_error,
+            mean_error: error_sum / samples,
+            rmse,
+            psnr,
+            ssim: ssim(reference, test),
+            flip: flip(reference, test),
+        }
+    }
+
+    pub fn max_error(&self) -> f64 {
+        self.max_error
+    }
+
+    pub fn mean_error(&self) -> f64 {
+        self.mean_error
+    }
+
+    pub fn rmse(&self) -> f64 {
+        self.rmse
+    }
+
+    // Peak signal to noise ratio in decibels, infinite for identical canvases.
+    pub fn psnr(&self) -> f64 {
+        self.psnr
+    }
+
+    pub fn ssim(&self) -> f64 {
+        self.ssim
+    }
+
+    pub fn flip(&self) -> f64 {
+        self.flip
+    }
+
+    pub fn within(&self, tolerance: &Tolerance) -> bool {
+        self.max_error <= tolerance.max_error
+            && self.psnr >= tolerance.min_psnr
+            && self.ssim >= tolerance.min_ssim
+            && self.flip <= tolerance.max_flip
+    }
+}
+
+impl Canvas {
+    // Absolute per channel difference, as an opaque canvas.
+    pub fn diff(&self, other: &Canvas) -> Canvas {
+        assert_same_size(self, other);
+        let other = &other.to_color_space(self.color_space());
+
+        let mut diff = Canvas::new(self.width(), self.height());
+        diff.set_color_space(self.color_space());
+        for (x, y, color) in self.enumerate_pixels() {
+            let [r, g, b] = channels(*color - other.get_pixel(x, y));
+            diff.set_pixel(x, y, Color::new(r.abs(), g.abs(), b.abs()));
+        }
+
+        diff
+    }
+
+    pub fn compare(&self, reference: &Canvas) -> Comparison {
+        Comparison::new(reference, self)
+    }
+}
+
+fn assert_same_size(a: &Canvas, b: &Canvas) {
+    assert_eq!(
+        (a.width(), a.height()),
+        (b.width(), b.height()),
+        "compared canvases must have the same size"
+    );
+}
+
+fn channels(color: Color) -> [f64; 3] {
+    [color.r(), color.g(), color.b()]
+}
+
+// Mean structural similarity of the luminance, using the usual 11x11 Gaussian
+// window with a standard deviation of 1.5 pixels.
+fn ssim(reference: &Canvas, test: &Canvas) -> f64 {
+    let (width, height) = (reference.width(), reference.height());
+    if width == 0 || height == 0 {
+        return 1.0;
+    }
+
+    let luminance = |canvas: &Canvas| -> Vec<f64> {
+        let space = canvas.color_space();
+        canvas
+            .pixels()
+            .iter()
+            .map(|color| space.luminance(*color))
+            .collect()
+    };
+    let x = luminance(reference);
+    let y = luminance(test);
+    let product =
+        |a: &[f64], b: &[f64]| -> Vec<f64> { a.iter().zip(b).map(|(a, b)| a * b).collect() };
+
+    let blur = |values: &[f64]| gaussian_blur(values, width, height, 1.5, 5);
+    let mean_x = blur(&x);
+    let mean_y = blur(&y);
+    let mean_xx = blur(&product(&x, &x));
+    let mean_yy = blur(&product(&y, &y));
+    let mean_xy = blur(&product(&x, &y));
+
+    let (c1, c2) = (0.01_f64.powi(2), 0.03_f64.powi(2));
+    let total: f64 = (0..width * height)
+        .map(|i| {
+            let (mx, my) = (mean_x[i], mean_y[i]);
+            let variance_x = mean_xx[i] - mx * mx;
+            let variance_y = mean_yy[i] - my * my;
+            let covariance = mean_xy[i] - mx * my;
+
+            ((2.0 * mx * my + c1) * (2.0 * covariance + c2))
+                / ((mx * mx + my * my + c1) * (variance_x + variance_y + c2))
+        })
+        .sum();
+
+    total / (width * height) as f64
+}
+
+// A simplified take on NVIDIA's FLIP. Both canvases are slightly blurred in
+// L*a*b*, the colour error is their HyAB distance, and it is amplified where
+// the luminance edges differ. The result is the mean error, from 0 to 1.
+fn flip(reference: &Canvas, test: &Canvas) -> f64 {
+    let (width, height) = (reference.width(), reference.height());
+    if width == 0 || height == 0 {
+        return 0.0;
+    }
+
+    let filtered = |canvas: &Canvas| -> [Vec<f64>; 3] {
+        let space = canvas.color_space();
+        let lab: Vec<(f64, f64, f64)> = canvas
+            .pixels()
+            .iter()
+            .map(|color| space.to_lab(*color))
+            .collect();
+        [0, 1, 2].map(|channel| {
+            let values: Vec<f64> = lab
+                .iter()
+                .map(|&(lightness, a, b)| [lightness, a, b][channel])
+                .collect();
+            gaussian_blur(&values, width, height, 1.0, 2)
+        })
+    };
+    let reference_lab = filtered(reference);
+    let test_lab = filtered(test);
+
+    let reference_edges = edges(&reference_lab[0], width, height);
+    let test_edges = edges(&test_lab[0], width, height);
+
+    let total: f64 = (0..width * height)
+        .map(|i| {
+            let lightness = (reference_lab[0][i] - test_lab[0][i]).abs();
+            let chroma =
+                (reference_lab[1][i] - test_lab[1][i]).hypot(reference_lab[2][i] - test_lab[2][i]);
+            let color_error = ((lightness + chroma) / MAX_HYAB).min(1.0);
+            let feature_error = (reference_edges[i] - test_edges[i]).abs().min(1.0);
+
+            color_error.powf(1.0 - feature_error)
+        })
+        .sum();
+
+    total / (width * height) as f64
+}
+
+// Sobel gradient magnitude of the lightness, scaled so a step from black to
+// white measures one.
+fn edges(lightness: &[f64], width: usize, height: usize) -> Vec<f64> {
+    let at = |x: isize, y: isize| {
+        let x = x.clamp(0, width as isize - 1) as usize;
+        let y = y.clamp(0, height as isize - 1) as usize;
+        lightness[x + y * width] / 100.0
+    };
+
+    (0..width * height)
+        .map(|i| {
+            let (x, y) = ((i % width) as isize, (i / width) as isize);
+            let dx = (at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1))
+                - (at(x - 1, y - 1) + 2.0 * at(x - 1, y) + at(x - 1, y + 1));
+            let dy = (at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1))
+                - (at(x - 1, y - 1) + 2.0 * at(x, y - 1) + at(x + 1, y - 1));
+
+            dx.hypot(dy) / 4.0
+        })
+        .collect()
+}
+
+// Separable Gaussian blur that renormalises the kernel at the borders.
+fn gaussian_blur(
+    values: &[f64],
+    width: usize,
+    height: usize,
+    sigma: f64,
+    radius: usize,
+) -> Vec<f64> {
+    let kernel: Vec<f64> = (0..=2 * radius)
+        .map(|i| {
+            let offset = i as f64 - radius as f64;
+            (-offset * offset / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+
+    let pass = |values: &[f64], horizontal: bool| -> Vec<f64> {
+        (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                let (position, len) = if horizontal { (x, width) } else { (y, height) };
+
+                let mut sum = 0.0;
+                let mut weight_sum = 0.0;
+                for (k, weight) in kernel.iter().enumerate() {
+                    let sample = position as isize + k as isize - radius as isize;
+                    if sample < 0 || sample >= len as isize {
+                        continue;
+                    }
+                    let index = if horizontal {
+                        sample as usize + y * width
+                    } else {
+                        x + sample as usize * width
+                    };
+                    sum += values[index] * weight;
+                    weight_sum += weight;
+                }
+
+                sum / weight_sum
+            })
+            .collect()
+    };
+
+    pass(&pass(values, true), false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A horizontal gradient with a bright square in the middle.
+    fn reference() -> Canvas {
+        let mut canvas = Canvas::new(16, 16);
+        for y in 0..16 {
+            for x in 0..16 {
+                let value = if (4..12).contains(&x) && (4..12).contains(&y) {
+                    0.9
+                } else {
+                    x as f64 / 32.0
+                };
+                canvas.set_pixel(x, y, Color::new(value, value, value));
+            }
+        }
+        canvas
+    }
+
+    fn offset(canvas: &Canvas, amount: f64) -> Canvas {
+        let mut offset = canvas.clone();
+        offset
+            .pixels_mut()
+            .for_each(|(color, _)| *color = *color + Color::new(amount, amount, amount));
+        offset
+    }
+
+    #[test]
+    fn diff_canvas() {
+        let mut a = Canvas::new(2, 1);
+        a.set_pixel(0, 0, Color::new(1.0, 0.5, 0.0));
+        let mut b = Canvas::new(2, 1);
+        b.set_pixel(0, 0, Color::new(0.5, 1.0, 0.0));
+
+        let diff = a.diff(&b);
+
+        assert_eq!(Color::new(0.5, 0.5, 0.0), diff.get_pixel(0, 0));
+        assert_eq!(Color::default(), diff.get_pixel(1, 0));
+        assert_eq!(1.0, diff.get_alpha(1, 0));
+    }
+
+    #[test]
+    fn compare_identical() {
+        let comparison = reference().compare(&reference());
+
+        assert_eq!(0.0, comparison.max_error());
+        assert_eq!(0.0, comparison.mean_error());
+        assert_eq!(0.0, comparison.rmse());
+        assert_eq!(f64::INFINITY, comparison.psnr());
+        assert_eq!(1.0, comparison.ssim());
+        assert_eq!(0.0, comparison.flip());
+    }
+
+    #[test]
+    fn compare_offset() {
+        let comparison = offset(&reference(), 0.1).compare(&reference());
+
+        assert!((comparison.max_error() - 0.1).abs() < 1e-12);
+        assert!((comparison.mean_error() - 0.1).abs() < 1e-12);
+        assert!((comparison.rmse() - 0.1).abs() < 1e-12);
+        assert!((comparison.psnr() - 20.0).abs() < 1e-9);
+        assert!(comparison.ssim() < 1.0);
+        assert!(comparison.flip() > 0.0);
+    }
+
+    #[test]
+    fn compare_orders_errors() {
+        let small = offset(&reference(), 0.02).compare(&reference());
+        let large = offset(&reference(), 0.2).compare(&reference());
+
+        assert!(small.psnr() > large.psnr());
+        assert!(small.ssim() > large.ssim());
+        assert!(small.flip() < large.flip());
+    }
+
+    #[test]
+    fn compare_structure() {
+        // Moving the square keeps the mean but breaks the structure.
+        let mut shifted = Canvas::new(16, 16);
+        let reference = reference();
+        for (x, y, color) in reference.enumerate_pixels() {
+            shifted.set_pixel((x + 3) % 16, y, *color);
+        }
+
+        let comparison = shifted.compare(&reference);
+
+        assert!(comparison.ssim() < 0.9);
+        assert!(comparison.flip() > offset(&reference, 0.02).compare(&reference).flip());
+    }
+
+    #[test]
+    fn comparison_within_tolerance() {
+        let comparison = offset(&reference(), 0.01).compare(&reference());
+
+        assert!(comparison.within(&Tolerance::default()));
+        assert!(comparison.within(&Tolerance::default().min_psnr(35.0).max_error(0.02)));
+        assert!(!comparison.within(&Tolerance::default().min_psnr(45.0)));
+        assert!(!comparison.within(&Tolerance::default().max_error(0.005)));
+        assert!(!comparison.within(&Tolerance::default().min_ssim(1.0)));
+        assert!(!comparison.within(&Tolerance::default().max_flip(0.0)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn compare_mismatched_sizes() {
+        Canvas::new(2, 2).compare(&Canvas::new(3, 2));
+    }
+}
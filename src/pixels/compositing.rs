@@ -0,0 +1,160 @@
+use crate::pixels::canvas::Canvas;
+
+// Porter-Duff operators, placing the source canvas relative to the destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Over,
+    In,
+    Out,
+    Atop,
+    Xor,
+}
+
+impl Operator {
+    // Fractions of the source and the destination that remain visible.
+    fn factors(&self, source_alpha: f64, destination_alpha: f64) -> (f64, f64) {
+        match self {
+            Operator::Over => (1.0, 1.0 - source_alpha),
+            Operator::In => (destination_alpha, 0.0),
+            Operator::Out => (1.0 - destination_alpha, 0.0),
+            Operator::Atop => (destination_alpha, 1.0 - source_alpha),
+            Operator::Xor => (1.0 - destination_alpha, 1.0 - source_alpha),
+        }
+    }
+
+    pub fn apply(&self, source: &Canvas, destination: &Canvas) -> Canvas {
+        assert_eq!(
+            (source.width(), source.height()),
+            (destination.width(), destination.height()),
+            "composited canvases must have the same size"
+        );
+        let destination = &destination.to_color_space(source.color_space());
+
+        let mut composite = Canvas::new(source.width(), source.height());
+        composite.set_color_space(source.color_space());
+
+        for y in 0..source.height() {
+            for x in 0..source.width() {
+                let source_alpha = source.get_alpha(x, y);
+                let destination_alpha = destination.get_alpha(x, y);
+                let (source_factor, destination_factor) =
+                    self.factors(source_alpha, destination_alpha);
+
+                // Colours are premultiplied, so they blend exactly like the alpha.
+                let color = source.get_pixel(x, y) * source_factor
+                    + destination.get_pixel(x, y) * destination_factor;
+                let alpha = source_alpha * source_factor + destination_alpha * destination_factor;
+
+                composite.set_pixel_with_alpha(x, y, color, alpha);
+            }
+        }
+
+        composite
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixels::color::Color;
+
+    // A red source covering the left column and a half transparent blue
+    // destination covering the top row of a 2x2 canvas.
+    fn layers() -> (Canvas, Canvas) {
+        let mut source = Canvas::new(2, 2);
+        source.set_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        source.set_pixel(0, 1, Color::new(1.0, 0.0, 0.0));
+
+        let mut destination = Canvas::new(2, 2);
+        destination.set_pixel_with_alpha(0, 0, Color::new(0.0, 0.0, 0.5), 0.5);
+        destination.set_pixel_with_alpha(1, 0, Color::new(0.0, 0.0, 0.5), 0.5);
+
+        (source, destination)
+    }
+
+    fn pixels(canvas: &Canvas) -> Vec<(Color, f64)> {
+        let mut pixels = Vec::new();
+        for y in 0..canvas.height() {
+            for x in 0..canvas.width() {
+                pixels.push((canvas.get_pixel(x, y), canvas.get_alpha(x, y)));
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn over_operator() {
+        let (source, destination) = layers();
+        let composite = Operator::Over.apply(&source, &destination);
+
+        let desired_result = vec![
+            (Color::new(1.0, 0.0, 0.0), 1.0),
+            (Color::new(0.0, 0.0, 0.5), 0.5),
+            (Color::new(1.0, 0.0, 0.0), 1.0),
+            (Color::default(), 0.0),
+        ];
+        assert_eq!(desired_result, pixels(&composite));
+    }
+
+    #[test]
+    fn in_operator() {
+        let (source, destination) = layers();
+        let composite = Operator::In.apply(&source, &destination);
+
+        let desired_result = vec![
+            (Color::new(0.5, 0.0, 0.0), 0.5),
+            (Color::default(), 0.0),
+            (Color::default(), 0.0),
+            (Color::default(), 0.0),
+        ];
+        assert_eq!(desired_result, pixels(&composite));
+    }
+
+    #[test]
+    fn out_operator() {
+        let (source, destination) = layers();
+        let composite = Operator::Out.apply(&source, &destination);
+
+        let desired_result = vec![
+            (Color::new(0.5, 0.0, 0.0), 0.5),
+            (Color::default(), 0.0),
+            (Color::new(1.0, 0.0, 0.0), 1.0),
+            (Color::default(), 0.0),
+        ];
+        assert_eq!(desired_result, pixels(&composite));
+    }
+
+    #[test]
+    fn atop_operator() {
+        let (source, destination) = layers();
+        let composite = Operator::Atop.apply(&source, &destination);
+
+        let desired_result = vec![
+            (Color::new(0.5, 0.0, 0.0), 0.5),
+            (Color::new(0.0, 0.0, 0.5), 0.5),
+            (Color::default(), 0.0),
+            (Color::default(), 0.0),
+        ];
+        assert_eq!(desired_result, pixels(&composite));
+    }
+
+    #[test]
+    fn xor_operator() {
+        let (source, destination) = layers();
+        let composite = Operator::Xor.apply(&source, &destination);
+
+        let desired_result = vec![
+            (Color::new(0.5, 0.0, 0.0), 0.5),
+            (Color::new(0.0, 0.0, 0.5), 0.5),
+            (Color::new(1.0, 0.0, 0.0), 1.0),
+            (Color::default(), 0.0),
+        ];
+        assert_eq!(desired_result, pixels(&composite));
+    }
+
+    #[test]
+    #[should_panic]
+    fn composite_mismatched_sizes() {
+        Operator::Over.apply(&Canvas::new(2, 2), &Canvas::new(2, 3));
+    }
+}
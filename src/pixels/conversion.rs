@@ -0,0 +1,382 @@
+use crate::pixels::color::Color;
+use crate::pixels::color_space::{transform, ColorSpace};
+use crate::pixels::encoding::Encoding;
+use crate::pixels::error::ColorError;
+use crate::pixels::rgb::Rgb;
+
+// HSV, HSL and hex colours are given in sRGB encoded values, the way artists
+// and CSS use them, with hues in degrees. Everything else works on the linear
+// values the colour stores.
+impl Color {
+    // The relative luminance of the colour taken as Rec.709, see
+    // `ColorSpace::luminance` for the others.
+    pub fn luminance(&self) -> f64 {
+        ColorSpace::Rec709.luminance(*self)
+    }
+
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64) -> Color {
+        let chroma = value * saturation;
+        from_hue(hue, chroma, value - chroma)
+    }
+
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = hue(&Encoding::Srgb.encode_color(*self));
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+
+        (hue, saturation, max)
+    }
+
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        from_hue(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = hue(&Encoding::Srgb.encode_color(*self));
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+
+        (hue, saturation, lightness)
+    }
+
+    pub fn from_xyz(x: f64, y: f64, z: f64) -> Color {
+        let [r, g, b] = transform(&ColorSpace::Rec709.from_xyz(), [x, y, z]);
+        Color::new(r, g, b)
+    }
+
+    pub fn to_xyz(&self) -> (f64, f64, f64) {
+        let rgb = [self.r(), self.g(), self.b()];
+        let [x, y, z] = transform(&ColorSpace::Rec709.to_xyz(), rgb);
+        (x, y, z)
+    }
+
+    // CIE L*a*b* of the colour taken as Rec.709, with a lightness from 0 to
+    // 100. Colours in other spaces go through `ColorSpace::to_lab`.
+    pub fn from_lab(lightness: f64, a: f64, b: f64) -> Color {
+        ColorSpace::Rec709.from_lab(lightness, a, b)
+    }
+
+    pub fn to_lab(&self) -> (f64, f64, f64) {
+        ColorSpace::Rec709.to_lab(*self)
+    }
+
+    pub fn from_lch(lightness: f64, chroma: f64, hue: f64) -> Color {
+        let (sin, cos) = hue.to_radians().sin_cos();
+        Color::from_lab(lightness, chroma * cos, chroma * sin)
+    }
+
+    pub fn to_lch(&self) -> (f64, f64, f64) {
+        let (lightness, a, b) = self.to_lab();
+        (
+            lightness,
+            a.hypot(b),
+            b.atan2(a).to_degrees().rem_euclid(360.0),
+        )
+    }
+
+    // Björn Ottosson's Oklab, with a lightness from 0 to 1.
+    pub fn from_oklab(lightness: f64, a: f64, b: f64) -> Color {
+        let l = lightness + 0.396_337_777_4 * a + 0.215_803_757_3 * b;
+        let m = lightness - 0.105_561_345_8 * a - 0.063_854_172_8 * b;
+        let s = lightness - 0.089_484_177_5 * a - 1.291_485_548_0 * b;
+        let (l, m, s) = (l.powi(3), m.powi(3), s.powi(3));
+
+        Color::new(
+            4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+            -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+            -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s,
+        )
+    }
+
+    pub fn to_oklab(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (self.r(), self.g(), self.b());
+        let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+        let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+        let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+
+        (
+            0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+            1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+            0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+        )
+    }
+
+    // Euclidean distance in Oklab, where equal steps look roughly equally large.
+    pub fn perceptual_distance(&self, other: &Color) -> f64 {
+        let (l1, a1, b1) = self.to_oklab();
+        let (l2, a2, b2) = other.to_oklab();
+
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    // Interpolates through Oklab, avoiding the dull midpoints of a linear blend.
+    pub fn mix_oklab(&self, other: &Color, t: f64) -> Color {
+        let (l1, a1, b1) = self.to_oklab();
+        let (l2, a2, b2) = other.to_oklab();
+
+        Color::from_oklab(l1 + (l2 - l1) * t, a1 + (a2 - a1) * t, b1 + (b2 - b1) * t)
+    }
+
+    // Accepts `#rgb` and `#rrggbb`, with or without the leading hash.
+    pub fn from_hex(hex: &str) -> Result<Color, ColorError> {
+        let invalid = || ColorError::InvalidHex(hex.to_string());
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.chars().all(|digit| digit.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| invalid());
+        let (r, g, b) = match digits.len() {
+            3 => {
+                let short = |i: usize| channel(&digits[i..=i]).map(|value| value * 17);
+                (short(0)?, short(1)?, short(2)?)
+            }
+            6 => (
+                channel(&digits[0..2])?,
+                channel(&digits[2..4])?,
+                channel(&digits[4..6])?,
+            ),
+            _ => return Err(invalid()),
+        };
+
+        let color = Color::new(r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+        Ok(Encoding::Srgb.decode_color(color))
+    }
+
+    pub fn to_hex(&self) -> String {
+        let encoded = Encoding::Srgb.encode_color(*self);
+
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            hex_digit_pair(encoded.r()),
+            hex_digit_pair(encoded.g()),
+            hex_digit_pair(encoded.b())
+        )
+    }
+}
+
+// Hex codes name the nearest 8-bit value, so they survive a round trip.
+fn hex_digit_pair(value: f64) -> u8 {
+    (value * 255.0).round() as u8
+}
+
+impl std::str::FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(hex: &str) -> Result<Self, Self::Err> {
+        Color::from_hex(hex)
+    }
+}
+
+// The hue in degrees shared by HSV and HSL, with the largest and smallest
+// channel of the encoded colour.
+fn hue(encoded: &Color) -> (f64, f64, f64) {
+    let (r, g, b) = (encoded.r(), encoded.g(), encoded.b());
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+
+    (hue.rem_euclid(360.0), max, min)
+}
+
+// Builds the linear colour from a hue, its chroma and the offset added to all
+// encoded channels.
+fn from_hue(hue: f64, chroma: f64, offset: f64) -> Color {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let second = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector as u8 {
+        0 => (chroma, second, 0.0),
+        1 => (second, chroma, 0.0),
+        2 => (0.0, chroma, second),
+        3 => (0.0, second, chroma),
+        4 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+
+    Encoding::Srgb.decode_color(Color::new(r + offset, g + offset, b + offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+
+    fn triple((a, b, c): (f64, f64, f64)) -> [f64; 3] {
+        [a, b, c]
+    }
+
+    #[test]
+    fn luminance_color() {
+        assert_approx_eq!(
+            1.0,
+            Color::new(1.0, 1.0, 1.0).luminance(),
+            Margin::Absolute(1e-6)
+        );
+        assert_approx_eq!(0.21263900587151036, Color::new(1.0, 0.0, 0.0).luminance());
+        assert_eq!(0.0, Color::default().luminance());
+    }
+
+    #[test]
+    fn hsv_color() {
+        assert_eq!("#ff8000", Color::from_hsv(30.0, 1.0, 1.0).to_hex());
+        assert_approx_eq!([0.0, 1.0, 1.0], triple(Color::new(1.0, 0.0, 0.0).to_hsv()));
+        assert_approx_eq!(
+            [0.0, 0.0, 0.0],
+            triple(Color::from_hsv(240.0, 1.0, 0.0).to_hsv())
+        );
+
+        let color = Color::from_hsv(200.0, 0.4, 0.7);
+        assert_approx_eq!([200.0, 0.4, 0.7], triple(color.to_hsv()));
+    }
+
+    #[test]
+    fn hsl_color() {
+        assert_approx_eq!(Color::new(0.0, 1.0, 0.0), Color::from_hsl(120.0, 1.0, 0.5));
+        assert_approx_eq!([0.0, 0.0, 1.0], triple(Color::new(1.0, 1.0, 1.0).to_hsl()));
+
+        let color = Color::from_hsl(320.0, 0.6, 0.3);
+        assert_approx_eq!([320.0, 0.6, 0.3], triple(color.to_hsl()));
+        assert_approx_eq!(color, Color::from_hsl(-40.0, 0.6, 0.3));
+    }
+
+    #[test]
+    fn xyz_color() {
+        let white = Color::new(1.0, 1.0, 1.0);
+
+        assert_approx_eq!(
+            [0.950456, 1.0, 1.089058],
+            triple(white.to_xyz()),
+            Margin::Absolute(1e-6)
+        );
+
+        let color = Color::new(0.2, 0.5, 0.9);
+        let (x, y, z) = color.to_xyz();
+        assert_approx_eq!(color, Color::from_xyz(x, y, z), Margin::Absolute(1e-6));
+    }
+
+    #[test]
+    fn lab_color() {
+        let red = Color::new(1.0, 0.0, 0.0);
+
+        assert_approx_eq!(
+            [100.0, 0.0, 0.0],
+            triple(Color::new(1.0, 1.0, 1.0).to_lab()),
+            Margin::Absolute(1e-4)
+        );
+        assert_approx_eq!(
+            [53.24, 80.09, 67.20],
+            triple(red.to_lab()),
+            Margin::Absolute(1e-2)
+        );
+
+        let color = Color::new(0.02, 0.5, 0.3);
+        let (lightness, a, b) = color.to_lab();
+        assert_approx_eq!(color, Color::from_lab(lightness, a, b));
+
+        let dark = Color::new(0.001, 0.002, 0.0005);
+        let (lightness, a, b) = dark.to_lab();
+        assert_approx_eq!(dark, Color::from_lab(lightness, a, b));
+    }
+
+    #[test]
+    fn lch_color() {
+        let (lightness, chroma, hue) = Color::new(1.0, 0.0, 0.0).to_lch();
+
+        assert_approx_eq!(53.24, lightness, Margin::Absolute(1e-2));
+        assert_approx_eq!(104.55, chroma, Margin::Absolute(1e-2));
+        assert_approx_eq!(39.999, hue, Margin::Absolute(1e-2));
+        assert_approx_eq!(
+            Color::new(1.0, 0.0, 0.0),
+            Color::from_lch(lightness, chroma, hue)
+        );
+    }
+
+    #[test]
+    fn oklab_color() {
+        let red = Color::new(1.0, 0.0, 0.0);
+
+        assert_approx_eq!(
+            [1.0, 0.0, 0.0],
+            triple(Color::new(1.0, 1.0, 1.0).to_oklab()),
+            Margin::Absolute(1e-6)
+        );
+        assert_approx_eq!(
+            [0.627955, 0.224863, 0.125846],
+            triple(red.to_oklab()),
+            Margin::Absolute(1e-6)
+        );
+
+        let (lightness, a, b) = red.to_oklab();
+        assert_approx_eq!(
+            red,
+            Color::from_oklab(lightness, a, b),
+            Margin::Absolute(1e-6)
+        );
+    }
+
+    #[test]
+    fn perceptual_distance_color() {
+        let grey = Color::new(0.2, 0.2, 0.2);
+
+        assert_eq!(0.0, grey.perceptual_distance(&grey));
+        assert!(
+            grey.perceptual_distance(&Color::new(0.21, 0.2, 0.2))
+                < grey.perceptual_distance(&Color::new(0.4, 0.2, 0.2))
+        );
+    }
+
+    #[test]
+    fn mix_oklab_color() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let green = Color::new(0.0, 1.0, 0.0);
+
+        assert_approx_eq!(red, red.mix_oklab(&green, 0.0), Margin::Absolute(1e-6));
+        assert_approx_eq!(green, red.mix_oklab(&green, 1.0), Margin::Absolute(1e-6));
+
+        // Lightness moves evenly between the ends.
+        let midpoint = (red.to_oklab().0 + green.to_oklab().0) / 2.0;
+        assert_approx_eq!(
+            midpoint,
+            red.mix_oklab(&green, 0.5).to_oklab().0,
+            Margin::Absolute(1e-6)
+        );
+    }
+
+    #[test]
+    fn hex_color() {
+        let color = Color::from_hex("#ff8800").unwrap();
+
+        assert_eq!(1.0, color.r());
+        assert_approx_eq!(Encoding::Srgb.decode(136.0 / 255.0), color.g());
+        assert_eq!(0.0, color.b());
+        assert_eq!("#ff8800", color.to_hex());
+        assert_eq!(color, "FF8800".parse::<Color>().unwrap());
+        assert_eq!(color, Color::from_hex("#f80").unwrap());
+    }
+
+    #[test]
+    fn hex_color_invalid() {
+        for hex in ["", "#ff88", "#ff88000", "#gg8800", "#+f8800", "#ffé00"] {
+            assert_eq!(
+                Err(ColorError::InvalidHex(hex.to_string())),
+                Color::from_hex(hex)
+            );
+        }
+    }
+}
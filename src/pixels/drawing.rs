@@ -0,0 +1,683 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::rect::Rect;
+
+impl Canvas {
+    pub fn draw_point(&mut self, x: isize, y: isize, color: Color) {
+        if let Some((x, y)) = self.visible(x, y) {
+            self.set_pixel(x, y, color);
+        }
+    }
+
+    // Lays `color` over the pixel with the given coverage, as anti-aliased
+    // primitives only partially cover their edge pixels.
+    pub fn blend_point(&mut self, x: isize, y: isize, color: Color, coverage: f64) {
+        if let Some((x, y)) = self.visible(x, y) {
+            let coverage = coverage.clamp(0.0, 1.0);
+            let blended = color * coverage + self.get_pixel(x, y) * (1.0 - coverage);
+            let alpha = coverage + self.get_alpha(x, y) * (1.0 - coverage);
+
+            self.set_pixel_with_alpha(x, y, blended, alpha);
+        }
+    }
+
+    // Bresenham's line algorithm, generalised to every octant. The pixels
+    // are worked out in closed form, so only the steps that can land on the
+    // drawable area are visited however long the line is.
+    pub fn draw_line(&mut self, x0: isize, y0: isize, x1: isize, y1: isize, color: Color) {
+        let area = self.drawable_area();
+        if area.is_empty() {
+            return;
+        }
+
+        let (dx, dy) = (x1 as i128 - x0 as i128, y1 as i128 - y0 as i128);
+        let x_major = dx.abs() >= dy.abs();
+        let (major, minor) = if x_major { (dx, dy) } else { (dy, dx) };
+        let steps = major.abs();
+
+        // Bresenham picks the minor coordinate nearest the ideal line, with
+        // halfway points rounding away from the start.
+        let position = |step: i128| {
+            let minor_offset = if steps == 0 {
+                0
+            } else {
+                (2 * step * minor.abs() + steps) / (2 * steps) * minor.signum()
+            };
+            let major_offset = step * major.signum();
+
+            if x_major {
+                (x0 as i128 + major_offset, y0 as i128 + minor_offset)
+            } else {
+                (x0 as i128 + minor_offset, y0 as i128 + major_offset)
+            }
+        };
+
+        let columns = (area.x() as i128, (area.x() + area.width()) as i128 - 1);
+        let rows = (area.y() as i128, (area.y() + area.height()) as i128 - 1);
+        let Some((first_x, last_x)) = visible_steps(steps, |step| position(step).0, columns) else {
+            return;
+        };
+        let Some((first_y, last_y)) = visible_steps(steps, |step| position(step).1, rows) else {
+            return;
+        };
+
+        for step in first_x.max(first_y)..=last_x.min(last_y) {
+            let (x, y) = position(step);
+            self.draw_point(x as isize, y as isize, color);
+        }
+    }
+
+    // Xiaolin Wu's line algorithm; coordinates address pixel centres.
+    pub fn draw_line_antialiased(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, color: Color) {
+        // Clipping to a margin around the drawable area keeps long lines
+        // cheap, while the partially covered pixels at the new ends stay out
+        // of sight.
+        let area = self.drawable_area();
+        let min = (area.x() as f64 - 2.0, area.y() as f64 - 2.0);
+        let max = (
+            (area.x() + area.width()) as f64 + 1.0,
+            (area.y() + area.height()) as f64 + 1.0,
+        );
+        let Some((x0, y0, x1, y1)) = clip_segment((x0, y0), (x1, y1), min, max) else {
+            return;
+        };
+
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (x0, y0, x1, y1) = if steep {
+            (y0, x0, y1, x1)
+        } else {
+            (x0, y0, x1, y1)
+        };
+        let (x0, y0, x1, y1) = if x0 > x1 {
+            (x1, y1, x0, y0)
+        } else {
+            (x0, y0, x1, y1)
+        };
+
+        let gradient = if x1 - x0 == 0.0 {
+            1.0
+        } else {
+            (y1 - y0) / (x1 - x0)
+        };
+
+        let plot = |canvas: &mut Canvas, x: f64, y: f64, coverage: f64| {
+            let (x, y) = if steep { (y, x) } else { (x, y) };
+            canvas.blend_point(x as isize, y as isize, color, coverage);
+        };
+
+        // Both end points are weighted by how far they reach into their pixel.
+        let endpoint = |canvas: &mut Canvas, x: f64, y: f64, gap: f64| {
+            let end_x = x.round();
+            let end_y = y + gradient * (end_x - x);
+            plot(canvas, end_x, end_y.floor(), (1.0 - fraction(end_y)) * gap);
+            plot(canvas, end_x, end_y.floor() + 1.0, fraction(end_y) * gap);
+            (end_x, end_y)
+        };
+
+        let (start_x, start_y) = endpoint(self, x0, y0, 1.0 - fraction(x0 + 0.5));
+        let (end_x, _) = endpoint(self, x1, y1, fraction(x1 + 0.5));
+
+        let mut intersection = start_y + gradient;
+        let mut x = start_x + 1.0;
+        while x < end_x {
+            let y = intersection.floor();
+            plot(self, x, y, 1.0 - fraction(intersection));
+            plot(self, x, y + 1.0, fraction(intersection));
+
+            intersection += gradient;
+            x += 1.0;
+        }
+    }
+
+    // Midpoint circle algorithm, plotting all eight octants at once. Each
+    // octant's pixels are worked out in closed form, so only the rows and
+    // columns of the drawable area are visited however large the circle is.
+    pub fn draw_circle(&mut self, center_x: isize, center_y: isize, radius: isize, color: Color) {
+        let area = self.drawable_area();
+        if radius < 0 || area.is_empty() {
+            return;
+        }
+
+        let (center_x, center_y, radius) = (center_x as i128, center_y as i128, radius as i128);
+        // Stepping y up from 0, the algorithm keeps the largest x with
+        // x (x - 1) + y² < r², and stops once x drops below y.
+        let octant_x = |y: i128| {
+            let limit = radius * radius - 1 - y * y;
+            if radius == 0 {
+                0
+            } else if limit < 0 {
+                -1
+            } else {
+                let root = limit.isqrt();
+                if root * (root + 1) <= limit {
+                    root + 1
+                } else {
+                    root
+                }
+            }
+        };
+        let last = partition_point(radius, |y| octant_x(y) >= y) - 1;
+
+        let columns = area.x() as i128..(area.x() + area.width()) as i128;
+        let rows = area.y() as i128..(area.y() + area.height()) as i128;
+        // The octants either side of the x axis are found by their rows,
+        // those either side of the y axis by their columns.
+        for row in rows.clone() {
+            let y = (row - center_y).abs();
+            if y <= last {
+                let x = octant_x(y);
+                for column in [center_x - x, center_x + x] {
+                    if columns.contains(&column) {
+                        self.set_pixel(column as usize, row as usize, color);
+                    }
+                }
+            }
+        }
+        for column in columns {
+            let y = (column - center_x).abs();
+            if y <= last {
+                let x = octant_x(y);
+                for row in [center_y - x, center_y + x] {
+                    if rows.contains(&row) {
+                        self.set_pixel(column as usize, row as usize, color);
+                    }
+                }
+            }
+        }
+    }
+
+    // Covers the same extent as the outline drawn by `draw_circle`: the
+    // pixels within half a pixel past the radius. Only the spans within the
+    // drawable area are visited.
+    pub fn fill_circle(&mut self, center_x: isize, center_y: isize, radius: isize, color: Color) {
+        let area = self.drawable_area();
+        if radius < 0 || area.is_empty() {
+            return;
+        }
+
+        let (center_x, center_y, radius) = (center_x as i128, center_y as i128, radius as i128);
+        let (left, right) = (area.x() as i128, (area.x() + area.width()) as i128 - 1);
+
+        for y in area.y()..area.y() + area.height() {
+            let dy = y as i128 - center_y;
+            if dy.abs() > radius {
+                continue;
+            }
+
+            // x² + dy² <= (r + 0.5)² holds for whole numbers exactly when
+            // x² + dy² <= r² + r.
+            let half_width = (radius * radius + radius - dy * dy).isqrt();
+            let start = (center_x - half_width).max(left);
+            let end = (center_x + half_width).min(right);
+            for x in start..=end {
+                self.set_pixel(x as usize, y, color);
+            }
+        }
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let bounds = self.drawable_area().intersect(&rect);
+
+        for y in bounds.y()..bounds.y() + bounds.height() {
+            for x in bounds.x()..bounds.x() + bounds.width() {
+                self.set_pixel(x, y, color);
+            }
+        }
+    }
+
+    // Fills every pixel whose centre lies inside the polygon by the even-odd rule.
+    pub fn fill_polygon(&mut self, vertices: &[(f64, f64)], color: Color) {
+        if vertices.len() < 3 {
+            return;
+        }
+
+        let bounds = self.drawable_area();
+        let mut crossings = Vec::with_capacity(vertices.len());
+
+        for y in bounds.y()..bounds.y() + bounds.height() {
+            let scan_y = y as f64 + 0.5;
+            crossings.clear();
+
+            for (i, &(x0, y0)) in vertices.iter().enumerate() {
+                let (x1, y1) = vertices[(i + 1) % vertices.len()];
+
+                // Half open test, so shared vertices are only counted once.
+                if (y0 <= scan_y) != (y1 <= scan_y) {
+                    crossings.push(x0 + (scan_y - y0) / (y1 - y0) * (x1 - x0));
+                }
+            }
+            crossings.sort_by(|a, b| a.total_cmp(b));
+
+            for span in crossings.chunks_exact(2) {
+                let start = (span[0] - 0.5).ceil().max(bounds.x() as f64);
+                let end = (span[1] - 0.5)
+                    .ceil()
+                    .min((bounds.x() + bounds.width()) as f64);
+
+                let mut x = start;
+                while x < end {
+                    self.set_pixel(x as usize, y, color);
+                    x += 1.0;
+                }
+            }
+        }
+    }
+
+    fn drawable_area(&self) -> Rect {
+        let canvas = Rect::new(0, 0, self.width(), self.height());
+
+        match self.clip() {
+            Some(clip) => canvas.intersect(&clip),
+            None => canvas,
+        }
+    }
+
+    fn visible(&self, x: isize, y: isize) -> Option<(usize, usize)> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        let (x, y) = (x as usize, y as usize);
+        if self.drawable_area().contains(x, y) {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+}
+
+fn fraction(value: f64) -> f64 {
+    value - value.floor()
+}
+
+// The first and last of the steps `0..=steps` at which `coordinate`, which
+// only ever moves one way, lies within `range`.
+fn visible_steps(
+    steps: i128,
+    coordinate: impl Fn(i128) -> i128,
+    (low, high): (i128, i128),
+) -> Option<(i128, i128)> {
+    let rising = coordinate(0) <= coordinate(steps);
+    let before = |step: i128| {
+        let value = coordinate(step);
+        if rising {
+            value < low
+        } else {
+            value > high
+        }
+    };
+    let within = |step: i128| (low..=high).contains(&coordinate(step));
+
+    let first = partition_point(steps, before);
+    let last = partition_point(steps, |step| before(step) || within(step)) - 1;
+    (first <= last).then_some((first, last))
+}
+
+// The first step in `0..=steps` for which the monotonic `predicate` fails.
+fn partition_point(steps: i128, predicate: impl Fn(i128) -> bool) -> i128 {
+    let (mut low, mut high) = (0, steps + 1);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if predicate(middle) {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+// Liang and Barsky's clipping of a segment to a box, or `None` when the
+// segment misses the box.
+fn clip_segment(
+    (x0, y0): (f64, f64),
+    (x1, y1): (f64, f64),
+    min: (f64, f64),
+    max: (f64, f64),
+) -> Option<(f64, f64, f64, f64)> {
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let (mut enter, mut exit) = (0.0_f64, 1.0_f64);
+
+    for (direction, distance) in [
+        (-dx, x0 - min.0),
+        (dx, max.0 - x0),
+        (-dy, y0 - min.1),
+        (dy, max.1 - y0),
+    ] {
+        if direction == 0.0 {
+            if distance < 0.0 {
+                return None;
+            }
+            continue;
+        }
+
+        let t = distance / direction;
+        if direction < 0.0 {
+            enter = enter.max(t);
+        } else {
+            exit = exit.min(t);
+        }
+    }
+
+    if enter > exit {
+        return None;
+    }
+    Some((
+        x0 + enter * dx,
+        y0 + enter * dy,
+        x0 + exit * dx,
+        y0 + exit * dy,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixels::rgb::Rgb;
+
+    fn white() -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    fn lit(canvas: &Canvas) -> Vec<(usize, usize)> {
+        canvas
+            .enumerate_pixels()
+            .filter(|(_, _, color)| color.r() > 0.0)
+            .map(|(x, y, _)| (x, y))
+            .collect()
+    }
+
+    #[test]
+    fn draw_point_clipped() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_clip(Some(Rect::new(1, 1, 2, 2)));
+
+        canvas.draw_point(0, 0, white());
+        canvas.draw_point(2, 2, white());
+        canvas.draw_point(-1, 2, white());
+        canvas.draw_point(7, 2, white());
+
+        assert_eq!(vec![(2, 2)], lit(&canvas));
+    }
+
+    #[test]
+    fn blend_point_coverage() {
+        let mut canvas = Canvas::new(1, 1);
+
+        canvas.blend_point(0, 0, Color::new(1.0, 0.0, 0.0), 0.25);
+        assert_eq!(Color::new(0.25, 0.0, 0.0), canvas.get_pixel(0, 0));
+        assert_eq!(0.25, canvas.get_alpha(0, 0));
+
+        canvas.blend_point(0, 0, Color::new(0.0, 1.0, 0.0), 0.5);
+        assert_eq!(Color::new(0.125, 0.5, 0.0), canvas.get_pixel(0, 0));
+        assert_eq!(0.625, canvas.get_alpha(0, 0));
+    }
+
+    #[test]
+    fn draw_line_shallow() {
+        let mut canvas = Canvas::new(6, 3);
+        canvas.draw_line(0, 0, 5, 2, white());
+
+        let desired_result = vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)];
+        assert_eq!(desired_result, lit(&canvas));
+    }
+
+    #[test]
+    fn draw_line_steep_reversed() {
+        let mut canvas = Canvas::new(3, 5);
+        canvas.draw_line(2, 4, 0, 0, white());
+
+        assert_eq!(vec![(0, 0), (0, 1), (1, 2), (1, 3), (2, 4)], lit(&canvas));
+    }
+
+    #[test]
+    fn draw_line_leaves_canvas() {
+        let mut canvas = Canvas::new(3, 1);
+        canvas.draw_line(-5, 0, 10, 0, white());
+
+        assert_eq!(vec![(0, 0), (1, 0), (2, 0)], lit(&canvas));
+    }
+
+    // The step by step form of Bresenham's algorithm.
+    fn reference_line(x0: isize, y0: isize, x1: isize, y1: isize) -> Vec<(isize, isize)> {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (step_x, step_y) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y) = (x0, y0);
+        let mut error = dx + dy;
+        let mut points = vec![(x, y)];
+
+        while x != x1 || y != y1 {
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+            points.push((x, y));
+        }
+        points
+    }
+
+    #[test]
+    fn draw_line_matches_bresenham() {
+        for (x0, y0) in [(0, 0), (3, 5), (7, 1)] {
+            for x1 in -4..12 {
+                for y1 in -4..12 {
+                    let mut canvas = Canvas::new(8, 8);
+                    canvas.draw_line(x0, y0, x1, y1, white());
+
+                    let mut expected: Vec<(usize, usize)> = reference_line(x0, y0, x1, y1)
+                        .into_iter()
+                        .filter(|&(x, y)| (0..8).contains(&x) && (0..8).contains(&y))
+                        .map(|(x, y)| (x as usize, y as usize))
+                        .collect();
+                    expected.sort_by_key(|&(x, y)| (y, x));
+                    assert_eq!(expected, lit(&canvas), "{:?}", (x0, y0, x1, y1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn draw_line_clips_huge_lines() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_line(isize::MIN, 1, isize::MAX, 1, white());
+        canvas.draw_line(-1_000_000_000_000, -1_000_000_000_000, 3, 3, white());
+        canvas.draw_line(
+            isize::MIN / 2,
+            isize::MIN / 2,
+            isize::MIN / 2 + 5,
+            0,
+            white(),
+        );
+
+        assert_eq!(
+            vec![(0, 0), (0, 1), (1, 1), (2, 1), (3, 1), (2, 2), (3, 3)],
+            lit(&canvas)
+        );
+
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_line_antialiased(-1e15, 2.0, 1e15, 2.0, white());
+        canvas.draw_line_antialiased(-1e15, -1e15, -1e15 + 1.0, 1e15, white());
+
+        let row: Vec<f64> = (0..4).map(|x| canvas.get_pixel(x, 2).r()).collect();
+        assert_eq!(vec![1.0; 4], row);
+        assert_eq!(4, lit(&canvas).len());
+    }
+
+    #[test]
+    fn draw_line_antialiased_horizontal() {
+        let mut canvas = Canvas::new(5, 3);
+        canvas.draw_line_antialiased(0.0, 1.0, 4.0, 1.0, white());
+
+        // The line starts and ends on pixel centres, half covering those pixels.
+        let row: Vec<f64> = (0..5).map(|x| canvas.get_pixel(x, 1).r()).collect();
+        assert_eq!(vec![0.5, 1.0, 1.0, 1.0, 0.5], row);
+        for x in 0..5 {
+            assert_eq!(0.0, canvas.get_pixel(x, 0).r());
+            assert_eq!(0.0, canvas.get_pixel(x, 2).r());
+        }
+    }
+
+    #[test]
+    fn draw_line_antialiased_splits_coverage() {
+        let mut canvas = Canvas::new(5, 3);
+        canvas.draw_line_antialiased(0.0, 0.5, 4.0, 0.5, white());
+
+        for x in 1..4 {
+            assert_eq!(0.5, canvas.get_pixel(x, 0).r());
+            assert_eq!(0.5, canvas.get_pixel(x, 1).r());
+            assert_eq!(0.0, canvas.get_pixel(x, 2).r());
+        }
+        assert_eq!(0.25, canvas.get_pixel(0, 0).r());
+        assert_eq!(0.25, canvas.get_pixel(4, 1).r());
+    }
+
+    #[test]
+    fn draw_line_antialiased_steep() {
+        let mut canvas = Canvas::new(3, 5);
+        canvas.draw_line_antialiased(1.0, 4.0, 1.0, 0.0, white());
+
+        let column: Vec<f64> = (0..5).map(|y| canvas.get_pixel(1, y).r()).collect();
+        assert_eq!(vec![0.5, 1.0, 1.0, 1.0, 0.5], column);
+        for y in 0..5 {
+            assert_eq!(0.0, canvas.get_pixel(0, y).r());
+            assert_eq!(0.0, canvas.get_pixel(2, y).r());
+        }
+    }
+
+    #[test]
+    fn draw_circle_outline() {
+        let mut canvas = Canvas::new(5, 5);
+        canvas.draw_circle(2, 2, 2, white());
+
+        let desired_result = vec![
+            (1, 0),
+            (2, 0),
+            (3, 0),
+            (0, 1),
+            (4, 1),
+            (0, 2),
+            (4, 2),
+            (0, 3),
+            (4, 3),
+            (1, 4),
+            (2, 4),
+            (3, 4),
+        ];
+        assert_eq!(desired_result, lit(&canvas));
+    }
+
+    // The midpoint circle algorithm stepped through pixel by pixel.
+    fn reference_circle(radius: isize) -> Vec<(isize, isize)> {
+        let (mut x, mut y) = (radius, 0);
+        let mut error = 1 - radius;
+        let mut points = Vec::new();
+
+        while x >= y {
+            for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y)] {
+                points.push((dx, dy));
+                points.push((-dx, -dy));
+            }
+
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn draw_circle_matches_midpoint() {
+        for radius in 0..40 {
+            let size = 2 * radius as usize + 3;
+            let mut canvas = Canvas::new(size, size);
+            let centre = radius + 1;
+            canvas.draw_circle(centre, centre, radius, white());
+
+            let mut expected: Vec<(usize, usize)> = reference_circle(radius)
+                .into_iter()
+                .map(|(dx, dy)| ((centre + dx) as usize, (centre + dy) as usize))
+                .collect();
+            expected.sort_by_key(|&(x, y)| (y, x));
+            expected.dedup();
+            assert_eq!(expected, lit(&canvas), "radius {}", radius);
+        }
+    }
+
+    #[test]
+    fn circles_clip_huge_sizes() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_clip(Some(Rect::new(0, 0, 3, 4)));
+        canvas.fill_circle(0, 0, 4_000_000_000_000, white());
+        assert_eq!(12, lit(&canvas).len());
+
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_circle(isize::MAX - 2, 0, 10, white());
+        canvas.draw_circle(isize::MIN, isize::MIN, isize::MAX, white());
+        assert!(lit(&canvas).is_empty());
+
+        // Only the rows and columns of the canvas are visited, where the
+        // left edge of the circle is straight.
+        canvas.draw_circle(100_000_000_001, 2, 100_000_000_000, white());
+        assert_eq!(vec![(1, 0), (1, 1), (1, 2), (1, 3)], lit(&canvas));
+    }
+
+    #[test]
+    fn fill_circle_disk() {
+        let mut canvas = Canvas::new(5, 5);
+        canvas.fill_circle(2, 2, 2, white());
+
+        assert_eq!(21, lit(&canvas).len());
+        assert_eq!(Color::default(), canvas.get_pixel(0, 0));
+        assert_eq!(white(), canvas.get_pixel(2, 2));
+    }
+
+    #[test]
+    fn fill_rect_clipped() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_clip(Some(Rect::new(0, 0, 3, 3)));
+        canvas.fill_rect(Rect::new(2, 1, 5, 5), white());
+
+        assert_eq!(vec![(2, 1), (2, 2)], lit(&canvas));
+    }
+
+    #[test]
+    fn fill_polygon_triangle() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_polygon(&[(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)], white());
+
+        let desired_result = vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (0, 2)];
+        assert_eq!(desired_result, lit(&canvas));
+    }
+
+    #[test]
+    fn fill_polygon_even_odd() {
+        let mut canvas = Canvas::new(5, 5);
+        // A square with a square hole, traced as one self-overlapping path.
+        let vertices = [
+            (0.0, 0.0),
+            (5.0, 0.0),
+            (5.0, 5.0),
+            (0.0, 5.0),
+            (0.0, 0.0),
+            (1.0, 1.0),
+            (1.0, 4.0),
+            (4.0, 4.0),
+            (4.0, 1.0),
+            (1.0, 1.0),
+        ];
+        canvas.fill_polygon(&vertices, white());
+
+        assert_eq!(16, lit(&canvas).len());
+        assert_eq!(Color::default(), canvas.get_pixel(2, 2));
+        assert_eq!(white(), canvas.get_pixel(0, 2));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::pixels::color::Color;
+use crate::pixels::rgb::Rgb;
+
+// Transfer function between the linear values stored in a `Color` and the
+// values written to (or read from) an image file.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Encoding {
+    Linear,
+    #[default]
+    Srgb,
+    Gamma(f64),
+}
+
+impl Encoding {
+    pub fn encode(&self, value: f64) -> f64 {
+        match self {
+            Encoding::Linear => value,
+            Encoding::Srgb => {
+                let value = value.max(0.0);
+
+                if value <= 0.003_130_8 {
+                    value * 12.92
+                } else {
+                    // Equal to 1.055 * v^(1/2.4) - 0.055, but exactly 1 for 1.
+                    let curve = value.powf(1.0 / 2.4);
+                    curve + 0.055 * (curve - 1.0)
+                }
+            }
+            Encoding::Gamma(gamma) => value.max(0.0).powf(1.0 / gamma),
+        }
+    }
+
+    pub fn decode(&self, value: f64) -> f64 {
+        match self {
+            Encoding::Linear => value,
+            Encoding::Srgb => {
+                let value = value.max(0.0);
+
+                if value <= 0.040_45 {
+                    value / 12.92
+                } else {
+                    ((value + 0.055) / 1.055).powf(2.4)
+                }
+            }
+            Encoding::Gamma(gamma) => value.max(0.0).powf(*gamma),
+        }
+    }
+
+    pub fn encode_color(&self, color: Color) -> Color {
+        Color::new(
+            self.encode(color.r()),
+            self.encode(color.g()),
+            self.encode(color.b()),
+        )
+    }
+
+    pub fn decode_color(&self, color: Color) -> Color {
+        Color::new(
+            self.decode(color.r()),
+            self.decode(color.g()),
+            self.decode(color.b()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_encoding_identity() {
+        let encoding = Encoding::Linear;
+
+        assert_eq!(0.25, encoding.encode(0.25));
+        assert_eq!(0.25, encoding.decode(0.25));
+        assert_eq!(1.5, encoding.encode(1.5));
+    }
+
+    #[test]
+    fn srgb_encoding_known_values() {
+        let encoding = Encoding::Srgb;
+
+        assert_eq!(0.0, encoding.encode(0.0));
+        assert!((encoding.encode(1.0) - 1.0).abs() < 1e-12);
+        assert!((encoding.encode(0.5) - 0.735_356_983).abs() < 1e-6);
+        assert!((encoding.encode(0.002) - 0.025_84).abs() < 1e-12);
+    }
+
+    #[test]
+    fn srgb_encoding_round_trip() {
+        let encoding = Encoding::Srgb;
+
+        for step in 0..=100 {
+            let value = step as f64 / 100.0;
+            assert!((encoding.decode(encoding.encode(value)) - value).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn gamma_encoding_round_trip() {
+        let encoding = Encoding::Gamma(2.2);
+
+        assert!((encoding.encode(0.5) - 0.5_f64.powf(1.0 / 2.2)).abs() < 1e-12);
+        assert!((encoding.decode(encoding.encode(0.3)) - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn encoding_negative_values() {
+        assert_eq!(0.0, Encoding::Srgb.encode(-0.5));
+        assert_eq!(0.0, Encoding::Gamma(2.2).encode(-0.5));
+        assert_eq!(-0.5, Encoding::Linear.encode(-0.5));
+    }
+
+    #[test]
+    fn encode_color_srgb() {
+        let color = Color::new(0.0, 0.5, 1.0);
+        let encoded = Encoding::Srgb.encode_color(color);
+
+        assert_eq!(0.0, encoded.r());
+        assert!((encoded.g() - 0.735_356_983).abs() < 1e-6);
+        assert!((encoded.b() - 1.0).abs() < 1e-12);
+
+        let decoded = Encoding::Srgb.decode_color(encoded);
+        assert!((decoded.g() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn default_encoding_srgb() {
+        assert_eq!(Encoding::Srgb, Encoding::default());
+    }
+}
@@ -0,0 +1,62 @@
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageError {
+    Malformed(String),
+    Truncated,
+    Unsupported(String),
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Malformed(reason) => write!(f, "malformed image: {}", reason),
+            ImageError::Truncated => write!(f, "image data ends unexpectedly"),
+            ImageError::Unsupported(feature) => write!(f, "unsupported image feature: {}", feature),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CanvasError {
+    OutOfBounds {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    },
+}
+
+impl std::fmt::Display for CanvasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CanvasError::OutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            } => write!(
+                f,
+                "pixel ({}, {}) is outside the {}x{} canvas",
+                x, y, width, height
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CanvasError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColorError {
+    InvalidHex(String),
+}
+
+impl std::fmt::Display for ColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorError::InvalidHex(hex) => write!(f, "invalid hex colour: {:?}", hex),
+        }
+    }
+}
+
+impl std::error::Error for ColorError {}
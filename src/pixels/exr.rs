@@ -0,0 +1,783 @@
+use crate::pixels::channel_buffer::{Channel, ChannelBuffer, PixelType};
+use crate::pixels::color_space::ColorSpace;
+use crate::pixels::error::ImageError;
+use crate::pixels::zlib;
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+// Version 2, single part scanline image.
+const VERSION: [u8; 4] = [2, 0, 0, 0];
+// Deflate cannot shrink data by more than this, which bounds how many
+// pixels a compressed file of a given length can hold.
+const MAX_DEFLATE_RATIO: usize = 1032;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Zip,
+}
+
+impl Compression {
+    fn id(&self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zip => 3,
+        }
+    }
+
+    fn lines_per_block(&self) -> usize {
+        match self {
+            Compression::None => 1,
+            Compression::Zip => 16,
+        }
+    }
+}
+
+pub struct EXR {
+    content: Vec<u8>,
+}
+
+impl EXR {
+    pub fn new(buffer: &ChannelBuffer, compression: Compression) -> Self {
+        let mut content = MAGIC.to_vec();
+        content.extend_from_slice(&VERSION);
+        content.append(&mut EXR::create_header(buffer, compression));
+
+        let blocks: Vec<Vec<u8>> = (0..buffer.height())
+            .step_by(compression.lines_per_block())
+            .map(|y| EXR::create_block(buffer, compression, y))
+            .collect();
+
+        // The offset table points at every block from the start of the file.
+        let mut offset = content.len() + 8 * blocks.len();
+        for block in blocks.iter() {
+            content.extend_from_slice(&(offset as u64).to_le_bytes());
+            offset += block.len();
+        }
+        for block in blocks {
+            content.extend(block);
+        }
+
+        EXR { content }
+    }
+
+    fn create_header(buffer: &ChannelBuffer, compression: Compression) -> Vec<u8> {
+        let mut header = Vec::new();
+
+        let mut channel_list = Vec::new();
+        for channel in sorted_channels(buffer) {
+            channel_list.extend_from_slice(channel.name().as_bytes());
+            channel_list.push(0);
+            channel_list.extend_from_slice(&pixel_type_id(channel.pixel_type()).to_le_bytes());
+            // Perceptually linear flag, three reserved bytes, then x and y sampling.
+            channel_list.extend_from_slice(&[0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0]);
+        }
+        channel_list.push(0);
+
+        let mut window = Vec::with_capacity(16);
+        for value in [0, 0, buffer.width() as i32 - 1, buffer.height() as i32 - 1] {
+            window.extend_from_slice(&value.to_le_bytes());
+        }
+
+        write_attribute(&mut header, "channels", "chlist", &channel_list);
+        // Files without chromaticities are taken to be Rec.709.
+        if buffer.color_space() != ColorSpace::Rec709 {
+            let [red, green, blue] = buffer.color_space().primaries();
+            let chromaticities: Vec<u8> = [red, green, blue, buffer.color_space().white_point()]
+                .iter()
+                .flat_map(|&(x, y)| [x as f32, y as f32])
+                .flat_map(f32::to_le_bytes)
+                .collect();
+            write_attribute(
+                &mut header,
+                "chromaticities",
+                "chromaticities",
+                &chromaticities,
+            );
+        }
+        write_attribute(
+            &mut header,
+            "compression",
+            "compression",
+            &[compression.id()],
+        );
+        write_attribute(&mut header, "dataWindow", "box2i", &window);
+        write_attribute(&mut header, "displayWindow", "box2i", &window);
+        write_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+        write_attribute(
+            &mut header,
+            "pixelAspectRatio",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+        write_attribute(
+            &mut header,
+            "screenWindowWidth",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        header.push(0);
+
+        header
+    }
+
+    fn create_block(buffer: &ChannelBuffer, compression: Compression, start: usize) -> Vec<u8> {
+        let end = (start + compression.lines_per_block()).min(buffer.height());
+        let mut raw = Vec::new();
+
+        for y in start..end {
+            for channel in sorted_channels(buffer) {
+                let row = &channel.samples()[y * buffer.width()..(y + 1) * buffer.width()];
+
+                for &sample in row {
+                    match channel.pixel_type() {
+                        PixelType::Half => {
+                            raw.extend_from_slice(&into_half(sample as f32).to_le_bytes())
+                        }
+                        PixelType::Float => raw.extend_from_slice(&(sample as f32).to_le_bytes()),
+                    }
+                }
+            }
+        }
+
+        let data = match compression {
+            Compression::None => raw,
+            Compression::Zip => {
+                let compressed = zlib::compress(&predict(&interleave(&raw)));
+                // Blocks that do not shrink are stored as they are.
+                if compressed.len() < raw.len() {
+                    compressed
+                } else {
+                    raw
+                }
+            }
+        };
+
+        let mut block = Vec::with_capacity(8 + data.len());
+        block.extend_from_slice(&(start as i32).to_le_bytes());
+        block.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        block.extend(data);
+
+        block
+    }
+
+    pub fn get(&self) -> Vec<u8> {
+        self.content.clone()
+    }
+
+    pub fn read(bytes: &[u8]) -> Result<ChannelBuffer, ImageError> {
+        if bytes.get(..4) != Some(&MAGIC) {
+            return Err(ImageError::Malformed("missing OpenEXR magic".to_string()));
+        }
+        let version = bytes.get(4..8).ok_or(ImageError::Truncated)?;
+        if version[0] != 2 || version[1] & 0x1e != 0 {
+            return Err(ImageError::Unsupported(
+                "tiled, deep or multi-part OpenEXR".to_string(),
+            ));
+        }
+
+        let mut position = 8;
+        let mut channels = Vec::new();
+        let mut compression = None;
+        let mut window = None;
+        let mut color_space = ColorSpace::Rec709;
+
+        loop {
+            let name = read_string(bytes, &mut position)?;
+            if name.is_empty() {
+                break;
+            }
+            let _kind = read_string(bytes, &mut position)?;
+            let size = read_size(bytes, position, "attribute size")?;
+            let start = position + 4;
+            let end = start.checked_add(size).ok_or(ImageError::Truncated)?;
+            let value = bytes.get(start..end).ok_or(ImageError::Truncated)?;
+            position = end;
+
+            match name.as_str() {
+                "channels" => channels = parse_channels(value)?,
+                "compression" => {
+                    compression = Some(match value.first() {
+                        Some(0) => (Compression::None, 1),
+                        Some(2) => (Compression::Zip, 1),
+                        Some(3) => (Compression::Zip, 16),
+                        other => {
+                            return Err(ImageError::Unsupported(format!(
+                                "OpenEXR compression {:?}",
+                                other
+                            )))
+                        }
+                    })
+                }
+                "chromaticities" => {
+                    let values = (0..8)
+                        .map(|i| read_f32(value, 4 * i))
+                        .collect::<Result<Vec<f64>, ImageError>>()?;
+                    let point = |i: usize| (values[2 * i], values[2 * i + 1]);
+                    color_space =
+                        ColorSpace::from_chromaticities([point(0), point(1), point(2)], point(3))
+                            .ok_or_else(|| {
+                            ImageError::Unsupported("OpenEXR chromaticities".to_string())
+                        })?;
+                }
+                "dataWindow" => {
+                    let bounds = (0..4)
+                        .map(|i| read_i32(value, 4 * i))
+                        .collect::<Result<Vec<i32>, ImageError>>()?;
+                    window = Some(bounds);
+                }
+                _ => {}
+            }
+        }
+
+        let (compression, lines_per_block) =
+            compression.ok_or_else(|| ImageError::Malformed("missing compression".to_string()))?;
+        let window =
+            window.ok_or_else(|| ImageError::Malformed("missing dataWindow".to_string()))?;
+        if channels.is_empty() {
+            return Err(ImageError::Malformed("missing channels".to_string()));
+        }
+        // Spans are worked out in 64 bits, where the i32 bounds cannot overflow.
+        let span = |from: i32, to: i32| (i64::from(to) - i64::from(from) + 1).max(0) as usize;
+        let width = span(window[0], window[2]);
+        let height = span(window[1], window[3]);
+
+        // Refuse windows larger than the file could possibly describe before
+        // allocating anything for them.
+        let pixel_size: usize = channels
+            .iter()
+            .map(|(_, pixel_type)| sample_size(*pixel_type))
+            .sum();
+        let capacity = match compression {
+            Compression::None => bytes.len(),
+            Compression::Zip => bytes.len().saturating_mul(MAX_DEFLATE_RATIO),
+        };
+        let pixel_bytes = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(pixel_size));
+        if pixel_bytes.is_none_or(|pixel_bytes| pixel_bytes > capacity) {
+            return Err(ImageError::Truncated);
+        }
+
+        let mut planes: Vec<Vec<f64>> = (0..channels.len())
+            .map(|_| vec![0.0; width * height])
+            .collect();
+        let block_count = height.div_ceil(lines_per_block);
+
+        for block in 0..block_count {
+            let entry = position
+                .checked_add(8 * block)
+                .ok_or(ImageError::Truncated)?;
+            let offset =
+                usize::try_from(read_u64(bytes, entry)?).map_err(|_| ImageError::Truncated)?;
+            let start = i64::from(read_i32(bytes, offset)?) - i64::from(window[1]);
+            if !(0..height as i64).contains(&start) {
+                return Err(ImageError::Malformed(format!(
+                    "block at line {}",
+                    start + i64::from(window[1])
+                )));
+            }
+            let start = start as usize;
+            let size = read_size(bytes, offset + 4, "block size")?;
+            let data_start = offset + 8;
+            let data_end = data_start.checked_add(size).ok_or(ImageError::Truncated)?;
+            let data = bytes
+                .get(data_start..data_end)
+                .ok_or(ImageError::Truncated)?;
+
+            let end = (start + lines_per_block).min(height);
+            let expected = pixel_size * width * (end - start);
+
+            let raw = if compression == Compression::Zip && size < expected {
+                deinterleave(&unpredict(&zlib::decompress(data)?))
+            } else {
+                data.to_vec()
+            };
+            if raw.len() < expected {
+                return Err(ImageError::Truncated);
+            }
+
+            let mut cursor = 0;
+            for y in start..end {
+                for (plane, (_, pixel_type)) in planes.iter_mut().zip(channels.iter()) {
+                    for x in 0..width {
+                        plane[y * width + x] = read_sample(&raw[cursor..], *pixel_type);
+                        cursor += sample_size(*pixel_type);
+                    }
+                }
+            }
+        }
+
+        let mut buffer = ChannelBuffer::new(width, height);
+        buffer.set_color_space(color_space);
+        for ((name, pixel_type), plane) in channels.into_iter().zip(planes) {
+            let pixel_type = match pixel_type {
+                1 => PixelType::Half,
+                _ => PixelType::Float,
+            };
+            buffer.add_channel(&name, pixel_type, plane);
+        }
+
+        Ok(buffer)
+    }
+}
+
+fn sorted_channels(buffer: &ChannelBuffer) -> Vec<&Channel> {
+    // OpenEXR requires the channel list in alphabetical order.
+    let mut channels: Vec<_> = buffer.channels().iter().collect();
+    channels.sort_by(|a, b| a.name().cmp(b.name()));
+
+    channels
+}
+
+fn pixel_type_id(pixel_type: PixelType) -> i32 {
+    match pixel_type {
+        PixelType::Half => 1,
+        PixelType::Float => 2,
+    }
+}
+
+fn sample_size(pixel_type: i32) -> usize {
+    match pixel_type {
+        1 => 2,
+        _ => 4,
+    }
+}
+
+fn read_sample(raw: &[u8], pixel_type: i32) -> f64 {
+    match pixel_type {
+        0 => f64::from(u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]])),
+        1 => f64::from(from_half(u16::from_le_bytes([raw[0], raw[1]]))),
+        _ => f64::from(f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]])),
+    }
+}
+
+fn write_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+fn parse_channels(value: &[u8]) -> Result<Vec<(String, i32)>, ImageError> {
+    let mut channels = Vec::new();
+    let mut position = 0;
+
+    loop {
+        let name = read_string(value, &mut position)?;
+        if name.is_empty() {
+            return Ok(channels);
+        }
+
+        let pixel_type = read_i32(value, position)?;
+        let x_sampling = read_i32(value, position + 8)?;
+        let y_sampling = read_i32(value, position + 12)?;
+        if !(0..=2).contains(&pixel_type) {
+            return Err(ImageError::Malformed(format!("pixel type {}", pixel_type)));
+        }
+        if x_sampling != 1 || y_sampling != 1 {
+            return Err(ImageError::Unsupported("subsampled channels".to_string()));
+        }
+
+        channels.push((name, pixel_type));
+        position += 16;
+    }
+}
+
+fn read_string(bytes: &[u8], position: &mut usize) -> Result<String, ImageError> {
+    let length = bytes
+        .get(*position..)
+        .and_then(|rest| rest.iter().position(|&byte| byte == 0))
+        .ok_or(ImageError::Truncated)?;
+    let string = String::from_utf8_lossy(&bytes[*position..*position + length]).to_string();
+    *position += length + 1;
+
+    Ok(string)
+}
+
+fn read_i32(bytes: &[u8], position: usize) -> Result<i32, ImageError> {
+    let end = position.checked_add(4).ok_or(ImageError::Truncated)?;
+    let slice = bytes.get(position..end).ok_or(ImageError::Truncated)?;
+
+    Ok(i32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]]))
+}
+
+fn read_f32(bytes: &[u8], position: usize) -> Result<f64, ImageError> {
+    Ok(f32::from_bits(read_i32(bytes, position)? as u32) as f64)
+}
+
+// A length stored as a signed 32-bit integer, which must not be negative.
+fn read_size(bytes: &[u8], position: usize, what: &str) -> Result<usize, ImageError> {
+    let size = read_i32(bytes, position)?;
+    usize::try_from(size).map_err(|_| ImageError::Malformed(format!("{} {}", what, size)))
+}
+
+fn read_u64(bytes: &[u8], position: usize) -> Result<u64, ImageError> {
+    let end = position.checked_add(8).ok_or(ImageError::Truncated)?;
+    let slice = bytes.get(position..end).ok_or(ImageError::Truncated)?;
+    let mut value = [0; 8];
+    value.copy_from_slice(slice);
+
+    Ok(u64::from_le_bytes(value))
+}
+
+// Splits the bytes into even and odd positions, which groups the high and
+// low bytes of the samples before compression.
+fn interleave(raw: &[u8]) -> Vec<u8> {
+    raw.iter()
+        .step_by(2)
+        .chain(raw.iter().skip(1).step_by(2))
+        .copied()
+        .collect()
+}
+
+fn deinterleave(data: &[u8]) -> Vec<u8> {
+    let (even, odd) = data.split_at(data.len().div_ceil(2));
+
+    (0..data.len())
+        .map(|i| if i % 2 == 0 { even[i / 2] } else { odd[i / 2] })
+        .collect()
+}
+
+fn predict(data: &[u8]) -> Vec<u8> {
+    let mut previous = 0u8;
+
+    data.iter()
+        .enumerate()
+        .map(|(i, &byte)| {
+            let predicted = if i == 0 {
+                byte
+            } else {
+                byte.wrapping_sub(previous).wrapping_add(128)
+            };
+            previous = byte;
+            predicted
+        })
+        .collect()
+}
+
+fn unpredict(data: &[u8]) -> Vec<u8> {
+    let mut output: Vec<u8> = Vec::with_capacity(data.len());
+
+    for (i, &byte) in data.iter().enumerate() {
+        let value = if i == 0 {
+            byte
+        } else {
+            output[i - 1].wrapping_add(byte).wrapping_sub(128)
+        };
+        output.push(value);
+    }
+
+    output
+}
+
+fn into_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 31 {
+        return sign | 0x7c00;
+    }
+
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+
+        // Subnormal half: shift the implicit leading one into the mantissa.
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        let half = mantissa >> shift;
+        let remainder = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round = remainder > halfway || (remainder == halfway && half & 1 == 1);
+
+        return sign | (half + u32::from(round)) as u16;
+    }
+
+    // Round to nearest even; a carry may correctly spill into the exponent.
+    let half = ((exponent as u32) << 10) | (mantissa >> 13);
+    let remainder = mantissa & 0x1fff;
+    let round = remainder > 0x1000 || (remainder == 0x1000 && half & 1 == 1);
+
+    sign | (half + u32::from(round)) as u16
+}
+
+fn from_half(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((half >> 10) & 0x1f);
+    let mantissa = f32::from(half & 0x3ff);
+
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        31 if mantissa == 0.0 => sign * f32::INFINITY,
+        31 => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixels::canvas::Canvas;
+    use crate::pixels::color::Color;
+
+    fn sample_buffer(width: usize, height: usize) -> ChannelBuffer {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                canvas.set_pixel(x, y, Color::new(x as f64 * 0.5, y as f64 * 4.0, 0.125));
+            }
+        }
+
+        let mut buffer = ChannelBuffer::from_canvas(&canvas, PixelType::Half);
+        let depth = (0..width * height).map(|i| i as f64 * 1.5).collect();
+        buffer.add_channel("Z", PixelType::Float, depth);
+        buffer.add_canvas("normal", &canvas, PixelType::Float);
+
+        buffer
+    }
+
+    #[test]
+    fn half_conversion() {
+        assert_eq!(0x0000, into_half(0.0));
+        assert_eq!(0x8000, into_half(-0.0));
+        assert_eq!(0x3c00, into_half(1.0));
+        assert_eq!(0xc000, into_half(-2.0));
+        assert_eq!(0x7bff, into_half(65504.0));
+        assert_eq!(0x7c00, into_half(1e6));
+        assert_eq!(0x0001, into_half(2f32.powi(-24)));
+        assert_eq!(0x0000, into_half(2f32.powi(-26)));
+        assert_eq!(0x3555, into_half(1.0 / 3.0));
+        assert_eq!(0x7e00, into_half(f32::NAN));
+
+        assert_eq!(1.0, from_half(0x3c00));
+        assert_eq!(-2.0, from_half(0xc000));
+        assert_eq!(65504.0, from_half(0x7bff));
+        assert_eq!(2f32.powi(-24), from_half(0x0001));
+        assert_eq!(f32::INFINITY, from_half(0x7c00));
+        assert!(from_half(0x7e00).is_nan());
+    }
+
+    #[test]
+    fn zip_predictor_round_trip() {
+        let raw: Vec<u8> = (0..=255).chain(0..17).collect();
+
+        assert_eq!(raw, deinterleave(&unpredict(&predict(&interleave(&raw)))));
+        assert_eq!(vec![0, 2, 4, 1, 3], interleave(&[0, 1, 2, 3, 4]));
+        assert_eq!(vec![5, 129, 126], predict(&[5, 6, 4]));
+    }
+
+    #[test]
+    fn new_exr_header() {
+        let buffer = ChannelBuffer::new(3, 2);
+        let header = EXR::create_header(&buffer, Compression::Zip);
+
+        let mut expected = b"channels\0chlist\0\x01\0\0\0\0".to_vec();
+        expected.extend_from_slice(b"compression\0compression\0\x01\0\0\0\x03");
+        expected.extend_from_slice(b"dataWindow\0box2i\0\x10\0\0\0");
+        expected.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(expected, header[..expected.len()]);
+        assert_eq!(Some(&0), header.last());
+    }
+
+    #[test]
+    fn new_exr_structure() {
+        let buffer = sample_buffer(2, 3);
+        let exr = EXR::new(&buffer, Compression::None).get();
+
+        assert_eq!(MAGIC, exr[..4]);
+        assert_eq!(VERSION, exr[4..8]);
+
+        // One block per scanline, each holding the channels alphabetically.
+        let header_size = 8 + EXR::create_header(&buffer, Compression::None).len();
+        let first_block = read_u64(&exr, header_size).unwrap() as usize;
+        assert_eq!(header_size + 3 * 8, first_block);
+        assert_eq!(0, read_i32(&exr, first_block).unwrap());
+        let row_size = 2 * (2 + 2 + 2 + 2 + 4 + 4 + 4 + 4 + 4);
+        assert_eq!(row_size as i32, read_i32(&exr, first_block + 4).unwrap());
+        assert_eq!(first_block + 8 + row_size, exr.len() - 2 * (8 + row_size));
+    }
+
+    #[test]
+    fn read_exr_round_trip() {
+        for compression in [Compression::None, Compression::Zip] {
+            let buffer = sample_buffer(20, 37);
+            let read = EXR::read(&EXR::new(&buffer, compression).get()).unwrap();
+
+            assert_eq!(buffer.width(), read.width());
+            assert_eq!(buffer.height(), read.height());
+            assert_eq!(buffer.channels().len(), read.channels().len());
+
+            for channel in buffer.channels() {
+                let other = read.channel(channel.name()).unwrap();
+                assert_eq!(channel.pixel_type(), other.pixel_type());
+                // Every sample value in the fixture is exactly representable as a half.
+                assert_eq!(channel.samples(), other.samples());
+            }
+        }
+    }
+
+    #[test]
+    fn read_exr_chromaticities() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set_color_space(ColorSpace::AcesCg);
+        canvas.set_pixel(0, 0, Color::new(0.25, 0.5, 0.125));
+
+        let mut buffer = ChannelBuffer::new(1, 1);
+        buffer.set_color_space(ColorSpace::AcesCg);
+        buffer.add_canvas("", &canvas, PixelType::Float);
+        let read = EXR::read(&EXR::new(&buffer, Compression::None).get()).unwrap();
+
+        assert_eq!(ColorSpace::AcesCg, read.color_space());
+        let read = read.to_canvas("").unwrap();
+        assert_eq!(ColorSpace::AcesCg, read.color_space());
+        assert_eq!(Color::new(0.25, 0.5, 0.125), read.get_pixel(0, 0));
+
+        let rec709 = EXR::read(&EXR::new(&sample_buffer(2, 2), Compression::None).get()).unwrap();
+        assert_eq!(ColorSpace::Rec709, rec709.color_space());
+    }
+
+    #[test]
+    fn zip_compression_shrinks_output() {
+        let buffer = sample_buffer(64, 64);
+
+        let uncompressed = EXR::new(&buffer, Compression::None).get();
+        let compressed = EXR::new(&buffer, Compression::Zip).get();
+        assert!(compressed.len() < uncompressed.len());
+    }
+
+    #[test]
+    fn read_exr_errors() {
+        assert!(matches!(
+            EXR::read(b"P6\n1 1\n255\n"),
+            Err(ImageError::Malformed(_))
+        ));
+
+        let mut tiled = MAGIC.to_vec();
+        tiled.extend_from_slice(&[2, 2, 0, 0]);
+        assert!(matches!(EXR::read(&tiled), Err(ImageError::Unsupported(_))));
+
+        let exr = EXR::new(&sample_buffer(4, 4), Compression::None).get();
+        assert_eq!(
+            Err(ImageError::Truncated),
+            EXR::read(&exr[..exr.len() - 10]).map(|_| ())
+        );
+    }
+
+    // A header with one float channel and the given data window, followed
+    // by `rest`.
+    fn exr_with_window(window: [i32; 4], rest: &[u8]) -> Vec<u8> {
+        let mut channels = b"R\0".to_vec();
+        channels.extend_from_slice(&2i32.to_le_bytes());
+        channels.extend_from_slice(&[0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0]);
+        let window: Vec<u8> = window
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+
+        let mut exr = MAGIC.to_vec();
+        exr.extend_from_slice(&VERSION);
+        write_attribute(&mut exr, "channels", "chlist", &channels);
+        write_attribute(&mut exr, "compression", "compression", &[0]);
+        write_attribute(&mut exr, "dataWindow", "box2i", &window);
+        exr.push(0);
+        exr.extend_from_slice(rest);
+        exr
+    }
+
+    #[test]
+    fn exr_canvas_keeps_alpha() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel_with_alpha(0, 0, Color::new(0.25, 0.5, 0.0), 0.5);
+        let buffer = ChannelBuffer::from_canvas(&canvas, PixelType::Half);
+
+        let read = EXR::read(&EXR::new(&buffer, Compression::None).get()).unwrap();
+        let decoded = read.to_canvas("").unwrap();
+
+        assert!(read.channel("A").is_some());
+        assert_eq!(canvas.pixels(), decoded.pixels());
+        assert_eq!(&[0.5, 0.0], decoded.alphas());
+    }
+
+    #[test]
+    fn read_exr_rejects_negative_attribute_size() {
+        let mut exr = MAGIC.to_vec();
+        exr.extend_from_slice(&VERSION);
+        exr.extend_from_slice(b"channels\0chlist\0");
+        exr.extend_from_slice(&(-1i32).to_le_bytes());
+
+        assert!(matches!(EXR::read(&exr), Err(ImageError::Malformed(_))));
+    }
+
+    #[test]
+    fn read_exr_rejects_block_outside_window() {
+        for line in [-1, 2, i32::MIN] {
+            // One offset entry pointing just past the table.
+            let mut rest = Vec::new();
+            let offset = exr_with_window([0, 0, 0, 1], &[]).len() + 8;
+            rest.extend_from_slice(&(offset as u64).to_le_bytes());
+            rest.extend_from_slice(&line.to_le_bytes());
+            rest.extend_from_slice(&4i32.to_le_bytes());
+            rest.extend_from_slice(&[0; 4]);
+
+            let exr = exr_with_window([0, 0, 0, 1], &rest);
+            assert!(matches!(EXR::read(&exr), Err(ImageError::Malformed(_))));
+        }
+    }
+
+    #[test]
+    fn read_exr_rejects_negative_block_size() {
+        let mut rest = Vec::new();
+        let offset = exr_with_window([0, 0, 0, 0], &[]).len() + 8;
+        rest.extend_from_slice(&(offset as u64).to_le_bytes());
+        rest.extend_from_slice(&0i32.to_le_bytes());
+        rest.extend_from_slice(&(-1i32).to_le_bytes());
+
+        let exr = exr_with_window([0, 0, 0, 0], &rest);
+        assert!(matches!(EXR::read(&exr), Err(ImageError::Malformed(_))));
+    }
+
+    #[test]
+    fn read_exr_rejects_missing_channels() {
+        let window: Vec<u8> = [0, 0, i32::MAX - 1, i32::MAX - 1]
+            .iter()
+            .flat_map(|value: &i32| value.to_le_bytes())
+            .collect();
+        let mut exr = MAGIC.to_vec();
+        exr.extend_from_slice(&VERSION);
+        write_attribute(&mut exr, "compression", "compression", &[0]);
+        write_attribute(&mut exr, "dataWindow", "box2i", &window);
+        exr.push(0);
+
+        assert_eq!(
+            Err(ImageError::Malformed("missing channels".to_string())),
+            EXR::read(&exr).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn read_exr_rejects_oversized_window() {
+        // Spanning the whole i32 range must not overflow, and a window far
+        // larger than the file must fail before anything is allocated.
+        for window in [
+            [i32::MIN, i32::MIN, i32::MAX, i32::MAX],
+            [0, 0, 99_999, 99_999],
+        ] {
+            assert_eq!(
+                Err(ImageError::Truncated),
+                EXR::read(&exr_with_window(window, &[])).map(|_| ())
+            );
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::color_space::ColorSpace;
+use crate::pixels::error::ImageError;
+use crate::pixels::rgb::Rgb;
+
+// Run length encoding is only defined for scanlines within these bounds.
+const MIN_RLE_WIDTH: usize = 8;
+const MAX_RLE_WIDTH: usize = 0x7fff;
+
+pub struct HDR {
+    content: Vec<u8>,
+}
+
+impl HDR {
+    pub fn new(canvas: &Canvas) -> Self {
+        let canvas = &canvas.to_color_space(ColorSpace::Rec709);
+        let mut header = HDR::create_header(canvas);
+        let mut body = HDR::create_body(canvas);
+
+        let mut content: Vec<u8> = Vec::with_capacity(header.len() + body.len());
+        content.append(&mut header);
+        content.append(&mut body);
+
+        HDR { content }
+    }
+
+    fn create_header(canvas: &Canvas) -> Vec<u8> {
+        format!(
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            canvas.height(),
+            canvas.width()
+        )
+        .into_bytes()
+    }
+
+    fn create_body(canvas: &Canvas) -> Vec<u8> {
+        let mut body: Vec<u8> = Vec::new();
+        let width = canvas.width();
+
+        for y in 0..canvas.height() {
+            let scanline: Vec<[u8; 4]> = (0..width)
+                .map(|x| into_rgbe(canvas.get_pixel(x, y)))
+                .collect();
+
+            if !(MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&width) {
+                scanline
+                    .iter()
+                    .for_each(|rgbe| body.extend_from_slice(rgbe));
+                continue;
+            }
+
+            body.extend_from_slice(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+            for channel in 0..4 {
+                let values: Vec<u8> = scanline.iter().map(|rgbe| rgbe[channel]).collect();
+                encode_runs(&values, &mut body);
+            }
+        }
+
+        body
+    }
+
+    pub fn get(&self) -> Vec<u8> {
+        self.content.clone()
+    }
+
+    pub fn read(bytes: &[u8]) -> Result<Canvas, ImageError> {
+        let mut position = 0;
+        let mut next_line = || {
+            let start = position;
+            let end = bytes[start..]
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .ok_or(ImageError::Truncated)?;
+            position = start + end + 1;
+
+            Ok::<_, ImageError>(String::from_utf8_lossy(&bytes[start..start + end]).to_string())
+        };
+
+        if !next_line()?.starts_with("#?") {
+            return Err(ImageError::Malformed(
+                "missing Radiance signature".to_string(),
+            ));
+        }
+
+        loop {
+            let line = next_line()?;
+
+            if line.is_empty() {
+                break;
+            }
+            if let Some(format) = line.strip_prefix("FORMAT=") {
+                if format != "32-bit_rle_rgbe" {
+                    return Err(ImageError::Unsupported(format!(
+                        "Radiance format {}",
+                        format
+                    )));
+                }
+            }
+        }
+
+        let resolution = next_line()?;
+        let (height, width) = match resolution.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["-Y", height, "+X", width] => (height.parse(), width.parse()),
+            _ => {
+                return Err(ImageError::Unsupported(format!(
+                    "Radiance orientation {}",
+                    resolution
+                )))
+            }
+        };
+        let height: usize =
+            height.map_err(|_| ImageError::Malformed(format!("resolution {}", resolution)))?;
+        let width: usize =
+            width.map_err(|_| ImageError::Malformed(format!("resolution {}", resolution)))?;
+
+        // Rows are decoded before the canvas is allocated, so a header
+        // claiming a huge image fails once the data runs out.
+        let mut data = &bytes[position..];
+        let mut scanlines = Vec::new();
+        if width > 0 {
+            for _ in 0..height {
+                scanlines.push(read_scanline(&mut data, width)?);
+            }
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        for (y, scanline) in scanlines.iter().enumerate() {
+            for (x, rgbe) in scanline.iter().enumerate() {
+                canvas.set_pixel(x, y, from_rgbe(*rgbe));
+            }
+        }
+
+        Ok(canvas)
+    }
+}
+
+// Shared exponent encoding: the mantissas are scaled by the largest channel.
+fn into_rgbe(color: Color) -> [u8; 4] {
+    let largest = color.r().max(color.g()).max(color.b());
+
+    if largest < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    let mut exponent = largest.log2().floor() as i32 + 1;
+    // Guard against rounding in `log2` right at powers of two.
+    if largest / 2f64.powi(exponent) >= 1.0 {
+        exponent += 1;
+    }
+    // The biased exponent has to fit a byte, so larger values saturate at
+    // the brightest representable colour.
+    let exponent = exponent.min(127);
+    let scale = 256.0 / 2f64.powi(exponent);
+
+    [
+        (color.r().max(0.0) * scale) as u8,
+        (color.g().max(0.0) * scale) as u8,
+        (color.b().max(0.0) * scale) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+fn from_rgbe(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::default();
+    }
+
+    let scale = 2f64.powi(i32::from(rgbe[3]) - 136);
+
+    Color::new(
+        (f64::from(rgbe[0]) + 0.5) * scale,
+        (f64::from(rgbe[1]) + 0.5) * scale,
+        (f64::from(rgbe[2]) + 0.5) * scale,
+    )
+}
+
+fn encode_runs(values: &[u8], body: &mut Vec<u8>) {
+    let mut position = 0;
+
+    while position < values.len() {
+        let run = values[position..]
+            .iter()
+            .take(127)
+            .take_while(|&&value| value == values[position])
+            .count();
+
+        if run > 2 {
+            body.push(128 + run as u8);
+            body.push(values[position]);
+            position += run;
+            continue;
+        }
+
+        // Gather literals until the next run worth encoding.
+        let start = position;
+        while position < values.len() && position - start < 128 {
+            let ahead = values[position..]
+                .iter()
+                .take(3)
+                .take_while(|&&value| value == values[position])
+                .count();
+            if ahead > 2 {
+                break;
+            }
+            position += 1;
+        }
+
+        body.push((position - start) as u8);
+        body.extend_from_slice(&values[start..position]);
+    }
+}
+
+fn read_scanline(data: &mut &[u8], width: usize) -> Result<Vec<[u8; 4]>, ImageError> {
+    let marker = match data.get(..4) {
+        Some(marker) if (MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&width) => marker,
+        _ => &[0; 4],
+    };
+    let encoded = marker[0] == 2 && marker[1] == 2 && marker[2] & 0x80 == 0;
+
+    if !encoded {
+        let flat = take(data, width.checked_mul(4).ok_or(ImageError::Truncated)?)?;
+        return Ok(flat
+            .chunks_exact(4)
+            .map(|rgbe| [rgbe[0], rgbe[1], rgbe[2], rgbe[3]])
+            .collect());
+    }
+
+    if (usize::from(marker[2]) << 8 | usize::from(marker[3])) != width {
+        return Err(ImageError::Malformed("scanline width mismatch".to_string()));
+    }
+    take(data, 4)?;
+
+    let mut scanline = vec![[0u8; 4]; width];
+    for channel in 0..4 {
+        let mut x = 0;
+
+        while x < width {
+            let count = take(data, 1)?[0];
+
+            if count > 128 {
+                let length = usize::from(count - 128);
+                let value = take(data, 1)?[0];
+                if x + length > width {
+                    return Err(ImageError::Malformed("run exceeds scanline".to_string()));
+                }
+                scanline[x..x + length]
+                    .iter_mut()
+                    .for_each(|rgbe| rgbe[channel] = value);
+                x += length;
+            } else {
+                let length = usize::from(count);
+                if length == 0 || x + length > width {
+                    return Err(ImageError::Malformed("run exceeds scanline".to_string()));
+                }
+                for &value in take(data, length)? {
+                    scanline[x][channel] = value;
+                    x += 1;
+                }
+            }
+        }
+    }
+
+    Ok(scanline)
+}
+
+fn take<'a>(data: &mut &'a [u8], count: usize) -> Result<&'a [u8], ImageError> {
+    let (head, tail) = data.split_at_checked(count).ok_or(ImageError::Truncated)?;
+    *data = tail;
+
+    Ok(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_hdr_header() {
+        let canvas = Canvas::new(5, 3);
+        let header = HDR::create_header(&canvas);
+
+        let desired_result = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 3 +X 5\n";
+        assert_eq!(desired_result.to_string().into_bytes(), header);
+    }
+
+    #[test]
+    fn rgbe_conversion() {
+        assert_eq!([0, 0, 0, 0], into_rgbe(Color::default()));
+        assert_eq!([128, 64, 0, 129], into_rgbe(Color::new(1.0, 0.5, 0.0)));
+        assert_eq!([128, 0, 0, 137], into_rgbe(Color::new(256.0, -1.0, 0.0)));
+        assert_eq!([255, 0, 0, 255], into_rgbe(Color::new(1e300, 0.0, 0.0)));
+
+        let color = from_rgbe([128, 64, 0, 129]);
+        assert!((color.r() - 1.0).abs() < 1e-2);
+        assert!((color.g() - 0.5).abs() < 1e-2);
+        assert_eq!(Color::default(), from_rgbe([10, 10, 10, 0]));
+    }
+
+    #[test]
+    fn run_length_encoding() {
+        let mut body = Vec::new();
+        encode_runs(&[1, 2, 3, 3, 3, 3, 4, 4], &mut body);
+
+        assert_eq!(vec![2, 1, 2, 132, 3, 2, 4, 4], body);
+    }
+
+    #[test]
+    fn read_hdr_round_trip() {
+        for width in [3, 20] {
+            let mut canvas = Canvas::new(width, 2);
+            for x in 0..width {
+                canvas.set_pixel(x, 0, Color::new(1000.0, 2.0, 0.25));
+                canvas.set_pixel(x, 1, Color::new(x as f64, 0.0, 0.001));
+            }
+
+            let read = HDR::read(&HDR::new(&canvas).get()).unwrap();
+
+            assert_eq!(canvas.width(), read.width());
+            assert_eq!(canvas.height(), read.height());
+            for (expected, actual) in canvas.pixels().iter().zip(read.pixels().iter()) {
+                let largest = expected.r().max(expected.g()).max(expected.b());
+                // Each channel keeps eight bits relative to the largest one.
+                let tolerance = largest / 128.0;
+                assert!((expected.r() - actual.r()).abs() <= tolerance);
+                assert!((expected.g() - actual.g()).abs() <= tolerance);
+                assert!((expected.b() - actual.b()).abs() <= tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn read_hdr_errors() {
+        assert!(matches!(
+            HDR::read(b"P6\n1 1\n"),
+            Err(ImageError::Malformed(_))
+        ));
+        assert_eq!(
+            Err(ImageError::Unsupported(
+                "Radiance format 32-bit_rle_xyze".to_string()
+            )),
+            HDR::read(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n").map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::Unsupported(
+                "Radiance orientation +Y 1 +X 1".to_string()
+            )),
+            HDR::read(b"#?RADIANCE\n\n+Y 1 +X 1\n").map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::Truncated),
+            HDR::read(b"#?RADIANCE\n\n-Y 1 +X 2\n\x00\x00").map(|_| ())
+        );
+        // A huge header without any data fails without allocating the image.
+        assert_eq!(
+            Err(ImageError::Truncated),
+            HDR::read(b"#?RADIANCE\n\n-Y 100000 +X 100000\n").map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::Truncated),
+            HDR::read(b"#?RADIANCE\n\n-Y 1 +X 18446744073709551615\n").map(|_| ())
+        );
+    }
+}
@@ -1,4 +1,23 @@
+pub mod blackbody;
 pub mod canvas;
+pub mod channel_buffer;
 pub mod color;
+pub mod color_space;
+pub mod compare;
+pub mod compositing;
+pub mod conversion;
+pub mod drawing;
+pub mod encoding;
+pub mod error;
+pub mod exr;
+pub mod font;
+pub mod hdr;
+pub mod pfm;
+pub mod png;
 pub mod ppm;
-mod rgb;
+pub mod rect;
+pub(crate) mod rgb;
+pub mod spectral;
+pub mod tone_mapping;
+pub mod transform;
+mod zlib;
@@ -0,0 +1,200 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::color_space::ColorSpace;
+use crate::pixels::error::ImageError;
+use crate::pixels::rgb::Rgb;
+
+pub struct PFM {
+    content: Vec<u8>,
+}
+
+impl PFM {
+    pub fn new(canvas: &Canvas) -> Self {
+        let canvas = &canvas.to_color_space(ColorSpace::Rec709);
+        let mut header = PFM::create_header(canvas);
+        let mut body = PFM::create_body(canvas);
+
+        let mut content: Vec<u8> = Vec::with_capacity(header.len() + body.len());
+        content.append(&mut header);
+        content.append(&mut body);
+
+        PFM { content }
+    }
+
+    fn create_header(canvas: &Canvas) -> Vec<u8> {
+        // A negative scale marks the samples as little endian.
+        format!("PF\n{} {}\n-1.0\n", canvas.width(), canvas.height()).into_bytes()
+    }
+
+    fn create_body(canvas: &Canvas) -> Vec<u8> {
+        let mut body: Vec<u8> = Vec::with_capacity(12 * canvas.width() * canvas.height());
+
+        // Scanlines are stored from the bottom of the image to the top.
+        for y in (0..canvas.height()).rev() {
+            for x in 0..canvas.width() {
+                let color = canvas.get_pixel(x, y);
+
+                for hue in [color.r(), color.g(), color.b()] {
+                    body.extend_from_slice(&(hue as f32).to_le_bytes());
+                }
+            }
+        }
+
+        body
+    }
+
+    pub fn get(&self) -> Vec<u8> {
+        self.content.clone()
+    }
+
+    pub fn read(bytes: &[u8]) -> Result<Canvas, ImageError> {
+        // Magic, width, height and scale, separated by whitespace.
+        let mut tokens = Vec::with_capacity(4);
+        let mut position = 0;
+
+        while tokens.len() < 4 {
+            while matches!(bytes.get(position), Some(byte) if byte.is_ascii_whitespace()) {
+                position += 1;
+            }
+            let start = position;
+            while matches!(bytes.get(position), Some(byte) if !byte.is_ascii_whitespace()) {
+                position += 1;
+            }
+            if start == position {
+                return Err(ImageError::Truncated);
+            }
+            tokens.push(String::from_utf8_lossy(&bytes[start..position]).to_string());
+        }
+
+        let channels = match tokens[0].as_str() {
+            "PF" => 3,
+            "Pf" => 1,
+            magic => return Err(ImageError::Unsupported(format!("PFM magic {}", magic))),
+        };
+        let parse_size = |token: &str| {
+            token
+                .parse::<usize>()
+                .map_err(|_| ImageError::Malformed(format!("expected a size, found {}", token)))
+        };
+        let width = parse_size(&tokens[1])?;
+        let height = parse_size(&tokens[2])?;
+        let scale: f64 = tokens[3]
+            .parse()
+            .map_err(|_| ImageError::Malformed(format!("expected a scale, found {}", tokens[3])))?;
+        let little_endian = scale < 0.0;
+
+        let body = bytes.get(position + 1..).ok_or(ImageError::Truncated)?;
+        // A size too large to count can't be in the file either.
+        let count = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .ok_or(ImageError::Truncated)?;
+        if count
+            .checked_mul(4)
+            .is_none_or(|length| body.len() < length)
+        {
+            return Err(ImageError::Truncated);
+        }
+
+        let samples: Vec<f64> = body
+            .chunks_exact(4)
+            .take(count)
+            .map(|chunk| {
+                let chunk = [chunk[0], chunk[1], chunk[2], chunk[3]];
+                let value = if little_endian {
+                    f32::from_le_bytes(chunk)
+                } else {
+                    f32::from_be_bytes(chunk)
+                };
+                f64::from(value)
+            })
+            .collect();
+
+        let mut canvas = Canvas::new(width, height);
+
+        for (i, sample) in samples.chunks(channels).enumerate() {
+            let color = match channels {
+                3 => Color::new(sample[0], sample[1], sample[2]),
+                _ => Color::new(sample[0], sample[0], sample[0]),
+            };
+
+            canvas.set_pixel(i % width, height - 1 - i / width, color);
+        }
+
+        Ok(canvas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_pfm_header() {
+        let canvas = Canvas::new(5, 3);
+        let header = PFM::create_header(&canvas);
+
+        assert_eq!("PF\n5 3\n-1.0\n".to_string().into_bytes(), header);
+    }
+
+    #[test]
+    fn new_pfm_body() {
+        let mut canvas = Canvas::new(1, 2);
+        canvas.set_pixel(0, 0, Color::new(1.5, 0.0, -2.0));
+        canvas.set_pixel(0, 1, Color::new(0.25, 100.0, 0.5));
+
+        let body = PFM::create_body(&canvas);
+
+        let mut desired_result = Vec::new();
+        for value in [0.25f32, 100.0, 0.5, 1.5, 0.0, -2.0] {
+            desired_result.extend_from_slice(&value.to_le_bytes());
+        }
+        assert_eq!(desired_result, body);
+    }
+
+    #[test]
+    fn read_pfm_round_trip() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.set_pixel(0, 0, Color::new(12.5, 0.0, 0.0));
+        canvas.set_pixel(2, 1, Color::new(0.0, 0.125, 1000.0));
+
+        let read = PFM::read(&PFM::new(&canvas).get()).unwrap();
+
+        assert_eq!(canvas.width(), read.width());
+        assert_eq!(canvas.height(), read.height());
+        assert_eq!(canvas.pixels(), read.pixels());
+    }
+
+    #[test]
+    fn read_pfm_big_endian_greyscale() {
+        let mut bytes = b"Pf\n2 1\n1.0\n".to_vec();
+        bytes.extend_from_slice(&2.0f32.to_be_bytes());
+        bytes.extend_from_slice(&0.5f32.to_be_bytes());
+
+        let canvas = PFM::read(&bytes).unwrap();
+
+        assert_eq!(Color::new(2.0, 2.0, 2.0), canvas.get_pixel(0, 0));
+        assert_eq!(Color::new(0.5, 0.5, 0.5), canvas.get_pixel(1, 0));
+    }
+
+    #[test]
+    fn read_pfm_errors() {
+        assert_eq!(
+            Err(ImageError::Unsupported("PFM magic P6".to_string())),
+            PFM::read(b"P6\n1 1\n255\n\x00\x00\x00").map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::Truncated),
+            PFM::read(b"PF\n1 1\n-1.0\n\x00\x00").map(|_| ())
+        );
+        assert_eq!(Err(ImageError::Truncated), PFM::read(b"PF\n1").map(|_| ()));
+        assert_eq!(
+            Err(ImageError::Truncated),
+            PFM::read(b"PF 1 1 -1.0").map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::Truncated),
+            PFM::read(b"PF\n4611686018427387904 4\n-1.0\n").map(|_| ())
+        );
+    }
+}
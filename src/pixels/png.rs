@@ -0,0 +1,786 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::{into_u8, Color};
+use crate::pixels::color_space::ColorSpace;
+use crate::pixels::encoding::Encoding;
+use crate::pixels::error::ImageError;
+use crate::pixels::rgb::Rgb;
+use crate::pixels::zlib;
+
+const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+const CRC_TABLE: [u32; 256] = crc_table();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorType {
+    Rgb,
+    Rgba,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitDepth {
+    Eight,
+    Sixteen,
+}
+
+pub struct PNG {
+    content: Vec<u8>,
+}
+
+impl PNG {
+    pub fn new(canvas: &Canvas) -> Self {
+        PNG::with_format(canvas, ColorType::Rgb, BitDepth::Eight, Encoding::default())
+    }
+
+    pub fn with_format(
+        canvas: &Canvas,
+        color_type: ColorType,
+        bit_depth: BitDepth,
+        encoding: Encoding,
+    ) -> Self {
+        PNG::with_color_space(canvas, color_type, bit_depth, encoding, ColorSpace::Rec709)
+    }
+
+    // Like `with_format`, converting the canvas into `color_space` and
+    // tagging the file with its chromaticities unless it is Rec.709.
+    pub fn with_color_space(
+        canvas: &Canvas,
+        color_type: ColorType,
+        bit_depth: BitDepth,
+        encoding: Encoding,
+        color_space: ColorSpace,
+    ) -> Self {
+        let canvas = &canvas.to_color_space(color_space);
+        let mut content = SIGNATURE.to_vec();
+
+        write_chunk(
+            &mut content,
+            b"IHDR",
+            &PNG::create_header(canvas, color_type, bit_depth),
+        );
+        if color_space != ColorSpace::Rec709 {
+            // The sRGB chunk would override the primaries, so other spaces
+            // describe their transfer curve with an approximate gamma.
+            let gamma = match encoding {
+                Encoding::Linear => 1.0,
+                Encoding::Srgb => 2.2,
+                Encoding::Gamma(gamma) => gamma,
+            };
+            write_chunk(
+                &mut content,
+                b"gAMA",
+                &into_png_fixed(1.0 / gamma).to_be_bytes(),
+            );
+            write_chunk(&mut content, b"cHRM", &chromaticities_chunk(color_space));
+        } else if encoding == Encoding::Srgb {
+            // Perceptual rendering intent.
+            write_chunk(&mut content, b"sRGB", &[0]);
+        }
+        write_chunk(
+            &mut content,
+            b"IDAT",
+            &zlib::compress(&PNG::create_body(canvas, color_type, bit_depth, encoding)),
+        );
+        write_chunk(&mut content, b"IEND", &[]);
+
+        PNG { content }
+    }
+
+    fn create_header(canvas: &Canvas, color_type: ColorType, bit_depth: BitDepth) -> Vec<u8> {
+        let mut header = Vec::with_capacity(13);
+
+        header.extend_from_slice(&(canvas.width() as u32).to_be_bytes());
+        header.extend_from_slice(&(canvas.height() as u32).to_be_bytes());
+        header.push(match bit_depth {
+            BitDepth::Eight => 8,
+            BitDepth::Sixteen => 16,
+        });
+        header.push(match color_type {
+            ColorType::Rgb => 2,
+            ColorType::Rgba => 6,
+        });
+        // Deflate compression, adaptive filtering and no interlacing.
+        header.extend_from_slice(&[0, 0, 0]);
+
+        header
+    }
+
+    fn create_body(
+        canvas: &Canvas,
+        color_type: ColorType,
+        bit_depth: BitDepth,
+        encoding: Encoding,
+    ) -> Vec<u8> {
+        let channels = match color_type {
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        };
+        let sample_size = match bit_depth {
+            BitDepth::Eight => 1,
+            BitDepth::Sixteen => 2,
+        };
+        let pixel_size = channels * sample_size;
+        let row_size = canvas.width() * pixel_size;
+
+        let mut body = Vec::with_capacity((row_size + 1) * canvas.height());
+        let mut previous = vec![0; row_size];
+        let mut row = Vec::with_capacity(row_size);
+
+        for y in 0..canvas.height() {
+            row.clear();
+
+            for x in 0..canvas.width() {
+                let alpha = canvas.get_alpha(x, y);
+                let mut color = canvas.get_pixel(x, y);
+
+                // PNG stores straight colours alongside the alpha channel.
+                if color_type == ColorType::Rgba {
+                    color = if alpha > 0.0 {
+                        color * (1.0 / alpha)
+                    } else {
+                        Color::default()
+                    };
+                }
+                let color = encoding.encode_color(color);
+
+                match bit_depth {
+                    BitDepth::Eight => {
+                        let (r, g, b) = color.to_tuple();
+                        row.extend_from_slice(&[r, g, b]);
+
+                        if color_type == ColorType::Rgba {
+                            row.push(into_u8(alpha));
+                        }
+                    }
+                    BitDepth::Sixteen => {
+                        for hue in [color.r(), color.g(), color.b()] {
+                            row.extend_from_slice(&into_u16(hue).to_be_bytes());
+                        }
+
+                        if color_type == ColorType::Rgba {
+                            row.extend_from_slice(&into_u16(alpha).to_be_bytes());
+                        }
+                    }
+                }
+            }
+
+            let (filter, filtered) = (0..5)
+                .map(|filter| (filter, apply_filter(filter, &row, &previous, pixel_size)))
+                .min_by_key(|(_, filtered)| {
+                    // Minimum sum of absolute differences, the usual heuristic.
+                    filtered
+                        .iter()
+                        .map(|&byte| (byte as i8).unsigned_abs() as usize)
+                        .sum::<usize>()
+                })
+                .unwrap();
+
+            body.push(filter);
+            body.extend_from_slice(&filtered);
+            previous.clone_from(&row);
+        }
+
+        body
+    }
+
+    pub fn get(&self) -> Vec<u8> {
+        self.content.clone()
+    }
+
+    pub fn read(bytes: &[u8]) -> Result<Canvas, ImageError> {
+        PNG::read_with_encoding(bytes, Encoding::default())
+    }
+
+    pub fn read_with_encoding(bytes: &[u8], encoding: Encoding) -> Result<Canvas, ImageError> {
+        if bytes.len() < SIGNATURE.len() || bytes[..SIGNATURE.len()] != SIGNATURE {
+            return Err(ImageError::Malformed("missing PNG signature".to_string()));
+        }
+
+        let mut position = SIGNATURE.len();
+        let mut header = None;
+        let mut palette = Vec::new();
+        let mut data = Vec::new();
+        let mut srgb = false;
+        let mut chromaticities = None;
+
+        loop {
+            let length = read_u32(bytes, position)? as usize;
+            let kind = bytes
+                .get(position + 4..position + 8)
+                .ok_or(ImageError::Truncated)?;
+            let body = bytes
+                .get(position + 8..position + 8 + length)
+                .ok_or(ImageError::Truncated)?;
+            let checksum = read_u32(bytes, position + 8 + length)?;
+
+            if crc32(&bytes[position + 4..position + 8 + length]) != checksum {
+                return Err(ImageError::Malformed(format!(
+                    "checksum mismatch in {} chunk",
+                    String::from_utf8_lossy(kind)
+                )));
+            }
+
+            match kind {
+                b"IHDR" => header = Some(Header::parse(body)?),
+                b"PLTE" => palette = body.to_vec(),
+                b"sRGB" => srgb = true,
+                b"cHRM" => chromaticities = Some(parse_chromaticities(body)?),
+                b"IDAT" => data.extend_from_slice(body),
+                b"IEND" => break,
+                _ => {}
+            }
+
+            position += 12 + length;
+        }
+
+        let header = header.ok_or_else(|| ImageError::Malformed("missing IHDR".to_string()))?;
+        let raw = zlib::decompress(&data)?;
+        let mut canvas = header.decode(&raw, &palette, encoding)?;
+
+        // An sRGB chunk takes precedence over the chromaticities.
+        if let (false, Some((primaries, white_point))) = (srgb, chromaticities) {
+            let color_space = ColorSpace::from_chromaticities(primaries, white_point)
+                .ok_or_else(|| ImageError::Unsupported("PNG chromaticities".to_string()))?;
+            canvas.set_color_space(color_space);
+        }
+
+        Ok(canvas)
+    }
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: usize,
+    color_type: u8,
+}
+
+impl Header {
+    fn parse(body: &[u8]) -> Result<Self, ImageError> {
+        if body.len() != 13 {
+            return Err(ImageError::Malformed("IHDR length".to_string()));
+        }
+
+        let header = Header {
+            width: read_u32(body, 0)? as usize,
+            height: read_u32(body, 4)? as usize,
+            bit_depth: usize::from(body[8]),
+            color_type: body[9],
+        };
+
+        let valid_depths: &[usize] = match header.color_type {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            other => return Err(ImageError::Malformed(format!("color type {}", other))),
+        };
+        if !valid_depths.contains(&header.bit_depth) {
+            return Err(ImageError::Malformed(format!(
+                "bit depth {} for color type {}",
+                header.bit_depth, header.color_type
+            )));
+        }
+        if body[12] != 0 {
+            return Err(ImageError::Unsupported("interlaced PNG".to_string()));
+        }
+
+        Ok(header)
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn decode(&self, raw: &[u8], palette: &[u8], encoding: Encoding) -> Result<Canvas, ImageError> {
+        let bits_per_pixel = self.channels() * self.bit_depth;
+        // A size too large to count can't be in the data either.
+        let row_size = self
+            .width
+            .checked_mul(bits_per_pixel)
+            .ok_or(ImageError::Truncated)?
+            .div_ceil(8);
+        let pixel_size = (bits_per_pixel / 8).max(1);
+        let max_value = ((1u32 << self.bit_depth) - 1) as f64;
+
+        let length = (row_size + 1).checked_mul(self.height);
+        if length.is_none_or(|length| raw.len() < length) {
+            return Err(ImageError::Truncated);
+        }
+
+        let mut canvas = Canvas::new(self.width, self.height);
+        let mut previous = vec![0; row_size];
+
+        for y in 0..self.height {
+            let start = y * (row_size + 1);
+            let row = remove_filter(
+                raw[start],
+                &raw[start + 1..start + 1 + row_size],
+                &previous,
+                pixel_size,
+            )?;
+
+            for x in 0..self.width {
+                let sample = |channel: usize| {
+                    read_sample(
+                        &row,
+                        (x * self.channels() + channel) * self.bit_depth,
+                        self.bit_depth,
+                    )
+                };
+
+                let color = match self.color_type {
+                    3 => {
+                        let index = sample(0) as usize * 3;
+                        let entry = palette
+                            .get(index..index + 3)
+                            .ok_or_else(|| ImageError::Malformed("palette index".to_string()))?;
+                        Color::new(
+                            f64::from(entry[0]) / 255.0,
+                            f64::from(entry[1]) / 255.0,
+                            f64::from(entry[2]) / 255.0,
+                        )
+                    }
+                    0 | 4 => {
+                        let grey = f64::from(sample(0)) / max_value;
+                        Color::new(grey, grey, grey)
+                    }
+                    _ => Color::new(
+                        f64::from(sample(0)) / max_value,
+                        f64::from(sample(1)) / max_value,
+                        f64::from(sample(2)) / max_value,
+                    ),
+                };
+
+                let alpha = match self.color_type {
+                    4 => f64::from(sample(1)) / max_value,
+                    6 => f64::from(sample(3)) / max_value,
+                    _ => 1.0,
+                };
+
+                canvas.set_pixel_with_alpha(x, y, encoding.decode_color(color) * alpha, alpha);
+            }
+
+            previous = row;
+        }
+
+        Ok(canvas)
+    }
+}
+
+fn into_u16(hue: f64) -> u16 {
+    (hue * 65535.0).round() as u16
+}
+
+// PNG stores fractions as integers in units of 1/100000.
+fn into_png_fixed(value: f64) -> u32 {
+    (value * 100_000.0).round() as u32
+}
+
+// White point then red, green and blue primaries, each as x and y.
+fn chromaticities_chunk(color_space: ColorSpace) -> Vec<u8> {
+    let [red, green, blue] = color_space.primaries();
+    [color_space.white_point(), red, green, blue]
+        .iter()
+        .flat_map(|&(x, y)| [x, y])
+        .flat_map(|value| into_png_fixed(value).to_be_bytes())
+        .collect()
+}
+
+type Chromaticities = ([(f64, f64); 3], (f64, f64));
+
+fn parse_chromaticities(body: &[u8]) -> Result<Chromaticities, ImageError> {
+    if body.len() != 32 {
+        return Err(ImageError::Malformed("cHRM length".to_string()));
+    }
+
+    let point = |index: usize| -> Result<(f64, f64), ImageError> {
+        let value = |offset: usize| Ok(read_u32(body, offset)? as f64 / 100_000.0);
+        Ok((value(8 * index)?, value(8 * index + 4)?))
+    };
+    Ok(([point(1)?, point(2)?, point(3)?], point(0)?))
+}
+
+fn read_u32(bytes: &[u8], position: usize) -> Result<u32, ImageError> {
+    let slice = bytes
+        .get(position..position + 4)
+        .ok_or(ImageError::Truncated)?;
+
+    Ok(u32::from_be_bytes([slice[0], slice[1], slice[2], slice[3]]))
+}
+
+fn read_sample(row: &[u8], bit_offset: usize, bit_depth: usize) -> u32 {
+    match bit_depth {
+        16 => u32::from(u16::from_be_bytes([
+            row[bit_offset / 8],
+            row[bit_offset / 8 + 1],
+        ])),
+        8 => u32::from(row[bit_offset / 8]),
+        _ => {
+            // Packed samples fill each byte from its most significant bit.
+            let shift = 8 - bit_depth - bit_offset % 8;
+            u32::from((row[bit_offset / 8] >> shift) & ((1 << bit_depth) - 1) as u8)
+        }
+    }
+}
+
+fn write_chunk(content: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    content.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = content.len();
+    content.extend_from_slice(kind);
+    content.extend_from_slice(data);
+
+    let checksum = crc32(&content[start..]);
+    content.extend_from_slice(&checksum.to_be_bytes());
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let distance_left = (estimate - i16::from(left)).abs();
+    let distance_up = (estimate - i16::from(up)).abs();
+    let distance_up_left = (estimate - i16::from(up_left)).abs();
+
+    if distance_left <= distance_up && distance_left <= distance_up_left {
+        left
+    } else if distance_up <= distance_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+fn predict(filter: u8, left: u8, up: u8, up_left: u8) -> u8 {
+    match filter {
+        1 => left,
+        2 => up,
+        3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+        4 => paeth(left, up, up_left),
+        _ => 0,
+    }
+}
+
+fn apply_filter(filter: u8, row: &[u8], previous: &[u8], pixel_size: usize) -> Vec<u8> {
+    (0..row.len())
+        .map(|i| {
+            let left = if i >= pixel_size {
+                row[i - pixel_size]
+            } else {
+                0
+            };
+            let up_left = if i >= pixel_size {
+                previous[i - pixel_size]
+            } else {
+                0
+            };
+
+            row[i].wrapping_sub(predict(filter, left, previous[i], up_left))
+        })
+        .collect()
+}
+
+fn remove_filter(
+    filter: u8,
+    filtered: &[u8],
+    previous: &[u8],
+    pixel_size: usize,
+) -> Result<Vec<u8>, ImageError> {
+    if filter > 4 {
+        return Err(ImageError::Malformed(format!("filter type {}", filter)));
+    }
+
+    let mut row: Vec<u8> = Vec::with_capacity(filtered.len());
+
+    for i in 0..filtered.len() {
+        let left = if i >= pixel_size {
+            row[i - pixel_size]
+        } else {
+            0
+        };
+        let up_left = if i >= pixel_size {
+            previous[i - pixel_size]
+        } else {
+            0
+        };
+
+        row.push(filtered[i].wrapping_add(predict(filter, left, previous[i], up_left)));
+    }
+
+    Ok(row)
+}
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+
+    while n < 256 {
+        let mut value = n as u32;
+        let mut k = 0;
+
+        while k < 8 {
+            value = if value & 1 == 1 {
+                0xedb8_8320 ^ (value >> 1)
+            } else {
+                value >> 1
+            };
+            k += 1;
+        }
+
+        table[n] = value;
+        n += 1;
+    }
+
+    table
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_canvas() -> Canvas {
+        let mut canvas = Canvas::new(4, 3);
+
+        for y in 0..3 {
+            for x in 0..4 {
+                let color = Color::new(x as f64 / 3.0, y as f64 / 2.0, ((x + y) % 2) as f64);
+                canvas.set_pixel(x, y, color);
+            }
+        }
+
+        canvas
+    }
+
+    #[test]
+    fn crc32_known_value() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0xae42_6082, crc32(b"IEND"));
+    }
+
+    #[test]
+    fn new_png_header() {
+        let canvas = Canvas::new(5, 3);
+        let header = PNG::create_header(&canvas, ColorType::Rgba, BitDepth::Sixteen);
+
+        assert_eq!(vec![0, 0, 0, 5, 0, 0, 0, 3, 16, 6, 0, 0, 0], header);
+    }
+
+    #[test]
+    fn new_png_structure() {
+        let png = PNG::new(&Canvas::new(5, 3)).get();
+
+        assert_eq!(SIGNATURE, png[..8]);
+        assert_eq!(b"IHDR", &png[12..16]);
+        assert_eq!(b"sRGB", &png[37..41]);
+        assert_eq!(
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82],
+            png[png.len() - 12..]
+        );
+    }
+
+    #[test]
+    fn new_png_body() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel(0, 0, Color::new(1.0, 0.5, 0.0));
+        canvas.set_pixel(1, 0, Color::new(1.0, 0.5, 0.0));
+
+        let body = PNG::create_body(&canvas, ColorType::Rgb, BitDepth::Eight, Encoding::Linear);
+
+        // The repeated pixel is cheapest with the Sub filter.
+        assert_eq!(vec![1, 255, 127, 0, 0, 0, 0], body);
+
+        let body = PNG::create_body(
+            &canvas,
+            ColorType::Rgba,
+            BitDepth::Sixteen,
+            Encoding::Linear,
+        );
+        assert_eq!(1 + 2 * 8, body.len());
+    }
+
+    #[test]
+    fn new_png_body_alpha() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel_with_alpha(0, 0, Color::new(0.5, 0.25, 0.0), 0.5);
+
+        let body = PNG::create_body(&canvas, ColorType::Rgba, BitDepth::Eight, Encoding::Linear);
+
+        // Straight colour for the covered pixel, nothing for the empty one.
+        assert_eq!(vec![0, 255, 127, 0, 127, 0, 0, 0, 0], body);
+    }
+
+    #[test]
+    fn read_png_alpha_round_trip() {
+        let mut canvas = Canvas::new(3, 1);
+        canvas.set_pixel(0, 0, Color::new(0.2, 0.4, 0.6));
+        canvas.set_pixel_with_alpha(1, 0, Color::new(0.1, 0.2, 0.3), 0.5);
+
+        let png = PNG::with_format(
+            &canvas,
+            ColorType::Rgba,
+            BitDepth::Sixteen,
+            Encoding::Linear,
+        );
+        let read = PNG::read_with_encoding(&png.get(), Encoding::Linear).unwrap();
+
+        for x in 0..3 {
+            let (expected, actual) = (canvas.get_pixel(x, 0), read.get_pixel(x, 0));
+            assert!((expected.r() - actual.r()).abs() < 1e-4);
+            assert!((expected.g() - actual.g()).abs() < 1e-4);
+            assert!((expected.b() - actual.b()).abs() < 1e-4);
+            assert!((canvas.get_alpha(x, 0) - read.get_alpha(x, 0)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn read_png_display_p3_round_trip() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel(0, 0, Color::new(0.2, 0.4, 0.6));
+        canvas.set_pixel(1, 0, Color::new(0.9, 0.1, 0.05));
+
+        let png = PNG::with_color_space(
+            &canvas,
+            ColorType::Rgb,
+            BitDepth::Sixteen,
+            Encoding::Linear,
+            ColorSpace::DisplayP3,
+        )
+        .get();
+        let read = PNG::read_with_encoding(&png, Encoding::Linear).unwrap();
+
+        assert_eq!(b"gAMA", &png[37..41]);
+        assert_eq!(b"cHRM", &png[53..57]);
+        assert_eq!(ColorSpace::DisplayP3, read.color_space());
+        for x in 0..2 {
+            let expected =
+                ColorSpace::Rec709.convert(canvas.get_pixel(x, 0), ColorSpace::DisplayP3);
+            let actual = read.get_pixel(x, 0);
+            assert!((expected.r() - actual.r()).abs() < 1e-4);
+            assert!((expected.g() - actual.g()).abs() < 1e-4);
+            assert!((expected.b() - actual.b()).abs() < 1e-4);
+        }
+        assert_eq!(
+            ColorSpace::Rec709,
+            PNG::read(&PNG::new(&canvas).get()).unwrap().color_space()
+        );
+    }
+
+    #[test]
+    fn filter_round_trip() {
+        let previous = [10, 20, 30, 40, 50, 60];
+        let row = [200, 3, 17, 255, 0, 128];
+
+        for filter in 0..5 {
+            let filtered = apply_filter(filter, &row, &previous, 3);
+            assert_eq!(
+                row.to_vec(),
+                remove_filter(filter, &filtered, &previous, 3).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn read_png_round_trip() {
+        let canvas = sample_canvas();
+
+        for color_type in [ColorType::Rgb, ColorType::Rgba] {
+            for bit_depth in [BitDepth::Eight, BitDepth::Sixteen] {
+                let png = PNG::with_format(&canvas, color_type, bit_depth, Encoding::Srgb);
+                let read = PNG::read(&png.get()).unwrap();
+
+                assert_eq!(canvas.width(), read.width());
+                assert_eq!(canvas.height(), read.height());
+
+                let tolerance = match bit_depth {
+                    BitDepth::Eight => 1e-2,
+                    BitDepth::Sixteen => 1e-4,
+                };
+                for (expected, actual) in canvas.pixels().iter().zip(read.pixels().iter()) {
+                    assert!((expected.r() - actual.r()).abs() < tolerance);
+                    assert!((expected.g() - actual.g()).abs() < tolerance);
+                    assert!((expected.b() - actual.b()).abs() < tolerance);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn read_png_palette() {
+        // A 2x1 palette image with a 1-bit depth: a red and a blue pixel.
+        let mut png = SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &[0, 0, 0, 2, 0, 0, 0, 1, 1, 3, 0, 0, 0]);
+        write_chunk(&mut png, b"PLTE", &[255, 0, 0, 0, 0, 255]);
+        write_chunk(&mut png, b"IDAT", &zlib::compress(&[0, 0b0100_0000]));
+        write_chunk(&mut png, b"IEND", &[]);
+
+        let canvas = PNG::read(&png).unwrap();
+
+        assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.get_pixel(0, 0));
+        assert_eq!(Color::new(0.0, 0.0, 1.0), canvas.get_pixel(1, 0));
+    }
+
+    #[test]
+    fn read_png_greyscale() {
+        let mut png = SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &[0, 0, 0, 3, 0, 0, 0, 1, 4, 0, 0, 0, 0]);
+        write_chunk(&mut png, b"IDAT", &zlib::compress(&[0, 0x0f, 0x50]));
+        write_chunk(&mut png, b"IEND", &[]);
+
+        let canvas = PNG::read_with_encoding(&png, Encoding::Linear).unwrap();
+
+        assert_eq!(Color::new(0.0, 0.0, 0.0), canvas.get_pixel(0, 0));
+        assert_eq!(Color::new(1.0, 1.0, 1.0), canvas.get_pixel(1, 0));
+        assert_eq!(
+            Color::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0),
+            canvas.get_pixel(2, 0)
+        );
+    }
+
+    #[test]
+    fn read_png_errors() {
+        assert!(matches!(
+            PNG::read(b"not a png"),
+            Err(ImageError::Malformed(_))
+        ));
+
+        let mut png = PNG::new(&sample_canvas()).get();
+        assert_eq!(
+            Err(ImageError::Truncated),
+            PNG::read(&png[..40]).map(|_| ())
+        );
+
+        png[20] ^= 0xff;
+        assert!(matches!(PNG::read(&png), Err(ImageError::Malformed(_))));
+
+        let mut interlaced = SIGNATURE.to_vec();
+        write_chunk(
+            &mut interlaced,
+            b"IHDR",
+            &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 1],
+        );
+        assert_eq!(
+            Err(ImageError::Unsupported("interlaced PNG".to_string())),
+            PNG::read(&interlaced).map(|_| ())
+        );
+
+        // A size too large to count fails instead of overflowing.
+        let mut huge = SIGNATURE.to_vec();
+        write_chunk(
+            &mut huge,
+            b"IHDR",
+            &[
+                0x7f, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff, 16, 6, 0, 0, 0,
+            ],
+        );
+        write_chunk(&mut huge, b"IDAT", &zlib::compress(&[0, 0, 0]));
+        write_chunk(&mut huge, b"IEND", &[]);
+        assert_eq!(Err(ImageError::Truncated), PNG::read(&huge).map(|_| ()));
+    }
+}
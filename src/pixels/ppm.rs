@@ -1,4 +1,8 @@
 use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::color_space::ColorSpace;
+use crate::pixels::encoding::Encoding;
+use crate::pixels::error::ImageError;
 
 pub struct PPM {
     content: Vec<u8>,
@@ -6,8 +10,13 @@ pub struct PPM {
 
 impl PPM {
     pub fn new(canvas: &Canvas) -> Self {
+        PPM::with_encoding(canvas, Encoding::default())
+    }
+
+    pub fn with_encoding(canvas: &Canvas, encoding: Encoding) -> Self {
+        let canvas = &canvas.to_color_space(ColorSpace::Rec709);
         let mut header = PPM::create_header(canvas);
-        let mut body = PPM::create_body(canvas);
+        let mut body = PPM::create_body(canvas, encoding);
 
         let mut content: Vec<u8> = Vec::with_capacity(header.len() + body.len());
         content.append(&mut header);
@@ -20,11 +29,11 @@ impl PPM {
         format!("P6 {} {} 255\n", canvas.width(), canvas.height()).into_bytes()
     }
 
-    fn create_body(canvas: &Canvas) -> Vec<u8> {
+    fn create_body(canvas: &Canvas, encoding: Encoding) -> Vec<u8> {
         let mut body: Vec<u8> = vec![0; 3 * canvas.width() * canvas.height()];
 
-        for (i, color) in canvas.content().iter().enumerate() {
-            let (r, g, b) = color.to_tuple();
+        for (i, color) in canvas.pixels().iter().enumerate() {
+            let (r, g, b) = encoding.encode_color(*color).to_tuple();
             let offset = 3 * i;
 
             // Always set a block of all three hues at once.
@@ -39,12 +48,125 @@ impl PPM {
     pub fn get(&self) -> Vec<u8> {
         self.content.clone()
     }
+
+    pub fn read(bytes: &[u8]) -> Result<Canvas, ImageError> {
+        PPM::read_with_encoding(bytes, Encoding::default())
+    }
+
+    pub fn read_with_encoding(bytes: &[u8], encoding: Encoding) -> Result<Canvas, ImageError> {
+        let mut reader = HeaderReader::new(bytes);
+
+        let binary = match reader.token()? {
+            "P6" => true,
+            "P3" => false,
+            magic => return Err(ImageError::Unsupported(format!("PPM magic {}", magic))),
+        };
+        let width = reader.number()?;
+        let height = reader.number()?;
+        let max_value = reader.number()?;
+
+        if max_value == 0 || max_value > 65535 {
+            return Err(ImageError::Malformed(format!(
+                "maximum value {}",
+                max_value
+            )));
+        }
+
+        // A size too large to count can't be in the file either.
+        let count = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or(ImageError::Truncated)?;
+
+        let samples = if binary {
+            // Exactly one whitespace byte separates the header from the raster.
+            let body = bytes
+                .get(reader.position + 1..)
+                .ok_or(ImageError::Truncated)?;
+            let bytes_per_sample = if max_value > 255 { 2 } else { 1 };
+
+            let length = count.checked_mul(bytes_per_sample);
+            if length.is_none_or(|length| body.len() < length) {
+                return Err(ImageError::Truncated);
+            }
+
+            body.chunks(bytes_per_sample)
+                .take(count)
+                .map(|chunk| {
+                    chunk
+                        .iter()
+                        .fold(0, |value, &byte| (value << 8) | byte as usize)
+                })
+                .collect::<Vec<usize>>()
+        } else {
+            (0..count)
+                .map(|_| reader.number())
+                .collect::<Result<Vec<usize>, ImageError>>()?
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        let scale = max_value as f64;
+
+        for (i, sample) in samples.chunks(3).enumerate() {
+            let color = Color::new(
+                sample[0] as f64 / scale,
+                sample[1] as f64 / scale,
+                sample[2] as f64 / scale,
+            );
+
+            canvas.set_pixel(i % width, i / width, encoding.decode_color(color));
+        }
+
+        Ok(canvas)
+    }
+}
+
+struct HeaderReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> HeaderReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        HeaderReader { bytes, position: 0 }
+    }
+
+    fn token(&mut self) -> Result<&'a str, ImageError> {
+        loop {
+            match self.bytes.get(self.position) {
+                Some(b'#') => {
+                    while !matches!(self.bytes.get(self.position), Some(b'\n') | None) {
+                        self.position += 1;
+                    }
+                }
+                Some(byte) if byte.is_ascii_whitespace() => self.position += 1,
+                Some(_) => break,
+                None => return Err(ImageError::Truncated),
+            }
+        }
+
+        let start = self.position;
+        while matches!(self.bytes.get(self.position), Some(byte) if !byte.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .map_err(|_| ImageError::Malformed("non-ASCII header".to_string()))
+    }
+
+    fn number(&mut self) -> Result<usize, ImageError> {
+        let token = self.token()?;
+
+        token
+            .parse()
+            .map_err(|_| ImageError::Malformed(format!("expected a number, found {}", token)))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pixels::color::Color;
+    use crate::pixels::rgb::Rgb;
 
     #[test]
     fn new_ppm_header() {
@@ -66,7 +188,7 @@ mod tests {
         canvas.set_pixel(2, 1, color_b);
         canvas.set_pixel(4, 2, color_c);
 
-        let body = PPM::create_body(&canvas);
+        let body = PPM::create_body(&canvas, Encoding::Linear);
 
         for (count, _value) in body.iter().enumerate() {
             let desired_result = match count {
@@ -94,8 +216,95 @@ mod tests {
 
         let mut desired_result: Vec<u8> = Vec::new();
         desired_result.append(&mut PPM::create_header(&canvas));
-        desired_result.append(&mut PPM::create_body(&canvas));
+        desired_result.append(&mut PPM::create_body(&canvas, Encoding::Srgb));
 
         assert_eq!(desired_result, ppm.get());
     }
+
+    #[test]
+    fn new_ppm_body_srgb() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel(0, 0, Color::new(0.5, 0.0, 1.0));
+        canvas.set_pixel(1, 0, Color::new(0.2, 0.002, 0.0));
+
+        let body = PPM::create_body(&canvas, Encoding::Srgb);
+
+        assert_eq!(vec![187, 0, 255, 123, 6, 0], body);
+    }
+
+    #[test]
+    fn read_ppm_round_trip() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.set_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.set_pixel(1, 1, Color::new(0.0, 1.0, 0.0));
+        canvas.set_pixel(2, 1, Color::new(0.0, 0.0, 1.0));
+
+        let ppm = PPM::with_encoding(&canvas, Encoding::Linear);
+        let read = PPM::read_with_encoding(&ppm.get(), Encoding::Linear).unwrap();
+
+        assert_eq!(canvas.width(), read.width());
+        assert_eq!(canvas.height(), read.height());
+        assert_eq!(canvas.pixels(), read.pixels());
+    }
+
+    #[test]
+    fn read_ppm_srgb_decoding() {
+        let bytes = b"P6 1 1 255\n\xbc\x00\xff";
+        let canvas = PPM::read(bytes).unwrap();
+        let color = canvas.get_pixel(0, 0);
+
+        assert!((color.r() - 0.503).abs() < 1e-3);
+        assert_eq!(0.0, color.g());
+        assert!((color.b() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn read_ppm_ascii_with_comments() {
+        let bytes = b"P3\n# a comment\n2 1\n# another\n15\n15 0 0  0 0 15\n";
+        let canvas = PPM::read_with_encoding(bytes, Encoding::Linear).unwrap();
+
+        assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.get_pixel(0, 0));
+        assert_eq!(Color::new(0.0, 0.0, 1.0), canvas.get_pixel(1, 0));
+    }
+
+    #[test]
+    fn read_ppm_sixteen_bit() {
+        let bytes = b"P6 1 1 65535\n\xff\xff\x00\x00\x80\x00";
+        let canvas = PPM::read_with_encoding(bytes, Encoding::Linear).unwrap();
+        let color = canvas.get_pixel(0, 0);
+
+        assert_eq!(1.0, color.r());
+        assert_eq!(0.0, color.g());
+        assert_eq!(32768.0 / 65535.0, color.b());
+    }
+
+    #[test]
+    fn read_ppm_errors() {
+        assert_eq!(
+            Err(ImageError::Unsupported("PPM magic P5".to_string())),
+            PPM::read(b"P5 1 1 255\n\x00").map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::Truncated),
+            PPM::read(b"P6 2 2 255\n\x00\x00").map(|_| ())
+        );
+        assert_eq!(Err(ImageError::Truncated), PPM::read(b"P6 2").map(|_| ()));
+        assert_eq!(
+            Err(ImageError::Truncated),
+            PPM::read(b"P6 2 2 255").map(|_| ())
+        );
+        // A size too large to count fails instead of overflowing.
+        assert_eq!(
+            Err(ImageError::Truncated),
+            PPM::read(b"P6 4294967296 4294967296 255\n\x00").map(|_| ())
+        );
+        assert_eq!(
+            Err(ImageError::Truncated),
+            PPM::read(b"P3\n6148914691236517206 1\n255\n").map(|_| ())
+        );
+        assert!(matches!(
+            PPM::read(b"P6 a 2 255\n"),
+            Err(ImageError::Malformed(_))
+        ));
+    }
 }
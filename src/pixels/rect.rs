@@ -0,0 +1,103 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        // Edges past the end of the address space stop there.
+        let right = (self.x.saturating_add(self.width)).min(other.x.saturating_add(other.width));
+        let bottom = (self.y.saturating_add(self.height)).min(other.y.saturating_add(other.height));
+
+        Rect::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_rect() {
+        let rect = Rect::new(1, 2, 3, 4);
+
+        assert_eq!(1, rect.x());
+        assert_eq!(2, rect.y());
+        assert_eq!(3, rect.width());
+        assert_eq!(4, rect.height());
+        assert!(!rect.is_empty());
+        assert!(Rect::new(1, 2, 0, 4).is_empty());
+    }
+
+    #[test]
+    fn contains_rect() {
+        let rect = Rect::new(1, 2, 3, 4);
+
+        assert!(rect.contains(1, 2));
+        assert!(rect.contains(3, 5));
+        assert!(!rect.contains(0, 2));
+        assert!(!rect.contains(4, 2));
+        assert!(!rect.contains(1, 6));
+    }
+
+    #[test]
+    fn intersect_rect() {
+        let rect_a = Rect::new(0, 0, 4, 4);
+        let rect_b = Rect::new(2, 1, 5, 2);
+
+        assert_eq!(Rect::new(2, 1, 2, 2), rect_a.intersect(&rect_b));
+        assert!(rect_a.intersect(&Rect::new(6, 6, 2, 2)).is_empty());
+    }
+
+    #[test]
+    fn intersect_rect_at_address_limit() {
+        let rect = Rect::new(2, 3, usize::MAX, usize::MAX);
+
+        assert_eq!(
+            Rect::new(2, 3, 2, 1),
+            rect.intersect(&Rect::new(0, 0, 4, 4))
+        );
+        assert_eq!(
+            Rect::new(usize::MAX - 1, 0, 1, 1),
+            Rect::new(usize::MAX - 1, 0, 5, 1).intersect(&Rect::new(usize::MAX - 1, 0, 5, 5))
+        );
+    }
+}
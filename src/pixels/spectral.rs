@@ -0,0 +1,659 @@
+use std::sync::OnceLock;
+
+use crate::pixels::blackbody::{color_matching, VISIBLE};
+use crate::pixels::color::Color;
+use crate::pixels::color_space::{bradford, Matrix3, D65};
+use crate::pixels::rgb::Rgb;
+
+// Wavelengths carried along each path, the first being the hero wavelength.
+pub const WAVELENGTHS: usize = 4;
+
+struct Tables {
+    // Colour matching functions in one nanometre steps over the visible range.
+    color_matching: Vec<[f64; 3]>,
+    // Their integrals, which a constant spectrum of one maps to.
+    integrals: [f64; 3],
+    // From XYZ relative to the equal energy white to XYZ relative to D65.
+    adaptation: Matrix3,
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+
+    TABLES.get_or_init(|| {
+        let color_matching: Vec<[f64; 3]> = VISIBLE
+            .map(|wavelength| {
+                let (x, y, z) = color_matching(wavelength as f64);
+                [x, y, z]
+            })
+            .collect();
+        let integrals = color_matching.iter().fold([0.0; 3], |sum, value| {
+            [sum[0] + value[0], sum[1] + value[1], sum[2] + value[2]]
+        });
+
+        Tables {
+            color_matching,
+            integrals,
+            adaptation: bradford((1.0 / 3.0, 1.0 / 3.0), D65),
+        }
+    })
+}
+
+// Turns XYZ relative to the equal energy white into a colour, so a constant
+// spectrum comes out as a neutral grey.
+fn xyz_to_color(xyz: [f64; 3]) -> Color {
+    let tables = tables();
+    let xyz = [0, 1, 2].map(|i| xyz[i] / tables.integrals[i] * tables.integrals[1]);
+    let [x, y, z] = tables
+        .adaptation
+        .map(|row| row[0] * xyz[0] + row[1] * xyz[1] + row[2] * xyz[2]);
+
+    Color::from_xyz(x, y, z)
+}
+
+// Wavelengths in nanometres, spread evenly over the visible range from a
+// randomly placed hero wavelength.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampledWavelengths {
+    lambda: [f64; WAVELENGTHS],
+    pdf: [f64; WAVELENGTHS],
+}
+
+impl SampledWavelengths {
+    // `u` is a uniform random number in [0, 1).
+    pub fn sample_uniform(u: f64) -> Self {
+        let (min, max) = (*VISIBLE.start() as f64, *VISIBLE.end() as f64);
+        let range = max - min;
+        let lambda = std::array::from_fn(|i| {
+            let offset = (u + i as f64 / WAVELENGTHS as f64).fract();
+            min + offset * range
+        });
+
+        SampledWavelengths {
+            lambda,
+            pdf: [1.0 / range; WAVELENGTHS],
+        }
+    }
+
+    pub fn lambda(&self, i: usize) -> f64 {
+        self.lambda[i]
+    }
+
+    pub fn pdf(&self, i: usize) -> f64 {
+        self.pdf[i]
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    pub fn secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|pdf| *pdf == 0.0)
+    }
+
+    // Keeps only the hero wavelength, for when a path takes a direction that
+    // depends on its wavelength, such as refraction through dispersive glass.
+    pub fn terminate_secondary(&mut self) {
+        if self.secondary_terminated() {
+            return;
+        }
+
+        self.pdf[1..].iter_mut().for_each(|pdf| *pdf = 0.0);
+        self.pdf[0] /= WAVELENGTHS as f64;
+    }
+}
+
+// Spectral values at the wavelengths of a `SampledWavelengths`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampledSpectrum {
+    values: [f64; WAVELENGTHS],
+}
+
+impl SampledSpectrum {
+    pub fn new(values: [f64; WAVELENGTHS]) -> Self {
+        SampledSpectrum { values }
+    }
+
+    pub fn constant(value: f64) -> Self {
+        SampledSpectrum::new([value; WAVELENGTHS])
+    }
+
+    pub fn value(&self, i: usize) -> f64 {
+        self.values[i]
+    }
+
+    // Monte Carlo estimate of CIE XYZ, scaled so a constant spectrum of one has
+    // a luminance of one.
+    pub fn to_xyz(&self, wavelengths: &SampledWavelengths) -> [f64; 3] {
+        let mut xyz = [0.0; 3];
+
+        for i in 0..WAVELENGTHS {
+            if wavelengths.pdf(i) == 0.0 {
+                continue;
+            }
+            let (x, y, z) = color_matching(wavelengths.lambda(i));
+            let weight = self.values[i] / wavelengths.pdf(i) / WAVELENGTHS as f64;
+
+            xyz[0] += x * weight;
+            xyz[1] += y * weight;
+            xyz[2] += z * weight;
+        }
+
+        // The tables are summed in one nanometre steps, matching the estimate.
+        xyz.map(|value| value / tables().integrals[1])
+    }
+
+    pub fn to_color(&self, wavelengths: &SampledWavelengths) -> Color {
+        xyz_to_color(self.to_xyz(wavelengths))
+    }
+}
+
+impl std::ops::Add for SampledSpectrum {
+    type Output = SampledSpectrum;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        SampledSpectrum::new(std::array::from_fn(|i| self.values[i] + rhs.values[i]))
+    }
+}
+
+impl std::ops::Mul for SampledSpectrum {
+    type Output = SampledSpectrum;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        SampledSpectrum::new(std::array::from_fn(|i| self.values[i] * rhs.values[i]))
+    }
+}
+
+impl std::ops::Mul<f64> for SampledSpectrum {
+    type Output = SampledSpectrum;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        SampledSpectrum::new(self.values.map(|value| value * rhs))
+    }
+}
+
+// Samples of the coefficient table along each of its axes.
+const TABLE_RESOLUTION: usize = 16;
+
+// Sigmoid coefficients fitted ahead of time over a grid of colours, as
+// little endian f32 triples. Regenerate it after changing the fit with
+// `cargo test --release -- --ignored regenerate_rgb_spectrum_table`.
+static TABLE: &[u8] = include_bytes!("rgb_spectrum_table.bin");
+
+// A smooth spectrum reproducing an RGB colour, following Jakob and Hanika's
+// sigmoid of a quadratic polynomial. The coefficients are interpolated from
+// a precomputed table, then polished with a single Gauss-Newton step that
+// removes most of the interpolation error. Colours brighter than one are
+// looked up at a lower intensity and scaled back up, so they can describe
+// emission.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RgbSpectrum {
+    coefficients: [f64; 3],
+    scale: f64,
+}
+
+impl RgbSpectrum {
+    pub fn new(color: Color) -> Self {
+        let target = [color.r(), color.g(), color.b()].map(|value| value.max(0.0));
+        let max = target[0].max(target[1]).max(target[2]);
+        if max == 0.0 {
+            return RgbSpectrum {
+                coefficients: [0.0; 3],
+                scale: 0.0,
+            };
+        }
+        let scale = if max > 1.0 { 2.0 * max } else { 1.0 };
+        let target = target.map(|value| value / scale);
+
+        RgbSpectrum {
+            coefficients: fit(target, lookup(target), 1),
+            scale,
+        }
+    }
+
+    pub fn evaluate(&self, wavelength: f64) -> f64 {
+        self.scale * sigmoid_polynomial(&self.coefficients, wavelength)
+    }
+
+    pub fn sample(&self, wavelengths: &SampledWavelengths) -> SampledSpectrum {
+        SampledSpectrum::new(std::array::from_fn(|i| {
+            self.evaluate(wavelengths.lambda(i))
+        }))
+    }
+
+    // The colour of the spectrum, integrated over the whole visible range.
+    pub fn to_color(&self) -> Color {
+        spectrum_color(&self.coefficients) * self.scale
+    }
+}
+
+// The table is indexed by the largest channel, its value, then the other two
+// channels relative to it in the order they follow it in RGB. Values are
+// spaced more densely towards black and white, where the coefficients change
+// fastest.
+fn table_value(index: usize) -> f64 {
+    let smoothstep = |x: f64| x * x * (3.0 - 2.0 * x);
+    smoothstep(smoothstep(index as f64 / (TABLE_RESOLUTION - 1) as f64))
+}
+
+fn table_index(largest: usize, value: usize, y: usize, x: usize) -> usize {
+    ((largest * TABLE_RESOLUTION + value) * TABLE_RESOLUTION + y) * TABLE_RESOLUTION + x
+}
+
+fn table_entry(index: usize) -> [f64; 3] {
+    std::array::from_fn(|i| {
+        let start = 4 * (3 * index + i);
+        let bytes = [
+            TABLE[start],
+            TABLE[start + 1],
+            TABLE[start + 2],
+            TABLE[start + 3],
+        ];
+        f32::from_le_bytes(bytes) as f64
+    })
+}
+
+// Trilinear interpolation of the table for a colour with channels in [0, 1]
+// and at least one of them positive.
+fn lookup(color: [f64; 3]) -> [f64; 3] {
+    let largest = (0..3)
+        .max_by(|a, b| color[*a].total_cmp(&color[*b]))
+        .unwrap_or(0);
+    let z = color[largest];
+    let last = TABLE_RESOLUTION - 1;
+
+    // The cell along an axis and the position within it.
+    let cell = |position: f64| {
+        let index = (position.floor() as usize).min(last - 1);
+        (index, position - index as f64)
+    };
+    let (x, fx) = cell(color[(largest + 1) % 3] / z * last as f64);
+    let (y, fy) = cell(color[(largest + 2) % 3] / z * last as f64);
+    let value = (1..last)
+        .find(|index| table_value(*index) > z)
+        .unwrap_or(last)
+        - 1;
+    let fz = (z - table_value(value)) / (table_value(value + 1) - table_value(value));
+
+    let mut coefficients = [0.0; 3];
+    for (dz, wz) in [(0, 1.0 - fz), (1, fz)] {
+        for (dy, wy) in [(0, 1.0 - fy), (1, fy)] {
+            for (dx, wx) in [(0, 1.0 - fx), (1, fx)] {
+                let entry = table_entry(table_index(largest, value + dz, y + dy, x + dx));
+                (0..3).for_each(|i| coefficients[i] += wz * wy * wx * entry[i]);
+            }
+        }
+    }
+
+    coefficients
+}
+
+fn sigmoid_polynomial(coefficients: &[f64; 3], wavelength: f64) -> f64 {
+    // Normalising the wavelength keeps the coefficients well conditioned.
+    let (min, max) = (*VISIBLE.start() as f64, *VISIBLE.end() as f64);
+    let t = (wavelength - min) / (max - min);
+    let x = (coefficients[0] * t + coefficients[1]) * t + coefficients[2];
+
+    if x.is_infinite() {
+        return if x > 0.0 { 1.0 } else { 0.0 };
+    }
+    0.5 + x / (2.0 * (1.0 + x * x).sqrt())
+}
+
+fn spectrum_color(coefficients: &[f64; 3]) -> Color {
+    let mut xyz = [0.0; 3];
+    for (wavelength, matching) in VISIBLE.zip(&tables().color_matching) {
+        let value = sigmoid_polynomial(coefficients, wavelength as f64);
+        (0..3).for_each(|i| xyz[i] += matching[i] * value);
+    }
+
+    xyz_to_color(xyz.map(|value| value / tables().integrals[1]))
+}
+
+fn residual(coefficients: &[f64; 3], target: &[f64; 3]) -> [f64; 3] {
+    let color = spectrum_color(coefficients);
+    [
+        color.r() - target[0],
+        color.g() - target[1],
+        color.b() - target[2],
+    ]
+}
+
+fn norm(vector: &[f64; 3]) -> f64 {
+    vector.iter().map(|value| value * value).sum::<f64>().sqrt()
+}
+
+// Gauss-Newton with a backtracking line search from `guess`, taking at most
+// `iterations` steps.
+fn fit(target: [f64; 3], guess: [f64; 3], iterations: usize) -> [f64; 3] {
+    let mut coefficients = guess;
+    let mut current = residual(&coefficients, &target);
+
+    for _ in 0..iterations {
+        if norm(&current) < 1e-7 {
+            break;
+        }
+
+        let step = 1e-4;
+        let mut jacobian = [[0.0; 3]; 3];
+        for j in 0..3 {
+            let mut nudged = coefficients;
+            nudged[j] += step;
+            let nudged = residual(&nudged, &target);
+            (0..3).for_each(|i| jacobian[i][j] = (nudged[i] - current[i]) / step);
+        }
+
+        let Some(delta) = solve(&jacobian, &current.map(|value| -value)) else {
+            break;
+        };
+
+        let mut length = 1.0;
+        let mut improved = false;
+        while length > 1e-3 {
+            let candidate = std::array::from_fn(|i| coefficients[i] + delta[i] * length);
+            let candidate_residual = residual(&candidate, &target);
+            if norm(&candidate_residual) < norm(&current) {
+                coefficients = candidate;
+                current = candidate_residual;
+                improved = true;
+                break;
+            }
+            length /= 2.0;
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    coefficients
+}
+
+// Solves a 3x3 linear system with Cramer's rule.
+fn solve(matrix: &Matrix3, rhs: &[f64; 3]) -> Option<[f64; 3]> {
+    let determinant = |m: &Matrix3| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+
+    let base = determinant(matrix);
+    if base.abs() < 1e-300 {
+        return None;
+    }
+
+    Some(std::array::from_fn(|column| {
+        let mut replaced = *matrix;
+        (0..3).for_each(|row| replaced[row][column] = rhs[row]);
+        determinant(&replaced) / base
+    }))
+}
+
+// Index of refraction as a function of wavelength.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dispersion {
+    Constant(f64),
+    // n = a + b / λ², with λ in micrometres.
+    Cauchy { a: f64, b: f64 },
+    // n² = 1 + Σ bᵢ λ² / (λ² - cᵢ), with λ in micrometres.
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    pub const BK7: Dispersion = Dispersion::Sellmeier {
+        b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+        c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+    };
+    pub const DIAMOND: Dispersion = Dispersion::Sellmeier {
+        b: [0.3306, 4.3356, 0.0],
+        c: [0.030_625, 0.011_236, 0.0],
+    };
+
+    // `wavelength` is in nanometres.
+    pub fn ior(&self, wavelength: f64) -> f64 {
+        let micrometres = wavelength / 1000.0;
+        let squared = micrometres * micrometres;
+
+        match self {
+            Dispersion::Constant(ior) => *ior,
+            Dispersion::Cauchy { a, b } => a + b / squared,
+            Dispersion::Sellmeier { b, c } => {
+                let sum: f64 = (0..3).map(|i| b[i] * squared / (squared - c[i])).sum();
+                (1.0 + sum).sqrt()
+            }
+        }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, Dispersion::Constant(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+
+    // Averages many stratified hero wavelength estimates of a spectrum.
+    fn estimate(spectrum: impl Fn(&SampledWavelengths) -> SampledSpectrum) -> Color {
+        let samples = 1000;
+        (0..samples)
+            .map(|i| {
+                let wavelengths =
+                    SampledWavelengths::sample_uniform((i as f64 + 0.5) / samples as f64);
+                spectrum(&wavelengths).to_color(&wavelengths)
+            })
+            .fold(Color::default(), |sum, color| sum + color)
+            * (1.0 / samples as f64)
+    }
+
+    #[test]
+    fn sample_wavelengths() {
+        let wavelengths = SampledWavelengths::sample_uniform(0.9);
+
+        assert_approx_eq!(783.0, wavelengths.hero());
+        assert_approx_eq!(360.0 + 0.15 * 470.0, wavelengths.lambda(1));
+        assert_approx_eq!(1.0 / 470.0, wavelengths.pdf(3));
+    }
+
+    #[test]
+    fn terminate_secondary_wavelengths() {
+        let mut wavelengths = SampledWavelengths::sample_uniform(0.3);
+        wavelengths.terminate_secondary();
+        wavelengths.terminate_secondary();
+
+        assert!(wavelengths.secondary_terminated());
+        assert_approx_eq!(1.0 / 470.0 / 4.0, wavelengths.pdf(0));
+        assert_eq!(0.0, wavelengths.pdf(2));
+    }
+
+    #[test]
+    fn constant_spectrum_is_white() {
+        let color = estimate(|_| SampledSpectrum::constant(1.0));
+
+        assert_approx_eq!(Color::new(1.0, 1.0, 1.0), color, Margin::Absolute(1e-3));
+    }
+
+    #[test]
+    fn hero_only_estimate() {
+        let samples = 1000;
+        let color = (0..samples)
+            .map(|i| {
+                let mut wavelengths =
+                    SampledWavelengths::sample_uniform((i as f64 + 0.5) / samples as f64);
+                wavelengths.terminate_secondary();
+                SampledSpectrum::constant(0.5).to_color(&wavelengths)
+            })
+            .fold(Color::default(), |sum, color| sum + color)
+            * (1.0 / samples as f64);
+
+        assert_approx_eq!(Color::new(0.5, 0.5, 0.5), color, Margin::Absolute(1e-2));
+    }
+
+    #[test]
+    fn sampled_spectrum_operators() {
+        let a = SampledSpectrum::new([1.0, 2.0, 3.0, 4.0]);
+        let b = SampledSpectrum::constant(2.0);
+
+        assert_eq!(SampledSpectrum::new([3.0, 4.0, 5.0, 6.0]), a + b);
+        assert_eq!(SampledSpectrum::new([2.0, 4.0, 6.0, 8.0]), a * b);
+        assert_eq!(SampledSpectrum::new([0.5, 1.0, 1.5, 2.0]), a * 0.5);
+    }
+
+    // The grid colour a table entry was fitted to.
+    fn table_color(largest: usize, value: usize, y: usize, x: usize) -> [f64; 3] {
+        let z = table_value(value);
+        let step = |index: usize| index as f64 / (TABLE_RESOLUTION - 1) as f64;
+        let mut color = [0.0; 3];
+        color[largest] = z;
+        color[(largest + 1) % 3] = step(x) * z;
+        color[(largest + 2) % 3] = step(y) * z;
+        color
+    }
+
+    // Fits every table entry starting from the fit of a neighbour, so the
+    // solutions vary smoothly. A slice of middling values is fitted first,
+    // working along the grid from grey, then the brighter and darker slices
+    // each start from the one before.
+    fn generate_table() -> Vec<u8> {
+        let size = 3 * TABLE_RESOLUTION.pow(3);
+        let mut coefficients = vec![[0.0; 3]; size];
+        let start = TABLE_RESOLUTION / 2;
+
+        for largest in 0..3 {
+            let index = |value: usize, y: usize, x: usize| table_index(largest, value, y, x);
+            let values = (start..TABLE_RESOLUTION).chain((0..start).rev());
+
+            for value in values {
+                for y in (0..TABLE_RESOLUTION).rev() {
+                    for x in (0..TABLE_RESOLUTION).rev() {
+                        let guess = if value > start {
+                            coefficients[index(value - 1, y, x)]
+                        } else if value < start {
+                            coefficients[index(value + 1, y, x)]
+                        } else if x + 1 < TABLE_RESOLUTION {
+                            coefficients[index(value, y, x + 1)]
+                        } else if y + 1 < TABLE_RESOLUTION {
+                            coefficients[index(value, y + 1, x)]
+                        } else {
+                            [0.0; 3]
+                        };
+                        coefficients[index(value, y, x)] =
+                            fit(table_color(largest, value, y, x), guess, 100);
+                    }
+                }
+            }
+        }
+
+        coefficients
+            .iter()
+            .flatten()
+            .flat_map(|value| (*value as f32).to_le_bytes())
+            .collect()
+    }
+
+    // Overwrites the checked in table, so it only runs when asked for.
+    #[test]
+    #[ignore]
+    fn regenerate_rgb_spectrum_table() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/pixels/rgb_spectrum_table.bin"
+        );
+        std::fs::write(path, generate_table()).unwrap();
+    }
+
+    // The table reproduces the colours it was fitted to.
+    #[test]
+    fn rgb_spectrum_table() {
+        assert_eq!(4 * 3 * 3 * TABLE_RESOLUTION.pow(3), TABLE.len());
+        // The darkest saturated colours lie beyond what the sigmoid can
+        // reach, so only the brighter slices fit exactly.
+        for largest in 0..3 {
+            for value in 3..TABLE_RESOLUTION {
+                for (y, x) in [(0, 0), (5, 11), (15, 15), (15, 0), (8, 3)] {
+                    let entry = table_entry(table_index(largest, value, y, x));
+                    let [r, g, b] = table_color(largest, value, y, x);
+                    assert_approx_eq!(
+                        Color::new(r, g, b),
+                        spectrum_color(&entry),
+                        Margin::Absolute(1e-5)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rgb_spectrum_grey() {
+        let spectrum = RgbSpectrum::new(Color::new(0.5, 0.5, 0.5));
+
+        assert_approx_eq!(0.5, spectrum.evaluate(400.0), Margin::Absolute(1e-6));
+        assert_approx_eq!(0.5, spectrum.evaluate(700.0), Margin::Absolute(1e-6));
+        assert_eq!(0.0, RgbSpectrum::new(Color::default()).evaluate(550.0));
+    }
+
+    #[test]
+    fn rgb_spectrum_round_trip() {
+        for color in [
+            Color::new(0.6, 0.3, 0.2),
+            Color::new(0.1, 0.2, 0.7),
+            Color::new(0.2, 0.8, 0.1),
+        ] {
+            let spectrum = RgbSpectrum::new(color);
+
+            assert_approx_eq!(color, spectrum.to_color(), Margin::Absolute(1e-4));
+            for wavelength in [360.0, 500.0, 830.0] {
+                assert!((0.0..=1.0).contains(&spectrum.evaluate(wavelength)));
+            }
+        }
+    }
+
+    #[test]
+    fn rgb_spectrum_emission() {
+        let spectrum = RgbSpectrum::new(Color::new(4.0, 2.0, 1.0));
+
+        assert_approx_eq!(
+            Color::new(4.0, 2.0, 1.0),
+            spectrum.to_color(),
+            Margin::Absolute(1e-3)
+        );
+    }
+
+    #[test]
+    fn rgb_spectrum_sampled() {
+        let color = Color::new(0.6, 0.3, 0.2);
+        let spectrum = RgbSpectrum::new(color);
+
+        assert_approx_eq!(
+            color,
+            estimate(|wavelengths| spectrum.sample(wavelengths)),
+            Margin::Absolute(2e-3)
+        );
+    }
+
+    #[test]
+    fn cauchy_dispersion() {
+        let glass = Dispersion::Cauchy {
+            a: 1.5046,
+            b: 0.004_20,
+        };
+
+        assert_approx_eq!(1.5046 + 0.0042 / 0.25, glass.ior(500.0));
+        assert!(glass.ior(400.0) > glass.ior(700.0));
+        assert!(glass.is_dispersive());
+        assert!(!Dispersion::Constant(1.5).is_dispersive());
+        assert_eq!(1.5, Dispersion::Constant(1.5).ior(400.0));
+    }
+
+    #[test]
+    fn sellmeier_dispersion() {
+        assert_approx_eq!(1.5168, Dispersion::BK7.ior(587.6), Margin::Absolute(1e-4));
+        assert_approx_eq!(
+            2.417,
+            Dispersion::DIAMOND.ior(589.3),
+            Margin::Absolute(2e-3)
+        );
+        assert!(Dispersion::DIAMOND.ior(430.0) - Dispersion::DIAMOND.ior(690.0) > 0.03);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::color_space::ColorSpace;
+use crate::pixels::rgb::Rgb;
+
+// White points at or below zero count as the smallest positive one, where
+// the curves are still finite.
+const MIN_WHITE_POINT: f64 = 1e-6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMapper {
+    Clamp,
+    Reinhard,
+    ExtendedReinhard { white_point: f64 },
+    Aces,
+    Hable { white_point: f64 },
+}
+
+impl ToneMapper {
+    // Maps a Rec.709 colour.
+    pub fn map(&self, color: Color) -> Color {
+        self.map_in(color, ColorSpace::Rec709)
+    }
+
+    // Maps a colour in `color_space`, which the luminance based curves weigh
+    // the channels by.
+    pub fn map_in(&self, color: Color, color_space: ColorSpace) -> Color {
+        match self {
+            ToneMapper::Clamp => map_channels(color, |value| value.clamp(0.0, 1.0)),
+            ToneMapper::Reinhard => map_luminance(color, color_space, |luminance| {
+                luminance / (1.0 + luminance)
+            }),
+            ToneMapper::ExtendedReinhard { white_point } => {
+                let white_point = white_point.max(MIN_WHITE_POINT);
+                map_luminance(color, color_space, |luminance| {
+                    luminance * (1.0 + luminance / white_point.powi(2)) / (1.0 + luminance)
+                })
+            }
+            ToneMapper::Aces => map_channels(color, |value| {
+                // Narkowicz's fit of the ACES reference rendering transform.
+                let value = value.max(0.0);
+                let mapped =
+                    (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14);
+                mapped.clamp(0.0, 1.0)
+            }),
+            ToneMapper::Hable { white_point } => {
+                let white_scale = 1.0 / hable_curve(white_point.max(MIN_WHITE_POINT));
+                map_channels(color, |value| hable_curve(value.max(0.0)) * white_scale)
+            }
+        }
+    }
+
+    pub fn apply(&self, canvas: &Canvas, exposure: f64) -> Canvas {
+        let mut mapped = Canvas::new(canvas.width(), canvas.height());
+        mapped.set_color_space(canvas.color_space());
+        // Exposure is given in stops, each doubling the incoming light.
+        let scale = exposure.exp2();
+
+        // The curves apply to straight colour, so each pixel is divided by
+        // its alpha first and multiplied back afterwards.
+        for y in 0..canvas.height() {
+            for x in 0..canvas.width() {
+                let alpha = canvas.get_alpha(x, y);
+                if alpha <= 0.0 {
+                    continue;
+                }
+
+                let color = canvas.get_pixel(x, y) * (scale / alpha);
+                let color = self.map_in(color, canvas.color_space());
+                mapped.set_pixel_with_alpha(x, y, color * alpha, alpha);
+            }
+        }
+
+        mapped
+    }
+}
+
+fn map_channels(color: Color, operator: impl Fn(f64) -> f64) -> Color {
+    Color::new(
+        operator(color.r()),
+        operator(color.g()),
+        operator(color.b()),
+    )
+}
+
+// Scales the colour to the mapped luminance. Saturated colours can then
+// have a channel above one, so those are scaled down further until it fits,
+// keeping the hue at the cost of some brightness.
+fn map_luminance(color: Color, color_space: ColorSpace, operator: impl Fn(f64) -> f64) -> Color {
+    let luminance = color_space.luminance(color);
+
+    if luminance <= 0.0 {
+        return Color::default();
+    }
+
+    let mapped = color * (operator(luminance) / luminance);
+    let peak = mapped.r().max(mapped.g()).max(mapped.b());
+    if peak > 1.0 {
+        mapped * (1.0 / peak)
+    } else {
+        mapped
+    }
+}
+
+// Filmic curve from Uncharted 2, as presented by John Hable.
+fn hable_curve(value: f64) -> f64 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+
+    ((value * (a * value + c * b) + d * e) / (value * (a * value + b) + d * f)) - e / f
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn clamp_tone_mapper() {
+        let color = Color::new(-0.5, 0.5, 4.0);
+
+        assert_eq!(Color::new(0.0, 0.5, 1.0), ToneMapper::Clamp.map(color));
+    }
+
+    #[test]
+    fn reinhard_tone_mapper() {
+        let grey = Color::new(1.0, 1.0, 1.0);
+        let mapped = ToneMapper::Reinhard.map(grey);
+
+        assert!((mapped.r() - 0.5).abs() < 1e-12);
+        assert!((mapped.g() - 0.5).abs() < 1e-12);
+        assert!((mapped.b() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reinhard_weighs_by_color_space() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set_color_space(ColorSpace::Rec2020);
+        canvas.set_pixel(0, 0, red);
+
+        let mapped = ToneMapper::Reinhard.apply(&canvas, 0.0).get_pixel(0, 0);
+        let luminance = ColorSpace::Rec2020.luminance(red);
+
+        assert_approx_eq!(red * (1.0 / (1.0 + luminance)), mapped);
+        assert_ne!(ToneMapper::Reinhard.map(red), mapped);
+    }
+
+    #[test]
+    fn reinhard_preserves_hue() {
+        let color = Color::new(4.0, 2.0, 1.0);
+        let mapped = ToneMapper::Reinhard.map(color);
+
+        assert!((mapped.r() / mapped.g() - 2.0).abs() < 1e-12);
+        assert!((mapped.g() / mapped.b() - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reinhard_keeps_saturated_channels_in_range() {
+        let mapped = ToneMapper::Reinhard.map(Color::new(40.0, 0.0, 0.0));
+
+        assert!((mapped.r() - 1.0).abs() < 1e-12);
+        assert_eq!(0.0, mapped.g());
+    }
+
+    #[test]
+    fn white_point_at_zero_stays_finite() {
+        for mapper in [
+            ToneMapper::ExtendedReinhard { white_point: 0.0 },
+            ToneMapper::Hable { white_point: -1.0 },
+        ] {
+            let mapped = mapper.map(Color::new(0.5, 0.5, 0.5));
+
+            assert!(mapped.r().is_finite() && mapped.r() >= 0.0);
+        }
+    }
+
+    #[test]
+    fn extended_reinhard_white_point() {
+        let mapper = ToneMapper::ExtendedReinhard { white_point: 4.0 };
+        let mapped = mapper.map(Color::new(4.0, 4.0, 4.0));
+
+        assert!((mapped.r() - 1.0).abs() < 1e-12);
+        assert_eq!(Color::default(), mapper.map(Color::default()));
+    }
+
+    #[test]
+    fn aces_tone_mapper() {
+        let mapped = ToneMapper::Aces.map(Color::new(0.0, 1.0, 100.0));
+
+        assert_eq!(0.0, mapped.r());
+        assert!((mapped.g() - 0.803_797).abs() < 1e-6);
+        assert_eq!(1.0, mapped.b());
+    }
+
+    #[test]
+    fn hable_tone_mapper() {
+        let mapper = ToneMapper::Hable { white_point: 11.2 };
+        let mapped = mapper.map(Color::new(0.0, 11.2, 1.0));
+
+        assert!(mapped.r().abs() < 1e-12);
+        assert!((mapped.g() - 1.0).abs() < 1e-12);
+        assert!(mapped.b() > 0.0 && mapped.b() < 1.0);
+    }
+
+    #[test]
+    fn apply_tone_mapper_with_exposure() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel(0, 0, Color::new(0.5, 0.5, 0.5));
+        canvas.set_pixel(1, 0, Color::new(8.0, 0.0, 0.0));
+
+        let mapped = ToneMapper::Clamp.apply(&canvas, 1.0);
+
+        assert_eq!(2, mapped.width());
+        assert_eq!(1, mapped.height());
+        assert_eq!(Color::new(1.0, 1.0, 1.0), mapped.get_pixel(0, 0));
+        assert_eq!(Color::new(1.0, 0.0, 0.0), mapped.get_pixel(1, 0));
+
+        let darkened = ToneMapper::Clamp.apply(&canvas, -1.0);
+        assert_eq!(Color::new(0.25, 0.25, 0.25), darkened.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn apply_tone_mapper_keeps_alpha() {
+        let mut canvas = Canvas::new(2, 1);
+        // Straight colour 4, half covered.
+        canvas.set_pixel_with_alpha(0, 0, Color::new(2.0, 2.0, 2.0), 0.5);
+
+        let mapped = ToneMapper::Reinhard.apply(&canvas, 0.0);
+
+        assert_eq!(0.5, mapped.get_alpha(0, 0));
+        assert!((mapped.get_pixel(0, 0).r() - 0.4).abs() < 1e-12);
+        assert_eq!(0.0, mapped.get_alpha(1, 0));
+        assert_eq!(Color::default(), mapped.get_pixel(1, 0));
+    }
+}
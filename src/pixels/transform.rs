@@ -0,0 +1,345 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::rect::Rect;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    Box,
+    Bilinear,
+    Bicubic,
+    Lanczos,
+}
+
+impl Filter {
+    fn support(&self) -> f64 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Bilinear => 1.0,
+            Filter::Bicubic => 2.0,
+            Filter::Lanczos => 3.0,
+        }
+    }
+
+    // `offset` runs from the destination sample to the source sample. The
+    // box is half open, so a source sample exactly between two destination
+    // samples counts for only one of them.
+    fn weight(&self, offset: f64) -> f64 {
+        let x = offset.abs();
+
+        match self {
+            Filter::Box => {
+                if (-0.5..0.5).contains(&offset) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Bilinear => (1.0 - x).max(0.0),
+            // Catmull-Rom, which interpolates the source samples exactly.
+            Filter::Bicubic => {
+                if x < 1.0 {
+                    1.5 * x * x * x - 2.5 * x * x + 1.0
+                } else if x < 2.0 {
+                    -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Lanczos => {
+                if x < 3.0 {
+                    sinc(x) * sinc(x / 3.0)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    // The first contributing source sample and the normalised weights of the
+    // samples that follow it, for every destination sample along one axis.
+    fn contributions(&self, source_len: usize, destination_len: usize) -> Vec<(usize, Vec<f64>)> {
+        let ratio = source_len as f64 / destination_len as f64;
+        // When shrinking, the kernel is widened so every source sample counts.
+        let scale = ratio.max(1.0);
+        let support = self.support() * scale;
+
+        (0..destination_len)
+            .map(|i| {
+                let center = (i as f64 + 0.5) * ratio;
+                let start = (center - support).floor().max(0.0) as usize;
+                let end = ((center + support).ceil() as usize).min(source_len);
+
+                let mut weights: Vec<f64> = (start..end)
+                    .map(|j| self.weight((j as f64 + 0.5 - center) / scale))
+                    .collect();
+                let total: f64 = weights.iter().sum();
+                if total != 0.0 {
+                    weights.iter_mut().for_each(|weight| *weight /= total);
+                } else if !weights.is_empty() {
+                    // Nothing fell under the kernel, so take the nearest sample.
+                    let nearest = (center.floor() as usize).clamp(start, end - 1);
+                    weights[nearest - start] = 1.0;
+                }
+
+                (start, weights)
+            })
+            .collect()
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = x * std::f64::consts::PI;
+        x.sin() / x
+    }
+}
+
+impl Canvas {
+    // The part of the canvas covered by `rect`, trimmed to the canvas bounds.
+    pub fn crop(&self, rect: Rect) -> Canvas {
+        let rect = rect.intersect(&Rect::new(0, 0, self.width(), self.height()));
+
+        self.remap(rect.width(), rect.height(), |x, y| {
+            (rect.x() + x, rect.y() + y)
+        })
+    }
+
+    pub fn flip_horizontal(&self) -> Canvas {
+        let width = self.width();
+        self.remap(width, self.height(), |x, y| (width - 1 - x, y))
+    }
+
+    pub fn flip_vertical(&self) -> Canvas {
+        let height = self.height();
+        self.remap(self.width(), height, |x, y| (x, height - 1 - y))
+    }
+
+    // Rotations are clockwise.
+    pub fn rotate_90(&self) -> Canvas {
+        let height = self.height();
+        self.remap(height, self.width(), |x, y| (y, height - 1 - x))
+    }
+
+    pub fn rotate_180(&self) -> Canvas {
+        let (width, height) = (self.width(), self.height());
+        self.remap(width, height, |x, y| (width - 1 - x, height - 1 - y))
+    }
+
+    pub fn rotate_270(&self) -> Canvas {
+        let width = self.width();
+        self.remap(self.height(), width, |x, y| (width - 1 - y, x))
+    }
+
+    // Resampling is separable, first along rows and then along columns. The
+    // colours are premultiplied, so they are filtered alongside the alpha.
+    pub fn resize(&self, width: usize, height: usize, filter: Filter) -> Canvas {
+        let mut resized = Canvas::new(width, height);
+        resized.set_color_space(self.color_space());
+        if width == 0 || height == 0 || self.width() == 0 || self.height() == 0 {
+            return resized;
+        }
+
+        let columns = filter.contributions(self.width(), width);
+        let rows = filter.contributions(self.height(), height);
+
+        let mut intermediate = Vec::with_capacity(width * self.height());
+        for y in 0..self.height() {
+            for (start, weights) in &columns {
+                let mut color = Color::default();
+                let mut alpha = 0.0;
+                for (offset, weight) in weights.iter().enumerate() {
+                    color = color + self.get_pixel(start + offset, y) * *weight;
+                    alpha += self.get_alpha(start + offset, y) * weight;
+                }
+                intermediate.push((color, alpha));
+            }
+        }
+
+        for (y, (start, weights)) in rows.iter().enumerate() {
+            for x in 0..width {
+                let mut color = Color::default();
+                let mut alpha = 0.0;
+                for (offset, weight) in weights.iter().enumerate() {
+                    let (sample_color, sample_alpha) = intermediate[x + (start + offset) * width];
+                    color = color + sample_color * *weight;
+                    alpha += sample_alpha * weight;
+                }
+                resized.set_pixel_with_alpha(x, y, color, alpha);
+            }
+        }
+
+        resized
+    }
+
+    // Builds a canvas whose pixels are copied from the source positions
+    // `source` maps them to.
+    fn remap<F>(&self, width: usize, height: usize, source: F) -> Canvas
+    where
+        F: Fn(usize, usize) -> (usize, usize),
+    {
+        let mut remapped = Canvas::new(width, height);
+        remapped.set_color_space(self.color_space());
+
+        for y in 0..height {
+            for x in 0..width {
+                let (source_x, source_y) = source(x, y);
+                remapped.set_pixel_with_alpha(
+                    x,
+                    y,
+                    self.get_pixel(source_x, source_y),
+                    self.get_alpha(source_x, source_y),
+                );
+            }
+        }
+
+        remapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixels::rgb::Rgb;
+
+    // A 3x2 canvas whose red channel numbers the pixels in row order.
+    fn numbered() -> Canvas {
+        let mut canvas = Canvas::new(3, 2);
+        for y in 0..2 {
+            for x in 0..3 {
+                canvas.set_pixel(x, y, Color::new((x + y * 3) as f64, 0.0, 0.0));
+            }
+        }
+        canvas
+    }
+
+    fn numbers(canvas: &Canvas) -> Vec<Vec<f64>> {
+        canvas
+            .rows()
+            .map(|(_, row)| row.iter().map(|color| color.r()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn crop_canvas() {
+        let cropped = numbered().crop(Rect::new(1, 0, 5, 1));
+
+        assert_eq!(vec![vec![1.0, 2.0]], numbers(&cropped));
+        assert_eq!(1.0, cropped.get_alpha(0, 0));
+    }
+
+    #[test]
+    fn flip_canvas() {
+        let canvas = numbered();
+
+        assert_eq!(
+            vec![vec![2.0, 1.0, 0.0], vec![5.0, 4.0, 3.0]],
+            numbers(&canvas.flip_horizontal())
+        );
+        assert_eq!(
+            vec![vec![3.0, 4.0, 5.0], vec![0.0, 1.0, 2.0]],
+            numbers(&canvas.flip_vertical())
+        );
+    }
+
+    #[test]
+    fn rotate_canvas() {
+        let canvas = numbered();
+
+        assert_eq!(
+            vec![vec![3.0, 0.0], vec![4.0, 1.0], vec![5.0, 2.0]],
+            numbers(&canvas.rotate_90())
+        );
+        assert_eq!(
+            vec![vec![5.0, 4.0, 3.0], vec![2.0, 1.0, 0.0]],
+            numbers(&canvas.rotate_180())
+        );
+        assert_eq!(
+            vec![vec![2.0, 5.0], vec![1.0, 4.0], vec![0.0, 3.0]],
+            numbers(&canvas.rotate_270())
+        );
+    }
+
+    #[test]
+    fn resize_same_size() {
+        let canvas = numbered();
+
+        for filter in [
+            Filter::Box,
+            Filter::Bilinear,
+            Filter::Bicubic,
+            Filter::Lanczos,
+        ] {
+            let resized = canvas.resize(3, 2, filter);
+
+            for (expected, actual) in canvas.pixels().iter().zip(resized.pixels()) {
+                assert!((expected.r() - actual.r()).abs() < 1e-12);
+            }
+            assert!((1.0 - resized.get_alpha(2, 1)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn resize_box_down() {
+        let mut canvas = Canvas::new(4, 1);
+        canvas.set_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.set_pixel(1, 0, Color::default());
+        canvas.set_pixel(2, 0, Color::default());
+        canvas.set_pixel(3, 0, Color::new(0.0, 0.0, 1.0));
+        let resized = canvas.resize(2, 1, Filter::Box);
+
+        assert_eq!(Color::new(0.5, 0.0, 0.0), resized.get_pixel(0, 0));
+        assert_eq!(Color::new(0.0, 0.0, 0.5), resized.get_pixel(1, 0));
+        assert_eq!(1.0, resized.get_alpha(0, 0));
+    }
+
+    #[test]
+    fn resize_box_up_between_samples() {
+        // The middle destination sample falls exactly between the two
+        // source samples.
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel(0, 0, Color::new(1.0, 1.0, 1.0));
+        canvas.set_pixel(1, 0, Color::new(1.0, 1.0, 1.0));
+        let resized = canvas.resize(3, 1, Filter::Box);
+
+        for x in 0..3 {
+            assert_eq!(Color::new(1.0, 1.0, 1.0), resized.get_pixel(x, 0));
+            assert_eq!(1.0, resized.get_alpha(x, 0));
+        }
+    }
+
+    #[test]
+    fn resize_bilinear_up() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel(1, 0, Color::new(1.0, 1.0, 1.0));
+        let resized = canvas.resize(4, 1, Filter::Bilinear);
+
+        assert_eq!(vec![vec![0.0, 0.25, 0.75, 1.0]], numbers(&resized));
+    }
+
+    #[test]
+    fn resize_filters_transparency() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.set_pixel(0, 0, Color::new(1.0, 1.0, 1.0));
+        let resized = canvas.resize(1, 1, Filter::Box);
+
+        assert_eq!(0.25, resized.get_alpha(0, 0));
+        assert_eq!(Color::new(0.25, 0.25, 0.25), resized.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn resize_preserves_constant() {
+        let mut canvas = Canvas::new(5, 3);
+        canvas.pixels_mut().for_each(|(color, alpha)| {
+            *color = Color::new(0.5, 0.5, 0.5);
+            *alpha = 1.0;
+        });
+        let resized = canvas.resize(8, 7, Filter::Lanczos);
+
+        for (x, y, color) in resized.enumerate_pixels() {
+            assert!((color.r() - 0.5).abs() < 1e-12);
+            assert!((resized.get_alpha(x, y) - 1.0).abs() < 1e-12);
+        }
+    }
+}
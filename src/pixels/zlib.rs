@@ -0,0 +1,544 @@
+use crate::pixels::error::ImageError;
+
+const LENGTH_BASE: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const WINDOW_SIZE: usize = 32768;
+const HASH_SIZE: usize = 1 << 15;
+const MAX_CHAIN: usize = 64;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    // 32K window, deflate, no preset dictionary; the check bits make it a multiple of 31.
+    writer.bytes.extend_from_slice(&[0x78, 0x9c]);
+
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+    deflate_fixed(data, &mut writer);
+    write_fixed_literal(&mut writer, 256);
+    writer.flush();
+
+    writer.bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    writer.bytes
+}
+
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, ImageError> {
+    if data.len() < 6 {
+        return Err(ImageError::Truncated);
+    }
+
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0f != 8 || (u16::from(cmf) * 256 + u16::from(flg)) % 31 != 0 {
+        return Err(ImageError::Malformed("invalid zlib header".to_string()));
+    }
+    if flg & 0x20 != 0 {
+        return Err(ImageError::Unsupported(
+            "zlib preset dictionary".to_string(),
+        ));
+    }
+
+    let mut reader = BitReader::new(&data[2..]);
+    let output = inflate(&mut reader)?;
+
+    let trailer = reader.aligned_position() + 2;
+    let checksum = data
+        .get(trailer..trailer + 4)
+        .ok_or(ImageError::Truncated)?;
+    if u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) != adler32(&output)
+    {
+        return Err(ImageError::Malformed("zlib checksum mismatch".to_string()));
+    }
+
+    Ok(output)
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    // 5552 is the largest run that cannot overflow `b` before the reduction.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+
+    (b << 16) | a
+}
+
+fn deflate_fixed(data: &[u8], writer: &mut BitWriter) {
+    let mut head = vec![usize::MAX; HASH_SIZE];
+    let mut previous = vec![usize::MAX; WINDOW_SIZE];
+    let mut position = 0;
+
+    while position < data.len() {
+        let (mut best_length, mut best_distance) = (0, 0);
+
+        if position + MIN_MATCH <= data.len() {
+            let mut candidate = head[hash(data, position)];
+            let mut chain = 0;
+
+            while candidate != usize::MAX
+                && position - candidate <= WINDOW_SIZE
+                && chain < MAX_CHAIN
+            {
+                let limit = MAX_MATCH.min(data.len() - position);
+                let length = (0..limit)
+                    .take_while(|&i| data[candidate + i] == data[position + i])
+                    .count();
+
+                if length > best_length {
+                    best_length = length;
+                    best_distance = position - candidate;
+                    if length == limit {
+                        break;
+                    }
+                }
+
+                let next = previous[candidate % WINDOW_SIZE];
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+                chain += 1;
+            }
+        }
+
+        if best_length >= MIN_MATCH {
+            write_length(writer, best_length);
+            write_distance(writer, best_distance);
+
+            for offset in 0..best_length {
+                insert(data, position + offset, &mut head, &mut previous);
+            }
+            position += best_length;
+        } else {
+            write_fixed_literal(writer, usize::from(data[position]));
+            insert(data, position, &mut head, &mut previous);
+            position += 1;
+        }
+    }
+}
+
+fn hash(data: &[u8], position: usize) -> usize {
+    let value = (usize::from(data[position]) << 16)
+        | (usize::from(data[position + 1]) << 8)
+        | usize::from(data[position + 2]);
+
+    (value.wrapping_mul(2_654_435_761) >> 7) & (HASH_SIZE - 1)
+}
+
+fn insert(data: &[u8], position: usize, head: &mut [usize], previous: &mut [usize]) {
+    if position + MIN_MATCH <= data.len() {
+        let key = hash(data, position);
+        previous[position % WINDOW_SIZE] = head[key];
+        head[key] = position;
+    }
+}
+
+fn write_fixed_literal(writer: &mut BitWriter, symbol: usize) {
+    let (code, length) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    };
+
+    writer.write_code(code as u32, length);
+}
+
+fn write_length(writer: &mut BitWriter, length: usize) {
+    let index = LENGTH_BASE
+        .iter()
+        .rposition(|&base| base <= length)
+        .unwrap();
+
+    write_fixed_literal(writer, 257 + index);
+    writer.write_bits((length - LENGTH_BASE[index]) as u32, LENGTH_EXTRA[index]);
+}
+
+fn write_distance(writer: &mut BitWriter, distance: usize) {
+    let index = DISTANCE_BASE
+        .iter()
+        .rposition(|&base| base <= distance)
+        .unwrap();
+
+    writer.write_code(index as u32, 5);
+    writer.write_bits(
+        (distance - DISTANCE_BASE[index]) as u32,
+        DISTANCE_EXTRA[index],
+    );
+}
+
+fn inflate(reader: &mut BitReader) -> Result<Vec<u8>, ImageError> {
+    let mut output = Vec::new();
+
+    loop {
+        let last = reader.read_bits(1)? == 1;
+
+        match reader.read_bits(2)? {
+            0 => inflate_stored(reader, &mut output)?,
+            1 => {
+                let (literals, distances) = fixed_tables();
+                inflate_block(reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_tables(reader)?;
+                inflate_block(reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(ImageError::Malformed("invalid deflate block".to_string())),
+        }
+
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+fn inflate_stored(reader: &mut BitReader, output: &mut Vec<u8>) -> Result<(), ImageError> {
+    reader.align();
+
+    let length = reader.read_bits(16)?;
+    let complement = reader.read_bits(16)?;
+    if length != !complement & 0xffff {
+        return Err(ImageError::Malformed("stored block length".to_string()));
+    }
+
+    for _ in 0..length {
+        output.push(reader.read_bits(8)? as u8);
+    }
+
+    Ok(())
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), ImageError> {
+    loop {
+        let symbol = literals.decode(reader)?;
+
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let length = LENGTH_BASE[index] + reader.read_bits(LENGTH_EXTRA[index])? as usize;
+
+                let index = distances.decode(reader)?;
+                if index >= 30 {
+                    return Err(ImageError::Malformed("invalid distance code".to_string()));
+                }
+                let distance =
+                    DISTANCE_BASE[index] + reader.read_bits(DISTANCE_EXTRA[index])? as usize;
+                if distance > output.len() {
+                    return Err(ImageError::Malformed("distance too far back".to_string()));
+                }
+
+                // Copy byte by byte, as the match may overlap the bytes it produces.
+                let start = output.len() - distance;
+                for offset in 0..length {
+                    output.push(output[start + offset]);
+                }
+            }
+            _ => return Err(ImageError::Malformed("invalid literal code".to_string())),
+        }
+    }
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), ImageError> {
+    let literal_count = reader.read_bits(5)? as usize + 257;
+    let distance_count = reader.read_bits(5)? as usize + 1;
+    let code_length_count = reader.read_bits(4)? as usize + 4;
+
+    let mut code_lengths = [0; 19];
+    for &index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_lengths[index] = reader.read_bits(3)? as u8;
+    }
+    let code_length_huffman = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code_length_huffman.decode(reader)?;
+
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or_else(|| ImageError::Malformed("repeat without length".to_string()))?;
+                (previous, 3 + reader.read_bits(2)?)
+            }
+            17 => (0, 3 + reader.read_bits(3)?),
+            _ => (0, 11 + reader.read_bits(7)?),
+        };
+
+        for _ in 0..repeat {
+            lengths.push(value);
+        }
+    }
+
+    if lengths.len() != literal_count + distance_count {
+        return Err(ImageError::Malformed("code lengths overflow".to_string()));
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ))
+}
+
+// Canonical Huffman decoding table: the number of codes of every length and
+// the symbols ordered by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[usize::from(length)] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for length in 1..16 {
+            offsets[length] = offsets[length - 1] + counts[length - 1];
+        }
+
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[usize::from(offsets[usize::from(length)])] = symbol as u16;
+                offsets[usize::from(length)] += 1;
+            }
+        }
+
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<usize, ImageError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+
+        for length in 1..16 {
+            code |= reader.read_bits(1)? as i32;
+            let count = i32::from(self.counts[length]);
+
+            if code - count < first {
+                return Ok(usize::from(self.symbols[(index + code - first) as usize]));
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(ImageError::Malformed("invalid Huffman code".to_string()))
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        BitReader {
+            bytes,
+            position: 0,
+            bit: 0,
+        }
+    }
+
+    fn read_bits(&mut self, count: u32) -> Result<u32, ImageError> {
+        let mut value = 0;
+
+        for i in 0..count {
+            let byte = self.bytes.get(self.position).ok_or(ImageError::Truncated)?;
+            value |= u32::from((byte >> self.bit) & 1) << i;
+
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+
+    fn aligned_position(&self) -> usize {
+        self.position + usize::from(self.bit != 0)
+    }
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        BitWriter {
+            bytes: Vec::new(),
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn write_bits(&mut self, value: u32, count: u32) {
+        for i in 0..count {
+            self.buffer |= ((value >> i) & 1) << self.count;
+            self.count += 1;
+
+            if self.count == 8 {
+                self.bytes.push(self.buffer as u8);
+                self.buffer = 0;
+                self.count = 0;
+            }
+        }
+    }
+
+    // Huffman codes are packed starting from their most significant bit.
+    fn write_code(&mut self, code: u32, length: u32) {
+        for i in (0..length).rev() {
+            self.write_bits((code >> i) & 1, 1);
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer = 0;
+            self.count = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adler32_known_value() {
+        assert_eq!(1, adler32(b""));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn compress_round_trip() {
+        let data: Vec<u8> = (0..20000).map(|i| ((i * i) % 251) as u8).collect();
+
+        assert_eq!(data, decompress(&compress(&data)).unwrap());
+        assert_eq!(Vec::<u8>::new(), decompress(&compress(&[])).unwrap());
+    }
+
+    #[test]
+    fn compress_repetitive_data() {
+        let data = vec![42u8; 100_000];
+        let compressed = compress(&data);
+
+        assert!(compressed.len() < 1000);
+        assert_eq!(data, decompress(&compressed).unwrap());
+    }
+
+    #[test]
+    fn decompress_stored_block() {
+        // "abc" as a single stored block.
+        let data = [
+            0x78, 0x01, 0x01, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c', 0x02, 0x4d, 0x01, 0x27,
+        ];
+
+        assert_eq!(b"abc".to_vec(), decompress(&data).unwrap());
+    }
+
+    #[test]
+    fn decompress_fixed_block() {
+        // zlib.compress(b"hello hello hello hello, yart!" * 4, 9) from CPython.
+        let data = [
+            0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x75, 0x14, 0x2a,
+            0x13, 0x8b, 0x4a, 0x14, 0x33, 0x68, 0x26, 0x0b, 0x00, 0x58, 0x32, 0x2b, 0x75,
+        ];
+
+        let expected = b"hello hello hello hello, yart!".repeat(4);
+        assert_eq!(expected, decompress(&data).unwrap());
+    }
+
+    #[test]
+    fn decompress_dynamic_block() {
+        // The alphabet three times followed by a pangram twice, compressed by CPython.
+        let data = [
+            0x78, 0xda, 0x9d, 0xca, 0xc7, 0x11, 0xc0, 0x20, 0x0c, 0x00, 0xb0, 0x55, 0xbc, 0x1a,
+            0xbd, 0x63, 0x30, 0x9d, 0xe9, 0x73, 0x19, 0x21, 0x79, 0x4b, 0x8c, 0x0b, 0xa9, 0xb4,
+            0xb1, 0xce, 0x87, 0x98, 0x32, 0x96, 0x4a, 0xad, 0x8f, 0xb9, 0xf6, 0xb9, 0xec, 0x87,
+            0x74, 0xab, 0xa0, 0x0e, 0x27, 0x02, 0x70, 0xc2, 0x95, 0x41, 0xe3, 0x06, 0x3f, 0x52,
+            0x69, 0x80, 0x53, 0x11, 0xbc, 0x1c, 0xd9, 0x3d, 0x20, 0xd1, 0x7c, 0xa8, 0x0f, 0xbc,
+            0xaa, 0x41, 0x50,
+        ];
+
+        let mut expected: Vec<u8> = (b'a'..=b'z').collect::<Vec<u8>>().repeat(3);
+        expected.extend(b"the quick brown fox jumps over the lazy dog".repeat(2));
+        assert_eq!(expected, decompress(&data).unwrap());
+    }
+
+    #[test]
+    fn decompress_errors() {
+        assert_eq!(Err(ImageError::Truncated), decompress(&[0x78]));
+        assert!(matches!(
+            decompress(&[0x78, 0x00, 0, 0, 0, 0]),
+            Err(ImageError::Malformed(_))
+        ));
+
+        let mut corrupted = compress(b"some data");
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xff;
+        assert!(matches!(
+            decompress(&corrupted),
+            Err(ImageError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,322 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::rendering::camera::Camera;
+use crate::rendering::film::Film;
+use crate::rendering::ray::Ray;
+use crate::rendering::sampler::Sampler;
+
+// Running mean and variance, updated one value at a time with Welford's
+// numerically stable method.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Welford {
+    count: usize,
+    mean: f64,
+    squared_deviations: f64,
+}
+
+impl Welford {
+    pub fn add(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.squared_deviations += delta * (value - self.mean);
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    // The unbiased sample variance, zero until there are two values.
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            return 0.0;
+        }
+        self.squared_deviations / (self.count - 1) as f64
+    }
+
+    // The expected error of the mean as an estimate.
+    pub fn standard_error(&self) -> f64 {
+        if self.count == 0 {
+            return f64::INFINITY;
+        }
+        (self.variance() / self.count as f64).sqrt()
+    }
+}
+
+// When to stop sampling a pixel. Every pixel takes at least `min_samples`
+// to estimate its variance, then stops once the standard error of its
+// luminance falls below `threshold` relative to its mean, or at
+// `max_samples`. Means below 0.01 count as 0.01, so black pixels converge.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveSampling {
+    min_samples: usize,
+    max_samples: usize,
+    threshold: f64,
+}
+
+impl AdaptiveSampling {
+    pub fn new(min_samples: usize, max_samples: usize, threshold: f64) -> Self {
+        let min_samples = min_samples.max(2);
+
+        AdaptiveSampling {
+            min_samples,
+            max_samples: max_samples.max(min_samples),
+            threshold,
+        }
+    }
+
+    pub fn min_samples(&self) -> usize {
+        self.min_samples
+    }
+
+    pub fn max_samples(&self) -> usize {
+        self.max_samples
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    pub fn converged(&self, statistics: &Welford) -> bool {
+        if statistics.count() < self.min_samples {
+            return false;
+        }
+        if statistics.count() >= self.max_samples {
+            return true;
+        }
+
+        statistics.standard_error() <= self.threshold * statistics.mean().abs().max(0.01)
+    }
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        AdaptiveSampling::new(16, 1024, 0.01)
+    }
+}
+
+// How many samples each pixel of an adaptive render took.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SampleCounts {
+    width: usize,
+    height: usize,
+    counts: Vec<usize>,
+}
+
+impl SampleCounts {
+    pub fn get(&self, x: usize, y: usize) -> usize {
+        self.counts[y * self.width + x]
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    pub fn max(&self) -> usize {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    // Counts relative to `max_samples` shown from black through red and
+    // yellow to white.
+    pub fn heatmap(&self, max_samples: usize) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let heat =
+                    (self.get(x, y) as f64 / max_samples.max(1) as f64).clamp(0.0, 1.0) * 3.0;
+                let channel = |start: f64| (heat - start).clamp(0.0, 1.0);
+                canvas.set_pixel(x, y, Color::new(channel(0.0), channel(1.0), channel(2.0)));
+            }
+        }
+
+        canvas
+    }
+}
+
+impl Camera {
+    // Like `render_to_film`, but keeps sampling each pixel only until its
+    // estimate is good enough. The sampler must provide at least
+    // `max_samples` samples per pixel.
+    pub fn render_adaptive<S: Sampler>(
+        &self,
+        film: &mut Film,
+        sampler: &mut S,
+        adaptive: &AdaptiveSampling,
+        mut radiance: impl FnMut(&Ray, &mut S) -> Option<Color>,
+    ) -> SampleCounts {
+        assert_eq!((self.width(), self.height()), (film.width(), film.height()));
+        assert!(
+            adaptive.max_samples() <= sampler.samples_per_pixel(),
+            "up to {} samples per pixel from a sampler with {}",
+            adaptive.max_samples(),
+            sampler.samples_per_pixel()
+        );
+        let mut counts = Vec::with_capacity(self.width() * self.height());
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let mut statistics = Welford::default();
+
+                while !adaptive.converged(&statistics) {
+                    sampler.start_pixel_sample(x, y, statistics.count());
+                    let (dx, dy) = sampler.get_2d();
+                    let (x, y) = (x as f64 + dx, y as f64 + dy);
+                    let color = self
+                        .sample_ray(x, y, sampler)
+                        .and_then(|ray| radiance(&ray, sampler));
+
+                    film.add_sample(x, y, color);
+                    let luminance = color.map_or(0.0, |color| film.color_space().luminance(color));
+                    statistics.add(luminance);
+                }
+
+                counts.push(statistics.count());
+            }
+        }
+
+        SampleCounts {
+            width: self.width(),
+            height: self.height(),
+            counts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+    use crate::pixels::rgb::Rgb;
+    use crate::rendering::film::ReconstructionFilter;
+    use crate::rendering::sampler::IndependentSampler;
+    use crate::tuples::coordinates::Coordinates;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn welford_statistics() {
+        let mut statistics = Welford::default();
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            statistics.add(value);
+        }
+
+        assert_eq!(8, statistics.count());
+        assert_approx_eq!(5.0, statistics.mean());
+        assert_approx_eq!(32.0 / 7.0, statistics.variance());
+        assert_approx_eq!((32.0 / 7.0 / 8.0_f64).sqrt(), statistics.standard_error());
+    }
+
+    #[test]
+    fn welford_is_stable() {
+        // A large offset ruins the naive sum of squares but not Welford.
+        let mut statistics = Welford::default();
+        for value in [4.0, 7.0, 13.0, 16.0] {
+            statistics.add(1e9 + value);
+        }
+
+        assert_approx_eq!(30.0, statistics.variance(), Margin::Absolute(1e-6));
+    }
+
+    #[test]
+    fn welford_empty() {
+        let statistics = Welford::default();
+
+        assert_eq!(0.0, statistics.variance());
+        assert_eq!(f64::INFINITY, statistics.standard_error());
+    }
+
+    #[test]
+    fn adaptive_sampling_bounds() {
+        let adaptive = AdaptiveSampling::new(0, 0, 0.1);
+
+        assert_eq!(2, adaptive.min_samples());
+        assert_eq!(2, adaptive.max_samples());
+    }
+
+    #[test]
+    fn converged_needs_min_samples() {
+        let adaptive = AdaptiveSampling::new(4, 8, 0.1);
+        let mut statistics = Welford::default();
+        for _ in 0..3 {
+            statistics.add(0.5);
+        }
+
+        assert!(!adaptive.converged(&statistics));
+        statistics.add(0.5);
+        assert!(adaptive.converged(&statistics));
+    }
+
+    #[test]
+    fn converged_at_max_samples() {
+        let adaptive = AdaptiveSampling::new(2, 4, 0.0);
+        let mut statistics = Welford::default();
+        for value in [0.0, 1.0, 0.0] {
+            statistics.add(value);
+        }
+
+        assert!(!adaptive.converged(&statistics));
+        statistics.add(1.0);
+        assert!(adaptive.converged(&statistics));
+    }
+
+    #[test]
+    fn render_adaptive_spends_samples_on_noise() {
+        // The right half of the view flickers between black and white, the
+        // left half is a constant grey.
+        let camera = Camera::new(4, 2, FRAC_PI_2);
+        let adaptive = AdaptiveSampling::new(8, 256, 0.05);
+        let mut film = Film::new(4, 2, ReconstructionFilter::default());
+        let mut sampler = IndependentSampler::new(256, 1);
+
+        let counts = camera.render_adaptive(&mut film, &mut sampler, &adaptive, |ray, sampler| {
+            let value = if ray.direction().x() < 0.0 {
+                0.5
+            } else if sampler.get_1d() < 0.5 {
+                1.0
+            } else {
+                0.0
+            };
+            Some(Color::new(value, value, value))
+        });
+        let canvas = film.resolve();
+
+        assert_eq!(8, counts.get(0, 0));
+        assert_eq!(8, counts.get(1, 1));
+        assert!(counts.get(3, 0) > 100);
+        assert!(counts.max() <= 256);
+        assert_approx_eq!(0.5, canvas.get_pixel(0, 0).r(), Margin::Absolute(1e-9));
+        assert_approx_eq!(0.5, canvas.get_pixel(3, 0).r(), Margin::Absolute(0.1));
+    }
+
+    #[test]
+    #[should_panic(expected = "up to 256 samples per pixel from a sampler with 64")]
+    fn render_adaptive_needs_enough_samples() {
+        let camera = Camera::new(4, 2, FRAC_PI_2);
+        let adaptive = AdaptiveSampling::new(8, 256, 0.05);
+        let mut film = Film::new(4, 2, ReconstructionFilter::default());
+        let mut sampler = IndependentSampler::new(64, 1);
+
+        camera.render_adaptive(&mut film, &mut sampler, &adaptive, |_, _| None);
+    }
+
+    #[test]
+    fn heatmap_shows_counts() {
+        let counts = SampleCounts {
+            width: 3,
+            height: 1,
+            counts: vec![0, 50, 100],
+        };
+
+        let heatmap = counts.heatmap(100);
+
+        assert_eq!(150, counts.total());
+        assert_eq!(Color::new(0.0, 0.0, 0.0), heatmap.get_pixel(0, 0));
+        assert_approx_eq!(Color::new(1.0, 0.5, 0.0), heatmap.get_pixel(1, 0));
+        assert_eq!(Color::new(1.0, 1.0, 1.0), heatmap.get_pixel(2, 0));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::tuples::coordinates::Coordinates;
+use crate::tuples::point::Point;
+use crate::tuples::vector::Vector;
+
+// An axis aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    min: Point,
+    max: Point,
+}
+
+impl Bounds {
+    // The smallest box around both corners, whichever order they come in.
+    pub fn new(a: Point, b: Point) -> Self {
+        Bounds {
+            min: Point::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            max: Point::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        }
+    }
+
+    // The smallest box around every point, or `None` without any.
+    pub fn around(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        points
+            .into_iter()
+            .map(|point| Bounds::new(point, point))
+            .reduce(|bounds, point| bounds.union(&point))
+    }
+
+    pub fn min(&self) -> Point {
+        self.min
+    }
+
+    pub fn max(&self) -> Point {
+        self.max
+    }
+
+    pub fn corners(&self) -> [Point; 8] {
+        let (min, max) = (self.min, self.max);
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|corner| {
+            Point::new(
+                if corner & 1 == 0 { min.x() } else { max.x() },
+                if corner & 2 == 0 { min.y() } else { max.y() },
+                if corner & 4 == 0 { min.z() } else { max.z() },
+            )
+        })
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min: Bounds::new(self.min, other.min).min,
+            max: Bounds::new(self.max, other.max).max,
+        }
+    }
+
+    // Grows the box by `margin` on every side.
+    pub fn expand(&self, margin: f64) -> Bounds {
+        let margin = Vector::new(margin, margin, margin);
+        Bounds {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x()..=self.max.x()).contains(&point.x())
+            && (self.min.y()..=self.max.y()).contains(&point.y())
+            && (self.min.z()..=self.max.z()).contains(&point.z())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_bounds_orders_corners() {
+        let bounds = Bounds::new(Point::new(1.0, -2.0, 3.0), Point::new(-1.0, 2.0, 0.0));
+
+        assert_eq!(Point::new(-1.0, -2.0, 0.0), bounds.min());
+        assert_eq!(Point::new(1.0, 2.0, 3.0), bounds.max());
+        assert!(bounds.contains(Point::new(0.0, 2.0, 1.5)));
+        assert!(!bounds.contains(Point::new(0.0, 2.5, 1.5)));
+    }
+
+    #[test]
+    fn bounds_around_points() {
+        let bounds = Bounds::around([
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(0.0, 5.0, -1.0),
+            Point::new(-2.0, 1.0, 1.0),
+        ])
+        .unwrap();
+
+        assert_eq!(Point::new(-2.0, 0.0, -1.0), bounds.min());
+        assert_eq!(Point::new(1.0, 5.0, 1.0), bounds.max());
+        assert_eq!(None, Bounds::around([]));
+    }
+
+    #[test]
+    fn bounds_corners_and_expand() {
+        let bounds = Bounds::new(Point::new(0.0, 0.0, 0.0), Point::new(1.0, 2.0, 3.0));
+        let expanded = bounds.expand(0.5);
+
+        assert_eq!(Some(bounds), Bounds::around(bounds.corners()));
+        assert!(bounds.corners().contains(&Point::new(1.0, 0.0, 3.0)));
+        assert_eq!(Point::new(-0.5, -0.5, -0.5), expanded.min());
+        assert_eq!(Point::new(1.5, 2.5, 3.5), expanded.max());
+    }
+}
@@ -0,0 +1,1057 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::rendering::film::{Film, ReconstructionFilter};
+use crate::rendering::lens::Aperture;
+use crate::rendering::motion::{Keyframes, Quaternion, Transform};
+use crate::rendering::projection::Projection;
+use crate::rendering::random::{hash, Random};
+use crate::rendering::ray::Ray;
+use crate::rendering::sampler::Sampler;
+use crate::tuples::coordinates::Coordinates;
+use crate::tuples::point::Point;
+use crate::tuples::scalar::Scalar;
+use crate::tuples::vector::Vector;
+
+// A camera at the origin looking down negative z until moved with `look_at`.
+// The image plane sits one unit in front of the eye. Without an aperture it
+// is a pinhole with everything in focus, otherwise a thin lens focused at
+// `focal_distance`. While the shutter is open it may follow keyframes,
+// blurring whatever moves relative to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera {
+    width: usize,
+    height: usize,
+    field_of_view: f64,
+    origin: Point,
+    right: Vector,
+    up: Vector,
+    forward: Vector,
+    samples: usize,
+    seed: u64,
+    aperture_radius: f64,
+    focal_distance: f64,
+    aperture: Aperture,
+    projection: Projection,
+    image_shift: (f64, f64),
+    eye_offset: Vector,
+    shutter_open: f64,
+    shutter_close: f64,
+    motion: Option<Keyframes<Transform>>,
+}
+
+impl Camera {
+    // `field_of_view` is the horizontal or vertical angle in radians,
+    // whichever side of the image is longer.
+    pub fn new(width: usize, height: usize, field_of_view: f64) -> Camera {
+        Camera {
+            width,
+            height,
+            field_of_view,
+            origin: Point::default(),
+            right: Vector::new(1.0, 0.0, 0.0),
+            up: Vector::new(0.0, 1.0, 0.0),
+            forward: Vector::new(0.0, 0.0, -1.0),
+            samples: 1,
+            seed: 0,
+            aperture_radius: 0.0,
+            focal_distance: 1.0,
+            aperture: Aperture::default(),
+            projection: Projection::default(),
+            image_shift: (0.0, 0.0),
+            eye_offset: Vector::default(),
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            motion: None,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn field_of_view(&self) -> f64 {
+        self.field_of_view
+    }
+
+    // Where the eye is and which way it faces as the shutter opens, after
+    // any keyframes and the eye offset.
+    pub fn origin(&self) -> Point {
+        self.frame(self.shutter_open).origin
+    }
+
+    pub fn forward(&self) -> Vector {
+        self.frame(self.shutter_open).forward
+    }
+
+    pub fn right(&self) -> Vector {
+        self.frame(self.shutter_open).right
+    }
+
+    pub fn up(&self) -> Vector {
+        self.frame(self.shutter_open).up
+    }
+
+    // Places the eye at `from` looking towards `to`, keeping `up` roughly
+    // upwards in the image. An `up` along the view falls back to another
+    // axis, see `view_axes`, and a `to` at `from` keeps the current view
+    // direction.
+    pub fn look_at(&mut self, from: Point, to: Point, up: Vector) {
+        let forward = view_direction(from, to, self.forward);
+        let (right, up) = view_axes(forward, up);
+
+        self.origin = from;
+        self.forward = forward;
+        self.right = right;
+        self.up = up;
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    // Rays shot through each pixel. A single sample goes through the centre,
+    // more are jittered over a stratified grid.
+    pub fn set_samples(&mut self, samples: usize) {
+        self.samples = samples.max(1);
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn aperture_radius(&self) -> f64 {
+        self.aperture_radius
+    }
+
+    // Zero makes a pinhole camera.
+    pub fn set_aperture_radius(&mut self, aperture_radius: f64) {
+        self.aperture_radius = aperture_radius.max(0.0);
+    }
+
+    pub fn focal_distance(&self) -> f64 {
+        self.focal_distance
+    }
+
+    // Distance along the view direction of the plane in sharp focus.
+    pub fn set_focal_distance(&mut self, focal_distance: f64) {
+        self.focal_distance = focal_distance;
+    }
+
+    pub fn aperture(&self) -> &Aperture {
+        &self.aperture
+    }
+
+    pub fn set_aperture(&mut self, aperture: Aperture) {
+        self.aperture = aperture;
+    }
+
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    pub fn eye_offset(&self) -> Vector {
+        self.eye_offset
+    }
+
+    // Moves the eye away from where `look_at` or the keyframes place it, in
+    // its own axes with x to the right, y up and z forwards, so the offset
+    // turns along with the camera.
+    pub fn set_eye_offset(&mut self, offset: Vector) {
+        self.eye_offset = offset;
+    }
+
+    pub fn image_shift(&self) -> (f64, f64) {
+        self.image_shift
+    }
+
+    // Slides the perspective image plane right and up by a share of its
+    // distance, giving an off-axis view without turning the camera. Other
+    // projections ignore it.
+    pub fn set_image_shift(&mut self, x: f64, y: f64) {
+        self.image_shift = (x, y);
+    }
+
+    pub fn shutter_open(&self) -> f64 {
+        self.shutter_open
+    }
+
+    pub fn shutter_close(&self) -> f64 {
+        self.shutter_close
+    }
+
+    // Rays get times spread evenly over [open, close]. An empty interval
+    // freezes the scene at `open`.
+    pub fn set_shutter(&mut self, open: f64, close: f64) {
+        self.shutter_open = open;
+        self.shutter_close = close.max(open);
+    }
+
+    // Places the eye as `look_at` does at `time`, moving it between
+    // keyframes with a straight path and a steady turn. Once the camera has
+    // keyframes they take the place of `look_at`. A `to` at `from` keeps
+    // the direction the camera faces at `time` so far.
+    pub fn add_keyframe(&mut self, time: f64, from: Point, to: Point, up: Vector) {
+        let forward = view_direction(from, to, self.frame(time).forward);
+        let (right, up) = view_axes(forward, up);
+        // The camera looks down its negative z axis, so its frame is right
+        // handed with z backwards.
+        let rotation = Quaternion::from_basis(right, up, -forward);
+        let transform = Transform::new(
+            from - Point::default(),
+            rotation,
+            Vector::new(1.0, 1.0, 1.0),
+        );
+
+        match &mut self.motion {
+            Some(motion) => motion.insert(time, transform),
+            None => self.motion = Some(Keyframes::new(time, transform)),
+        }
+    }
+
+    // The width of a pixel on the perspective image plane.
+    pub fn pixel_size(&self) -> f64 {
+        let half_view = (self.field_of_view / 2.0).tan();
+        half_view * 2.0 / self.width.max(self.height) as f64
+    }
+
+    // The ray through a position on the image, in pixels from its top left
+    // corner, so (0.5, 0.5) is the centre of the first pixel. It leaves from
+    // the centre of the lens. Returns `None` for positions the projection
+    // does not cover. The ray is cast as the shutter opens.
+    pub fn ray_for_position(&self, x: f64, y: f64) -> Option<Ray> {
+        let frame = self.frame(self.shutter_open);
+        let (origin, direction) = self.world_ray(&frame, x, y)?;
+        Some(Ray::new(origin, direction.normalize()).with_time(self.shutter_open))
+    }
+
+    pub fn ray_for_pixel(&self, x: usize, y: usize) -> Option<Ray> {
+        self.ray_for_position(x as f64 + 0.5, y as f64 + 0.5)
+    }
+
+    // The ray through a position on the image leaving from the point of the
+    // aperture that `lens`, a sample in [0, 1) squared, maps to. All rays
+    // through one position meet on the focal plane. Only the perspective
+    // projection models a lens.
+    pub fn ray_through_lens(&self, x: f64, y: f64, lens: (f64, f64)) -> Option<Ray> {
+        self.ray_at_time(x, y, lens, self.shutter_open)
+    }
+
+    // Like `ray_through_lens`, cast at `time` from wherever the camera's
+    // keyframes place it then.
+    pub fn ray_at_time(&self, x: f64, y: f64, lens: (f64, f64), time: f64) -> Option<Ray> {
+        let frame = self.frame(time);
+        let (origin, direction) = self.world_ray(&frame, x, y)?;
+        if !self.has_lens() {
+            return Some(Ray::new(origin, direction.normalize()).with_time(time));
+        }
+
+        let focus = frame.origin + direction * self.focal_distance;
+        let (u, v) = self.aperture.sample(lens.0, lens.1);
+        let origin = frame.origin
+            + frame.right * (u * self.aperture_radius)
+            + frame.up * (v * self.aperture_radius);
+
+        Some(Ray::new(origin, (focus - origin).normalize()).with_time(time))
+    }
+
+    // Like `ray_at_time`, drawing the lens sample from `sampler` only when
+    // the camera has a lens, then the time only when the shutter is open
+    // for a while.
+    pub fn sample_ray(&self, x: f64, y: f64, sampler: &mut impl Sampler) -> Option<Ray> {
+        let lens = if self.has_lens() {
+            sampler.get_2d()
+        } else {
+            (0.5, 0.5)
+        };
+        let time = if self.has_exposure() {
+            self.shutter_time(sampler.get_1d())
+        } else {
+            self.shutter_open
+        };
+
+        self.ray_at_time(x, y, lens, time)
+    }
+
+    fn has_lens(&self) -> bool {
+        self.aperture_radius > 0.0 && self.projection == Projection::Perspective
+    }
+
+    fn has_exposure(&self) -> bool {
+        self.shutter_close > self.shutter_open
+    }
+
+    // Maps a uniform sample in [0, 1) to a moment the shutter is open.
+    fn shutter_time(&self, u: f64) -> f64 {
+        self.shutter_open + (self.shutter_close - self.shutter_open) * u
+    }
+
+    // Where the camera is and which way it faces at `time`.
+    fn frame(&self, time: f64) -> Frame {
+        let mut frame = match &self.motion {
+            Some(motion) => {
+                let transform = motion.at(time);
+                Frame {
+                    origin: transform.apply_point(Point::default()),
+                    right: transform.apply_vector(Vector::new(1.0, 0.0, 0.0)),
+                    up: transform.apply_vector(Vector::new(0.0, 1.0, 0.0)),
+                    forward: transform.apply_vector(Vector::new(0.0, 0.0, -1.0)),
+                }
+            }
+            None => Frame {
+                origin: self.origin,
+                right: self.right,
+                up: self.up,
+                forward: self.forward,
+            },
+        };
+        let offset = self.eye_offset;
+        frame.origin = frame.origin
+            + frame.right * offset.x()
+            + frame.up * offset.y()
+            + frame.forward * offset.z();
+
+        frame
+    }
+
+    // The projection's ray in world space, with an unnormalised direction.
+    fn world_ray(&self, frame: &Frame, x: f64, y: f64) -> Option<(Point, Vector)> {
+        let (origin, mut direction) =
+            self.projection
+                .local_ray(x, y, self.width, self.height, self.field_of_view)?;
+        if self.projection == Projection::Perspective {
+            direction[0] += self.image_shift.0;
+            direction[1] += self.image_shift.1;
+        }
+        let to_world = |local: [f64; 3]| {
+            frame.right * local[0] + frame.up * local[1] + frame.forward * local[2]
+        };
+
+        Some((frame.origin + to_world(origin), to_world(direction)))
+    }
+
+    // Offsets within a pixel, each in [0, 1), for every sample it takes.
+    // Samples fill the cells of a square grid in a shuffled order and are
+    // jittered within their cell, seeded from the pixel so renders repeat.
+    pub fn sample_offsets(&self, x: usize, y: usize) -> Vec<(f64, f64)> {
+        if self.samples == 1 {
+            return vec![(0.5, 0.5)];
+        }
+
+        let mut random = Random::new(hash(&[self.seed, x as u64, y as u64]));
+        let side = (self.samples as f64).sqrt().ceil() as usize;
+        let mut cells: Vec<usize> = (0..side * side).collect();
+        if cells.len() > self.samples {
+            random.shuffle(&mut cells);
+        }
+
+        cells[..self.samples]
+            .iter()
+            .map(|cell| {
+                let column = (cell % side) as f64 + random.next_f64();
+                let row = (cell / side) as f64 + random.next_f64();
+                (column / side as f64, row / side as f64)
+            })
+            .collect()
+    }
+
+    // Renders an image by averaging the radiance arriving along every sample
+    // ray of each pixel. Radiance is `None` for rays that miss the scene,
+    // which leave their samples transparent just like positions the
+    // projection does not cover, so each pixel's alpha is the share of its
+    // samples that hit something.
+    pub fn render(&self, radiance: impl Fn(&Ray) -> Option<Color>) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let offsets = self.sample_offsets(x, y);
+                // Lens and shutter samples come from a generator of their own,
+                // so adding an aperture leaves the pixel offsets unchanged.
+                let mut lens = Random::with_stream(hash(&[self.seed, x as u64, y as u64]), 1);
+                let (mut sum, mut hits) = (Color::default(), 0);
+                for (dx, dy) in offsets.iter() {
+                    let sample = (lens.next_f64(), lens.next_f64());
+                    let time = if self.has_exposure() {
+                        self.shutter_time(lens.next_f64())
+                    } else {
+                        self.shutter_open
+                    };
+                    let ray = self.ray_at_time(x as f64 + dx, y as f64 + dy, sample, time);
+                    if let Some(color) = ray.and_then(|ray| radiance(&ray)) {
+                        sum = sum + color;
+                        hits += 1;
+                    }
+                }
+
+                let scale = 1.0 / offsets.len() as f64;
+                canvas.set_pixel_with_alpha(x, y, sum * scale, hits as f64 * scale);
+            }
+        }
+
+        canvas
+    }
+
+    // Like `render`, but takes as many samples per pixel as `sampler` and
+    // positions them with its first 2D sample. The radiance function can
+    // draw further dimensions from the sampler, for example for lights.
+    pub fn render_with<S: Sampler>(
+        &self,
+        sampler: &mut S,
+        radiance: impl FnMut(&Ray, &mut S) -> Option<Color>,
+    ) -> Canvas {
+        let mut film = Film::new(self.width, self.height, ReconstructionFilter::default());
+        self.render_to_film(&mut film, sampler, radiance);
+
+        film.resolve()
+    }
+
+    // Adds every sample to `film`, which reconstructs the image with its
+    // filter and must match the camera in size.
+    pub fn render_to_film<S: Sampler>(
+        &self,
+        film: &mut Film,
+        sampler: &mut S,
+        mut radiance: impl FnMut(&Ray, &mut S) -> Option<Color>,
+    ) {
+        assert_eq!((self.width, self.height), (film.width(), film.height()));
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                for index in 0..sampler.samples_per_pixel() {
+                    sampler.start_pixel_sample(x, y, index);
+                    let (dx, dy) = sampler.get_2d();
+                    let (x, y) = (x as f64 + dx, y as f64 + dy);
+                    let color = self
+                        .sample_ray(x, y, sampler)
+                        .and_then(|ray| radiance(&ray, sampler));
+                    film.add_sample(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+// The direction from `from` to `to`, or `current` when they coincide and
+// there is none.
+fn view_direction(from: Point, to: Point, current: Vector) -> Vector {
+    let direction = to - from;
+    if direction.magnitude() == 0.0 {
+        current
+    } else {
+        direction.normalize()
+    }
+}
+
+// The right and up axes of a view along `forward`, with up as close to
+// `up` as the view allows. When `up` runs along the view, or is zero, the
+// world's y axis takes its place, or for views along y the z axis a camera
+// tilted that far would have.
+fn view_axes(forward: Vector, up: Vector) -> (Vector, Vector) {
+    let mut right = forward.cross_product(up);
+    if right.magnitude() <= 1e-9 * up.magnitude() || right.magnitude() == 0.0 {
+        let fallback = if forward.y().abs() < 0.9 {
+            Vector::new(0.0, 1.0, 0.0)
+        } else {
+            Vector::new(0.0, 0.0, forward.y().signum())
+        };
+        right = forward.cross_product(fallback);
+    }
+    let right = right.normalize();
+
+    (right, right.cross_product(forward))
+}
+
+// The camera's position and axes at one moment.
+struct Frame {
+    origin: Point,
+    right: Vector,
+    up: Vector,
+    forward: Vector,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+    use crate::pixels::rgb::Rgb;
+    use crate::rendering::projection::{FisheyeMapping, Projection};
+    use crate::rendering::sampler::{IndependentSampler, SobolSampler, StratifiedSampler};
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    #[test]
+    fn new_camera_constructor() {
+        let camera = Camera::new(160, 120, FRAC_PI_2);
+
+        assert_eq!(160, camera.width());
+        assert_eq!(120, camera.height());
+        assert_eq!(FRAC_PI_2, camera.field_of_view());
+        assert_eq!(1, camera.samples());
+    }
+
+    #[test]
+    fn pixel_size_horizontal_canvas() {
+        assert_approx_eq!(0.01, Camera::new(200, 125, FRAC_PI_2).pixel_size());
+    }
+
+    #[test]
+    fn pixel_size_vertical_canvas() {
+        assert_approx_eq!(0.01, Camera::new(125, 200, FRAC_PI_2).pixel_size());
+    }
+
+    #[test]
+    fn ray_through_centre() {
+        let camera = Camera::new(201, 101, FRAC_PI_2);
+        let ray = camera.ray_for_pixel(100, 50).unwrap();
+
+        assert_eq!(Point::new(0.0, 0.0, 0.0), ray.origin());
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), ray.direction());
+    }
+
+    #[test]
+    fn ray_through_corner() {
+        let camera = Camera::new(201, 101, FRAC_PI_2);
+        let ray = camera.ray_for_pixel(0, 0).unwrap();
+
+        assert_approx_eq!(
+            Vector::new(-0.66519, 0.33259, -0.66851),
+            ray.direction(),
+            Margin::Absolute(1e-5)
+        );
+    }
+
+    #[test]
+    fn look_at_moves_camera() {
+        let mut camera = Camera::new(201, 101, FRAC_PI_2);
+        camera.look_at(
+            Point::new(0.0, 2.0, -5.0),
+            Point::new(0.0, 2.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+
+        let centre = camera.ray_for_pixel(100, 50).unwrap();
+        let corner = camera.ray_for_position(0.0, 0.0).unwrap();
+
+        assert_eq!(Point::new(0.0, 2.0, -5.0), centre.origin());
+        assert_approx_eq!(Vector::new(0.0, 0.0, 1.0), centre.direction());
+        // Looking down positive z, the left of the image is positive x.
+        assert!(corner.direction().x() > 0.0 && corner.direction().y() > 0.0);
+    }
+
+    #[test]
+    fn look_at_rotated() {
+        let mut camera = Camera::new(100, 100, FRAC_PI_4);
+        camera.look_at(
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, -3.0),
+            Vector::new(1.0, 1.0, 0.0),
+        );
+
+        let ray = camera.ray_for_position(50.0, 50.0).unwrap();
+
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), ray.direction());
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), camera.forward());
+    }
+
+    #[test]
+    fn image_shift_slides_view() {
+        let mut camera = Camera::new(100, 100, FRAC_PI_2);
+        camera.set_image_shift(0.5, -0.25);
+
+        let centre = camera.ray_for_position(50.0, 50.0).unwrap();
+        let corner = camera.ray_for_position(100.0, 0.0).unwrap();
+
+        assert_approx_eq!(
+            Vector::new(0.5, -0.25, -1.0).normalize(),
+            centre.direction()
+        );
+        assert_approx_eq!(Vector::new(1.5, 0.75, -1.0).normalize(), corner.direction());
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), camera.forward());
+    }
+
+    #[test]
+    fn single_sample_through_centre() {
+        let camera = Camera::new(4, 4, FRAC_PI_2);
+
+        assert_eq!(vec![(0.5, 0.5)], camera.sample_offsets(2, 3));
+    }
+
+    #[test]
+    fn sample_offsets_are_stratified() {
+        let mut camera = Camera::new(4, 4, FRAC_PI_2);
+        camera.set_samples(16);
+
+        let offsets = camera.sample_offsets(1, 2);
+        let mut cells: Vec<usize> = offsets
+            .iter()
+            .map(|(x, y)| (y * 4.0) as usize * 4 + (x * 4.0) as usize)
+            .collect();
+        cells.sort();
+
+        assert_eq!(16, offsets.len());
+        assert_eq!((0..16).collect::<Vec<_>>(), cells);
+    }
+
+    #[test]
+    fn sample_offsets_are_deterministic() {
+        let mut camera = Camera::new(4, 4, FRAC_PI_2);
+        camera.set_samples(5);
+
+        let offsets = camera.sample_offsets(1, 1);
+
+        assert_eq!(5, offsets.len());
+        assert!(offsets
+            .iter()
+            .all(|(x, y)| (0.0..1.0).contains(x) && (0.0..1.0).contains(y)));
+        assert_eq!(offsets, camera.sample_offsets(1, 1));
+        assert_ne!(offsets, camera.sample_offsets(2, 1));
+
+        camera.set_seed(1);
+        assert_ne!(offsets, camera.sample_offsets(1, 1));
+    }
+
+    #[test]
+    fn render_antialiases_edges() {
+        // The left half of the view is white, the right half black, with the
+        // edge running through the middle of the centre column.
+        let radiance = |ray: &Ray| {
+            if ray.direction().x() < 0.0 {
+                Some(Color::new(1.0, 1.0, 1.0))
+            } else {
+                Some(Color::default())
+            }
+        };
+
+        let mut camera = Camera::new(3, 3, FRAC_PI_2);
+        let aliased = camera.render(radiance);
+        camera.set_samples(64);
+        let antialiased = camera.render(radiance);
+
+        assert_eq!(1.0, aliased.get_pixel(0, 1).r());
+        assert!(aliased.get_pixel(1, 1).r() == 0.0 || aliased.get_pixel(1, 1).r() == 1.0);
+        assert_eq!(1.0, antialiased.get_pixel(0, 1).r());
+        assert_eq!(0.0, antialiased.get_pixel(2, 1).r());
+        assert_approx_eq!(0.5, antialiased.get_pixel(1, 1).r(), Margin::Absolute(0.05));
+        assert_eq!(1.0, antialiased.get_alpha(1, 1));
+    }
+
+    #[test]
+    fn render_with_sampler() {
+        let radiance = |ray: &Ray, _: &mut StratifiedSampler| {
+            if ray.direction().x() < 0.0 {
+                Some(Color::new(1.0, 1.0, 1.0))
+            } else {
+                Some(Color::default())
+            }
+        };
+
+        let camera = Camera::new(3, 3, FRAC_PI_2);
+        let canvas = camera.render_with(&mut StratifiedSampler::new(8, 8, 0), radiance);
+
+        assert_eq!(1.0, canvas.get_pixel(0, 1).r());
+        assert_eq!(0.0, canvas.get_pixel(2, 1).r());
+        assert_approx_eq!(0.5, canvas.get_pixel(1, 1).r(), Margin::Absolute(0.02));
+    }
+
+    #[test]
+    fn render_with_draws_more_dimensions() {
+        // Estimates the mean of x squared over [0, 1) in every pixel.
+        let camera = Camera::new(2, 2, FRAC_PI_2);
+        let canvas = camera.render_with(&mut SobolSampler::new(256, 3), |_, sampler| {
+            let value = sampler.get_1d().powi(2);
+            Some(Color::new(value, value, value))
+        });
+
+        for (_, _, color) in canvas.enumerate_pixels() {
+            assert_approx_eq!(1.0 / 3.0, color.r(), Margin::Absolute(1e-3));
+        }
+    }
+
+    #[test]
+    fn render_to_film_filters_samples() {
+        // A single bright pixel in the middle of the view.
+        let camera = Camera::new(5, 5, FRAC_PI_2);
+        let pixel = camera.pixel_size();
+        let radiance = |ray: &Ray, _: &mut IndependentSampler| {
+            let direction = ray.direction();
+            let (x, y) = (
+                direction.x() / -direction.z(),
+                direction.y() / -direction.z(),
+            );
+            if x.abs() < pixel / 2.0 && y.abs() < pixel / 2.0 {
+                Some(Color::new(1.0, 1.0, 1.0))
+            } else {
+                Some(Color::default())
+            }
+        };
+
+        let mut film = Film::new(5, 5, ReconstructionFilter::Tent { radius: 1.5 });
+        camera.render_to_film(&mut film, &mut IndependentSampler::new(64, 0), radiance);
+        let canvas = film.resolve();
+
+        assert!(canvas.get_pixel(1, 2).r() > 0.0);
+        assert!(canvas.get_pixel(2, 2).r() > canvas.get_pixel(1, 2).r());
+        assert_eq!(0.0, canvas.get_pixel(0, 2).r());
+    }
+
+    // Renders a half plane at depth `depth`, white left of x = 0, and counts
+    // the pixels of the middle row between black and white.
+    fn blurred_pixels(camera: &Camera, depth: f64) -> usize {
+        let canvas = camera.render(|ray| {
+            let distance = (-depth - ray.origin().z()) / ray.direction().z();
+            if ray.position(distance).x() < 0.0 {
+                Some(Color::new(1.0, 1.0, 1.0))
+            } else {
+                Some(Color::default())
+            }
+        });
+
+        (0..camera.width())
+            .filter(|x| {
+                let value = canvas.get_pixel(*x, camera.height() / 2).r();
+                value > 0.01 && value < 0.99
+            })
+            .count()
+    }
+
+    #[test]
+    fn thin_lens_rays_meet_at_focus() {
+        let mut camera = Camera::new(100, 100, FRAC_PI_2);
+        camera.set_aperture_radius(0.5);
+        camera.set_focal_distance(4.0);
+
+        let centre = camera.ray_for_position(20.0, 70.0).unwrap();
+        let focus = centre.position(4.0 / -centre.direction().z());
+
+        for lens in [(0.1, 0.2), (0.9, 0.5), (0.5, 0.99)] {
+            let ray = camera.ray_through_lens(20.0, 70.0, lens).unwrap();
+            let distance = (focus.z() - ray.origin().z()) / ray.direction().z();
+
+            assert_approx_eq!(0.0, ray.origin().z());
+            assert!(ray.origin().x().hypot(ray.origin().y()) <= 0.5 + 1e-12);
+            assert_approx_eq!(focus, ray.position(distance), Margin::Absolute(1e-9));
+        }
+    }
+
+    #[test]
+    fn pinhole_ignores_lens_sample() {
+        let camera = Camera::new(10, 10, FRAC_PI_2);
+
+        assert_eq!(
+            camera.ray_for_position(3.0, 4.0).unwrap(),
+            camera.ray_through_lens(3.0, 4.0, (0.1, 0.9)).unwrap()
+        );
+    }
+
+    #[test]
+    fn depth_of_field_blurs_out_of_focus() {
+        let mut camera = Camera::new(21, 3, FRAC_PI_2);
+        camera.set_samples(64);
+        let pinhole_focus = blurred_pixels(&camera, 2.0);
+        let pinhole_far = blurred_pixels(&camera, 8.0);
+
+        camera.set_aperture_radius(1.0);
+        camera.set_focal_distance(2.0);
+
+        assert_eq!(pinhole_focus, blurred_pixels(&camera, 2.0));
+        assert_eq!(pinhole_far, 1);
+        assert!(blurred_pixels(&camera, 8.0) > 3);
+    }
+
+    #[test]
+    fn polygonal_aperture() {
+        let mut camera = Camera::new(21, 3, FRAC_PI_2);
+        camera.set_samples(64);
+        camera.set_aperture_radius(1.0);
+        camera.set_focal_distance(2.0);
+        camera.set_aperture(Aperture::Polygon {
+            blades: 5,
+            rotation: 0.0,
+        });
+
+        assert!(blurred_pixels(&camera, 8.0) > 3);
+        assert_eq!(
+            &Aperture::Polygon {
+                blades: 5,
+                rotation: 0.0
+            },
+            camera.aperture()
+        );
+    }
+
+    #[test]
+    fn render_to_film_samples_lens() {
+        let mut camera = Camera::new(21, 3, FRAC_PI_2);
+        camera.set_aperture_radius(1.0);
+        camera.set_focal_distance(2.0);
+        let radiance = |ray: &Ray, _: &mut StratifiedSampler| {
+            let distance = (-8.0 - ray.origin().z()) / ray.direction().z();
+            let value = if ray.position(distance).x() < 0.0 {
+                1.0
+            } else {
+                0.0
+            };
+            Some(Color::new(value, value, value))
+        };
+
+        let canvas = camera.render_with(&mut StratifiedSampler::new(8, 8, 0), radiance);
+        let blurred = (0..21)
+            .filter(|x| {
+                let value = canvas.get_pixel(*x, 1).r();
+                value > 0.01 && value < 0.99
+            })
+            .count();
+
+        assert!(blurred > 3);
+    }
+
+    #[test]
+    fn orthographic_camera() {
+        let mut camera = Camera::new(100, 50, FRAC_PI_2);
+        camera.set_projection(Projection::Orthographic { size: 20.0 });
+        camera.look_at(
+            Point::new(0.0, 0.0, 5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        camera.set_aperture_radius(1.0);
+
+        let corner = camera.ray_for_position(0.0, 0.0).unwrap();
+        let lens = camera.ray_through_lens(0.0, 0.0, (0.9, 0.1)).unwrap();
+
+        assert_approx_eq!(Point::new(-10.0, 5.0, 5.0), corner.origin());
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), corner.direction());
+        // Only the perspective projection models a lens.
+        assert_eq!(corner, lens);
+    }
+
+    #[test]
+    fn fisheye_camera_leaves_corners_transparent() {
+        let mut camera = Camera::new(9, 9, std::f64::consts::PI);
+        camera.set_projection(Projection::Fisheye(FisheyeMapping::Equisolid));
+        let white = |_: &Ray| Some(Color::new(1.0, 1.0, 1.0));
+
+        let canvas = camera.render(white);
+        let filmed = camera.render_with(&mut IndependentSampler::new(16, 0), |ray, _| white(ray));
+
+        assert_eq!(None, camera.ray_for_pixel(0, 0));
+        for canvas in [canvas, filmed] {
+            assert_eq!(0.0, canvas.get_pixel(0, 0).r());
+            assert_eq!(0.0, canvas.get_alpha(0, 0));
+            assert_eq!(1.0, canvas.get_pixel(4, 4).r());
+            assert_eq!(1.0, canvas.get_alpha(4, 4));
+            // The edge of the circle crosses the leftmost pixel.
+            assert!(canvas.get_alpha(0, 4) > 0.5);
+        }
+    }
+
+    #[test]
+    fn render_misses_are_transparent() {
+        // Only the left half of the view hits anything.
+        let radiance = |ray: &Ray| (ray.direction().x() < 0.0).then(|| Color::new(0.5, 0.5, 0.5));
+        let mut camera = Camera::new(3, 3, FRAC_PI_2);
+        camera.set_samples(64);
+
+        let canvas = camera.render(radiance);
+        let filmed =
+            camera.render_with(&mut StratifiedSampler::new(8, 8, 0), |ray, _| radiance(ray));
+
+        for canvas in [canvas, filmed] {
+            assert_eq!(1.0, canvas.get_alpha(0, 1));
+            assert_eq!(0.5, canvas.get_pixel(0, 1).r());
+            assert_eq!(0.0, canvas.get_alpha(2, 1));
+            let alpha = canvas.get_alpha(1, 1);
+            assert_approx_eq!(0.5, alpha, Margin::Absolute(0.05));
+            // Colours are premultiplied by the coverage.
+            assert_approx_eq!(0.5 * alpha, canvas.get_pixel(1, 1).r());
+        }
+    }
+
+    #[test]
+    fn look_at_with_up_along_view() {
+        let mut camera = Camera::new(10, 10, FRAC_PI_2);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        camera.look_at(Point::new(0.0, 5.0, 0.0), Point::default(), up);
+        assert_approx_eq!(Vector::new(0.0, -1.0, 0.0), camera.forward());
+        assert_approx_eq!(Vector::new(1.0, 0.0, 0.0), camera.right());
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), camera.up());
+
+        camera.look_at(
+            Point::default(),
+            Point::new(0.0, 0.0, -3.0),
+            Vector::default(),
+        );
+        assert_approx_eq!(Vector::new(1.0, 0.0, 0.0), camera.right());
+        assert_approx_eq!(Vector::new(0.0, 1.0, 0.0), camera.up());
+
+        camera.add_keyframe(0.0, Point::default(), Point::new(0.0, 2.0, 0.0), up);
+        let ray = camera.ray_for_pixel(5, 5).unwrap();
+        assert!(ray.direction().x().is_finite());
+        assert_approx_eq!(
+            Vector::new(0.0, 1.0, 0.0),
+            ray.direction(),
+            Margin::Absolute(0.2)
+        );
+    }
+
+    #[test]
+    fn look_at_own_position_keeps_direction() {
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let mut camera = Camera::new(10, 10, FRAC_PI_2);
+        camera.look_at(Point::default(), Point::new(1.0, 0.0, 0.0), up);
+
+        let eye = Point::new(0.0, 0.0, 3.0);
+        camera.look_at(eye, eye, up);
+        assert_eq!(eye, camera.origin());
+        assert_approx_eq!(Vector::new(1.0, 0.0, 0.0), camera.forward());
+
+        camera.add_keyframe(0.0, eye, eye, up);
+        camera.add_keyframe(1.0, Point::default(), Point::new(0.0, 0.0, -1.0), up);
+        camera.add_keyframe(2.0, eye, eye, up);
+        let ray = |time| camera.ray_at_time(5.0, 5.0, (0.5, 0.5), time).unwrap();
+
+        assert_approx_eq!(Vector::new(1.0, 0.0, 0.0), ray(0.0).direction());
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), ray(2.0).direction());
+        assert_approx_eq!(eye, ray(2.0).origin());
+    }
+
+    #[test]
+    fn equirectangular_camera_follows_orientation() {
+        let mut camera = Camera::new(360, 180, FRAC_PI_2);
+        camera.set_projection(Projection::Equirectangular);
+        camera.look_at(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+
+        let centre = camera.ray_for_position(180.0, 90.0).unwrap();
+        let right = camera.ray_for_position(270.0, 90.0).unwrap();
+        let behind = camera.ray_for_position(0.0, 90.0).unwrap();
+
+        assert_approx_eq!(Vector::new(1.0, 0.0, 0.0), centre.direction());
+        assert_approx_eq!(Vector::new(0.0, 0.0, 1.0), right.direction());
+        assert_approx_eq!(Vector::new(-1.0, 0.0, 0.0), behind.direction());
+    }
+
+    #[test]
+    fn keyframes_match_look_at() {
+        let from = Point::new(1.0, 2.0, 3.0);
+        let to = Point::new(-2.0, 0.0, 1.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let mut still = Camera::new(40, 30, FRAC_PI_2);
+        still.look_at(from, to, up);
+        let mut keyed = Camera::new(40, 30, FRAC_PI_2);
+        keyed.add_keyframe(0.0, from, to, up);
+
+        for (x, y) in [(0.5, 0.5), (20.0, 15.0), (39.5, 3.5)] {
+            let expected = still.ray_for_position(x, y).unwrap();
+            let actual = keyed.ray_for_position(x, y).unwrap();
+
+            assert_approx_eq!(expected.origin(), actual.origin());
+            assert_approx_eq!(expected.direction(), actual.direction());
+        }
+    }
+
+    #[test]
+    fn keyframes_move_camera_over_time() {
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let mut camera = Camera::new(10, 10, FRAC_PI_2);
+        camera.add_keyframe(0.0, Point::default(), Point::new(0.0, 0.0, -1.0), up);
+        camera.add_keyframe(
+            1.0,
+            Point::new(2.0, 0.0, 0.0),
+            Point::new(3.0, 0.0, 0.0),
+            up,
+        );
+
+        let ray = camera.ray_at_time(5.0, 5.0, (0.5, 0.5), 0.5).unwrap();
+
+        assert_approx_eq!(Point::new(1.0, 0.0, 0.0), ray.origin());
+        // Halfway through a quarter turn to the right.
+        assert_approx_eq!(
+            Vector::new(FRAC_PI_4.sin(), 0.0, -FRAC_PI_4.cos()),
+            ray.direction()
+        );
+        assert_eq!(0.5, ray.time());
+    }
+
+    #[test]
+    fn keyframed_camera_reports_opening_frame() {
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let mut camera = Camera::new(10, 10, FRAC_PI_2);
+        camera.look_at(Point::new(5.0, 5.0, 5.0), Point::default(), up);
+        camera.add_keyframe(0.0, Point::default(), Point::new(0.0, 0.0, -1.0), up);
+        camera.add_keyframe(1.0, Point::default(), Point::new(1.0, 0.0, 0.0), up);
+        camera.set_shutter(1.0, 2.0);
+        camera.set_eye_offset(Vector::new(0.0, 0.5, -2.0));
+
+        assert_approx_eq!(Point::new(-2.0, 0.5, 0.0), camera.origin());
+        assert_approx_eq!(Vector::new(1.0, 0.0, 0.0), camera.forward());
+        assert_approx_eq!(Vector::new(0.0, 0.0, 1.0), camera.right());
+        assert_approx_eq!(up, camera.up());
+        assert_approx_eq!(
+            camera.origin(),
+            camera.ray_for_pixel(3, 3).unwrap().origin()
+        );
+    }
+
+    #[test]
+    fn shutter_spreads_ray_times() {
+        let mut camera = Camera::new(1, 1, FRAC_PI_2);
+        camera.set_shutter(0.25, 0.75);
+        let mut sampler = IndependentSampler::new(64, 3);
+
+        let times: Vec<f64> = (0..64)
+            .map(|index| {
+                sampler.start_pixel_sample(0, 0, index);
+                camera.sample_ray(0.5, 0.5, &mut sampler).unwrap().time()
+            })
+            .collect();
+
+        assert!(times.iter().all(|time| (0.25..0.75).contains(time)));
+        assert!(times.iter().any(|time| *time < 0.35));
+        assert!(times.iter().any(|time| *time > 0.65));
+        assert_eq!(0.25, camera.ray_for_pixel(0, 0).unwrap().time());
+    }
+
+    #[test]
+    fn moving_camera_blurs() {
+        // A wall whose right half is lit, seen by a camera sliding across
+        // the boundary.
+        let radiance = |ray: &Ray| {
+            let value = if ray.origin().x() > 0.5 { 1.0 } else { 0.0 };
+            Some(Color::new(value, value, value))
+        };
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let mut camera = Camera::new(1, 1, FRAC_PI_2);
+        camera.set_samples(256);
+        camera.add_keyframe(0.0, Point::default(), Point::new(0.0, 0.0, -1.0), up);
+        camera.add_keyframe(
+            1.0,
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, -1.0),
+            up,
+        );
+
+        let strobed = camera.render(radiance);
+        camera.set_shutter(0.0, 1.0);
+        let blurred = camera.render(radiance);
+
+        assert_eq!(0.0, strobed.get_pixel(0, 0).r());
+        assert_approx_eq!(0.5, blurred.get_pixel(0, 0).r(), Margin::Absolute(0.1));
+    }
+}
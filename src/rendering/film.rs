@@ -0,0 +1,360 @@
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::pixels::color_space::ColorSpace;
+
+// How much a sample contributes to the pixels around it, by its offset from
+// their centres in pixels. Every filter is zero beyond its radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReconstructionFilter {
+    Box { radius: f64 },
+    Tent { radius: f64 },
+    Gaussian { radius: f64, sigma: f64 },
+    // The cubic family of Mitchell and Netravali, who recommend b = c = 1/3.
+    Mitchell { radius: f64, b: f64, c: f64 },
+    BlackmanHarris { radius: f64 },
+    // A sinc windowed by a wider sinc, with `radius` lobes.
+    Lanczos { radius: f64 },
+}
+
+impl Default for ReconstructionFilter {
+    // Averages the samples within each pixel.
+    fn default() -> Self {
+        ReconstructionFilter::Box { radius: 0.5 }
+    }
+}
+
+impl ReconstructionFilter {
+    pub fn radius(&self) -> f64 {
+        match self {
+            ReconstructionFilter::Box { radius }
+            | ReconstructionFilter::Tent { radius }
+            | ReconstructionFilter::Gaussian { radius, .. }
+            | ReconstructionFilter::Mitchell { radius, .. }
+            | ReconstructionFilter::BlackmanHarris { radius }
+            | ReconstructionFilter::Lanczos { radius } => *radius,
+        }
+    }
+
+    pub fn evaluate(&self, dx: f64, dy: f64) -> f64 {
+        self.evaluate_1d(dx) * self.evaluate_1d(dy)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let radius = self.radius();
+        let x = x.abs();
+        if x > radius {
+            return 0.0;
+        }
+
+        match *self {
+            ReconstructionFilter::Box { .. } => 1.0,
+            ReconstructionFilter::Tent { .. } => radius - x,
+            ReconstructionFilter::Gaussian { sigma, .. } => {
+                let gaussian = |x: f64| (-x * x / (2.0 * sigma * sigma)).exp();
+                (gaussian(x) - gaussian(radius)).max(0.0)
+            }
+            ReconstructionFilter::Mitchell { b, c, .. } => {
+                // The cubic is defined over [-2, 2].
+                let x = 2.0 * x / radius;
+                if x < 1.0 {
+                    ((12.0 - 9.0 * b - 6.0 * c) * x.powi(3)
+                        + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+                        + (6.0 - 2.0 * b))
+                        / 6.0
+                } else {
+                    ((-b - 6.0 * c) * x.powi(3)
+                        + (6.0 * b + 30.0 * c) * x.powi(2)
+                        + (-12.0 * b - 48.0 * c) * x
+                        + (8.0 * b + 24.0 * c))
+                        / 6.0
+                }
+            }
+            ReconstructionFilter::BlackmanHarris { .. } => {
+                let phase = std::f64::consts::PI * (x + radius) / radius;
+                0.35875 - 0.48829 * phase.cos() + 0.14128 * (2.0 * phase).cos()
+                    - 0.01168 * (3.0 * phase).cos()
+            }
+            ReconstructionFilter::Lanczos { .. } => sinc(x) * sinc(x / radius),
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x < 1e-8 {
+        return 1.0;
+    }
+    let x = std::f64::consts::PI * x;
+    x.sin() / x
+}
+
+// Accumulates samples at arbitrary positions on the image as filter weighted
+// sums, so each sample splats into every pixel its filter reaches. Samples
+// are taken to be in the film's colour space, Rec.709 unless set otherwise.
+#[derive(Clone, Debug)]
+pub struct Film {
+    width: usize,
+    height: usize,
+    filter: ReconstructionFilter,
+    color_space: ColorSpace,
+    sums: Vec<Color>,
+    coverage: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize, filter: ReconstructionFilter) -> Film {
+        Film {
+            width,
+            height,
+            filter,
+            color_space: ColorSpace::default(),
+            sums: vec![Color::default(); width * height],
+            coverage: vec![0.0; width * height],
+            weights: vec![0.0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn filter(&self) -> ReconstructionFilter {
+        self.filter
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+    }
+
+    pub fn weight(&self, x: usize, y: usize) -> f64 {
+        self.weights[y * self.width + x]
+    }
+
+    // Adds a sample at a position in pixels from the top left corner of the
+    // image, where (0.5, 0.5) is the centre of the first pixel. A sample
+    // without a colour missed the scene: it weighs on the pixels like any
+    // other but leaves them transparent.
+    pub fn add_sample(&mut self, x: f64, y: f64, color: Option<Color>) {
+        let (color, alpha) = match color {
+            Some(color) => (color, 1.0),
+            None => (Color::default(), 0.0),
+        };
+        let radius = self.filter.radius();
+        // Pixels whose centres lie in (position - radius, position + radius],
+        // so a box filter puts samples on a boundary into the later pixel.
+        let range = |position: f64, size: usize| {
+            let start = ((position - radius - 0.5).floor() + 1.0).max(0.0) as usize;
+            let end = ((position + radius - 0.5).floor() + 1.0).max(0.0) as usize;
+            start..end.min(size)
+        };
+
+        for py in range(y, self.height) {
+            for px in range(x, self.width) {
+                let weight = self
+                    .filter
+                    .evaluate(px as f64 + 0.5 - x, py as f64 + 0.5 - y);
+                if weight == 0.0 {
+                    continue;
+                }
+
+                let index = py * self.width + px;
+                self.sums[index] = self.sums[index] + color * weight;
+                self.coverage[index] += alpha * weight;
+                self.weights[index] += weight;
+            }
+        }
+    }
+
+    // The weighted average of the samples of each pixel, with the share
+    // that hit something as its alpha. Pixels no sample reached stay
+    // transparent.
+    pub fn resolve(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+        canvas.set_color_space(self.color_space);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = y * self.width + x;
+                let weight = self.weights[index];
+                if weight.abs() > 1e-12 {
+                    canvas.set_pixel_with_alpha(
+                        x,
+                        y,
+                        self.sums[index] * (1.0 / weight),
+                        self.coverage[index] / weight,
+                    );
+                }
+            }
+        }
+
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+    use crate::pixels::rgb::Rgb;
+
+    fn filters() -> [ReconstructionFilter; 6] {
+        [
+            ReconstructionFilter::Box { radius: 0.5 },
+            ReconstructionFilter::Tent { radius: 1.0 },
+            ReconstructionFilter::Gaussian {
+                radius: 1.5,
+                sigma: 0.5,
+            },
+            ReconstructionFilter::Mitchell {
+                radius: 2.0,
+                b: 1.0 / 3.0,
+                c: 1.0 / 3.0,
+            },
+            ReconstructionFilter::BlackmanHarris { radius: 1.5 },
+            ReconstructionFilter::Lanczos { radius: 3.0 },
+        ]
+    }
+
+    #[test]
+    fn filters_vanish_beyond_radius() {
+        for filter in filters() {
+            let radius = filter.radius();
+
+            assert!(filter.evaluate(0.0, 0.0) > 0.0);
+            assert_eq!(0.0, filter.evaluate(radius + 0.01, 0.0));
+            assert_eq!(0.0, filter.evaluate(0.0, -radius - 0.01));
+            assert_approx_eq!(
+                filter.evaluate(0.3, -0.2),
+                filter.evaluate(-0.3, 0.2),
+                Margin::Absolute(1e-12)
+            );
+        }
+    }
+
+    #[test]
+    fn filters_are_continuous_at_radius() {
+        for filter in filters().into_iter().skip(1) {
+            let radius = filter.radius();
+
+            assert_approx_eq!(
+                0.0,
+                filter.evaluate(radius - 1e-9, 0.0),
+                Margin::Absolute(1e-4)
+            );
+        }
+    }
+
+    #[test]
+    fn mitchell_values() {
+        let filter = ReconstructionFilter::Mitchell {
+            radius: 2.0,
+            b: 1.0 / 3.0,
+            c: 1.0 / 3.0,
+        };
+
+        assert_approx_eq!(8.0 / 9.0, filter.evaluate_1d(0.0));
+        assert_approx_eq!(1.0 / 18.0, filter.evaluate_1d(1.0));
+        // The negative lobe that sharpens edges.
+        assert!(filter.evaluate_1d(1.5) < 0.0);
+    }
+
+    #[test]
+    fn lanczos_values() {
+        let filter = ReconstructionFilter::Lanczos { radius: 3.0 };
+
+        assert_eq!(1.0, filter.evaluate_1d(0.0));
+        assert_approx_eq!(0.0, filter.evaluate_1d(1.0), Margin::Absolute(1e-12));
+        assert!(filter.evaluate_1d(1.5) < 0.0);
+    }
+
+    #[test]
+    fn box_film_averages_pixel() {
+        let mut film = Film::new(2, 1, ReconstructionFilter::default());
+        film.add_sample(0.25, 0.5, Some(Color::new(1.0, 0.0, 0.0)));
+        film.add_sample(0.75, 0.5, Some(Color::new(0.0, 0.0, 1.0)));
+        film.add_sample(1.0, 0.5, Some(Color::new(0.0, 1.0, 0.0)));
+
+        let canvas = film.resolve();
+
+        assert_eq!(Color::new(0.5, 0.0, 0.5), canvas.get_pixel(0, 0));
+        assert_eq!(Color::new(0.0, 1.0, 0.0), canvas.get_pixel(1, 0));
+        assert_eq!(2.0, film.weight(0, 0));
+    }
+
+    #[test]
+    fn wide_filters_splat_into_neighbours() {
+        let mut film = Film::new(3, 3, ReconstructionFilter::Tent { radius: 1.5 });
+        film.add_sample(1.5, 1.5, Some(Color::new(1.0, 1.0, 1.0)));
+
+        assert_eq!(2.25, film.weight(1, 1));
+        assert_eq!(0.75, film.weight(0, 1));
+        assert_eq!(0.25, film.weight(2, 2));
+    }
+
+    #[test]
+    fn samples_outside_film_reach_edges() {
+        let mut film = Film::new(2, 2, ReconstructionFilter::Tent { radius: 1.0 });
+        film.add_sample(-0.25, 0.5, Some(Color::new(1.0, 1.0, 1.0)));
+
+        assert_eq!(0.25, film.weight(0, 0));
+        assert_eq!(0.0, film.weight(1, 0));
+    }
+
+    #[test]
+    fn resolve_skips_empty_pixels() {
+        let mut film = Film::new(2, 1, ReconstructionFilter::default());
+        film.add_sample(0.5, 0.5, Some(Color::new(0.2, 0.4, 0.6)));
+
+        let canvas = film.resolve();
+
+        assert_eq!(1.0, canvas.get_alpha(0, 0));
+        assert_eq!(0.0, canvas.get_alpha(1, 0));
+    }
+
+    #[test]
+    fn constant_image_survives_every_filter() {
+        for filter in filters() {
+            let mut film = Film::new(4, 4, filter);
+            for i in 0..64 {
+                let (x, y) = ((i % 8) as f64 * 0.5 + 0.25, (i / 8) as f64 * 0.5 + 0.25);
+                film.add_sample(x, y, Some(Color::new(0.3, 0.6, 0.9)));
+            }
+
+            for (_, _, color) in film.resolve().enumerate_pixels() {
+                assert_approx_eq!(Color::new(0.3, 0.6, 0.9), *color, Margin::Absolute(1e-9));
+            }
+        }
+    }
+
+    #[test]
+    fn wide_filter_blurs_edges() {
+        let render = |filter| {
+            let mut film = Film::new(4, 1, filter);
+            for i in 0..16 {
+                let x = i as f64 * 0.25 + 0.125;
+                let value = if x < 2.0 { 1.0 } else { 0.0 };
+                film.add_sample(x, 0.5, Some(Color::new(value, value, value)));
+            }
+            film.resolve()
+        };
+
+        let sharp = render(ReconstructionFilter::default());
+        let smooth = render(ReconstructionFilter::Gaussian {
+            radius: 2.0,
+            sigma: 0.7,
+        });
+
+        assert_eq!(1.0, sharp.get_pixel(1, 0).r());
+        assert!(smooth.get_pixel(1, 0).r() < 1.0);
+        assert!(smooth.get_pixel(2, 0).r() > 0.0);
+    }
+}
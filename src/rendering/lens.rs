@@ -0,0 +1,266 @@
+use std::f64::consts::{FRAC_PI_4, PI};
+
+use crate::pixels::canvas::Canvas;
+
+// The shape of the lens opening, which out of focus highlights take on.
+// Samples are positions within [-1, 1] on both axes, scaled by the aperture
+// radius of the camera.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Aperture {
+    #[default]
+    Circle,
+    // A regular polygon formed by `blades` iris blades, with a vertex at
+    // `rotation` radians anticlockwise from the right.
+    Polygon {
+        blades: usize,
+        rotation: f64,
+    },
+    Image(ApertureImage),
+}
+
+impl Aperture {
+    // Maps a uniform sample in [0, 1) squared to a uniformly distributed
+    // point of the aperture.
+    pub fn sample(&self, u: f64, v: f64) -> (f64, f64) {
+        match self {
+            Aperture::Circle => concentric_disk(u, v),
+            Aperture::Polygon { blades, rotation } => polygon(*blades, *rotation, u, v),
+            Aperture::Image(image) => image.sample(u, v),
+        }
+    }
+}
+
+// Shirley and Chiu's mapping from the square to the disk, which keeps
+// stratified samples well spread.
+fn concentric_disk(u: f64, v: f64) -> (f64, f64) {
+    let (x, y) = (2.0 * u - 1.0, 2.0 * v - 1.0);
+    if x == 0.0 && y == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let (radius, angle) = if x.abs() > y.abs() {
+        (x, FRAC_PI_4 * (y / x))
+    } else {
+        (y, 2.0 * FRAC_PI_4 - FRAC_PI_4 * (x / y))
+    };
+
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+// Picks one of the triangles fanning out from the centre, then a uniform
+// point within it.
+fn polygon(blades: usize, rotation: f64, u: f64, v: f64) -> (f64, f64) {
+    let blades = blades.max(3);
+    let scaled = u * blades as f64;
+    let blade = (scaled as usize).min(blades - 1);
+    let u = scaled - blade as f64;
+
+    let vertex = |index: usize| {
+        let angle = rotation + 2.0 * PI * index as f64 / blades as f64;
+        (angle.cos(), angle.sin())
+    };
+    let (first, second) = (vertex(blade), vertex(blade + 1));
+
+    // Uniform barycentric coordinates with the centre as the third vertex.
+    let distance = u.sqrt();
+    let (a, b) = (distance * (1.0 - v), distance * v);
+    (a * first.0 + b * second.0, a * first.1 + b * second.1)
+}
+
+// An aperture drawn as an image, letting light through in proportion to the
+// luminance of each pixel. The image covers the square around the lens.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApertureImage {
+    width: usize,
+    height: usize,
+    // Cumulative distribution of the rows, then of the pixels within each.
+    rows: Vec<f64>,
+    columns: Vec<Vec<f64>>,
+}
+
+impl ApertureImage {
+    // Returns `None` when no pixel lets light through.
+    pub fn new(canvas: &Canvas) -> Option<ApertureImage> {
+        let (width, height) = (canvas.width(), canvas.height());
+        let mut columns = Vec::with_capacity(height);
+        let mut row_weights = Vec::with_capacity(height);
+
+        let space = canvas.color_space();
+        for (_, row) in canvas.rows() {
+            let weights: Vec<f64> = row
+                .iter()
+                .map(|color| space.luminance(*color).max(0.0))
+                .collect();
+            row_weights.push(weights.iter().sum());
+            columns.push(cumulative(&weights));
+        }
+
+        let rows = cumulative(&row_weights);
+        if rows.last().is_none_or(|total| *total <= 0.0) {
+            return None;
+        }
+
+        Some(ApertureImage {
+            width,
+            height,
+            rows,
+            columns,
+        })
+    }
+
+    pub fn sample(&self, u: f64, v: f64) -> (f64, f64) {
+        let (row, v) = invert(&self.rows, v);
+        let (column, u) = invert(&self.columns[row], u);
+
+        let x = (column as f64 + u) / self.width as f64;
+        let y = (row as f64 + v) / self.height as f64;
+        // Image rows grow downwards, lens coordinates upwards.
+        (2.0 * x - 1.0, 1.0 - 2.0 * y)
+    }
+}
+
+// Running totals normalised to end at one, or all zero for zero weights.
+fn cumulative(weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    let mut sum = 0.0;
+
+    weights
+        .iter()
+        .map(|weight| {
+            sum += weight;
+            if total > 0.0 {
+                sum / total
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+// The bucket a uniform sample falls in and where within it, reused so the
+// sample stays stratified.
+fn invert(cumulative: &[f64], u: f64) -> (usize, f64) {
+    let index = cumulative
+        .partition_point(|value| *value <= u)
+        .min(cumulative.len() - 1);
+    let start = if index == 0 {
+        0.0
+    } else {
+        cumulative[index - 1]
+    };
+    let width = cumulative[index] - start;
+
+    let offset = if width > 0.0 {
+        (u - start) / width
+    } else {
+        0.5
+    };
+    (index, offset.clamp(0.0, 1.0 - f64::EPSILON))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+    use crate::pixels::color::Color;
+    use crate::rendering::random::Random;
+
+    fn samples(aperture: &Aperture) -> Vec<(f64, f64)> {
+        let mut random = Random::new(5);
+        (0..2000)
+            .map(|_| aperture.sample(random.next_f64(), random.next_f64()))
+            .collect()
+    }
+
+    fn mean(points: &[(f64, f64)]) -> (f64, f64) {
+        let sum = points
+            .iter()
+            .fold((0.0, 0.0), |sum, point| (sum.0 + point.0, sum.1 + point.1));
+        (sum.0 / points.len() as f64, sum.1 / points.len() as f64)
+    }
+
+    #[test]
+    fn circle_samples_fill_disk() {
+        let points = samples(&Aperture::Circle);
+        let (x, y) = mean(&points);
+        let outer = points
+            .iter()
+            .filter(|(x, y)| x.hypot(*y) > 0.5f64.sqrt())
+            .count();
+
+        assert!(points.iter().all(|(x, y)| x.hypot(*y) <= 1.0 + 1e-12));
+        assert_approx_eq!(0.0, x, Margin::Absolute(0.05));
+        assert_approx_eq!(0.0, y, Margin::Absolute(0.05));
+        // Half the area of the disk lies beyond a radius of one over root two.
+        assert_approx_eq!(
+            0.5,
+            outer as f64 / points.len() as f64,
+            Margin::Absolute(0.05)
+        );
+    }
+
+    #[test]
+    fn concentric_disk_edges() {
+        assert_eq!((0.0, 0.0), concentric_disk(0.5, 0.5));
+        assert_approx_eq!(1.0, concentric_disk(1.0, 0.5).0);
+        assert_approx_eq!(-1.0, concentric_disk(0.5, 0.0).1);
+    }
+
+    #[test]
+    fn polygon_samples_stay_inside() {
+        let aperture = Aperture::Polygon {
+            blades: 6,
+            rotation: 0.0,
+        };
+        let points = samples(&aperture);
+        // The inscribed radius of a unit hexagon.
+        let apothem = (PI / 6.0).cos();
+
+        assert!(points.iter().all(|(_, y)| y.abs() <= apothem + 1e-12));
+        assert!(points.iter().any(|(x, y)| x.hypot(*y) > 0.95));
+        assert!(points
+            .iter()
+            .all(|(x, y)| (x * apothem + y * 0.5).abs() <= apothem + 1e-12));
+    }
+
+    #[test]
+    fn polygon_rotation() {
+        let aperture = Aperture::Polygon {
+            blades: 3,
+            rotation: PI / 2.0,
+        };
+        let points = samples(&aperture);
+
+        // A triangle pointing up reaches the top, but not the bottom.
+        assert!(points.iter().any(|(_, y)| *y > 0.9));
+        assert!(points.iter().all(|(_, y)| *y >= -0.5 - 1e-12));
+    }
+
+    #[test]
+    fn image_aperture_follows_pixels() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_pixel(3, 0, Color::new(1.0, 1.0, 1.0));
+        canvas.set_pixel(0, 3, Color::new(0.5, 0.5, 0.5));
+
+        let aperture = Aperture::Image(ApertureImage::new(&canvas).unwrap());
+        let points = samples(&aperture);
+        let top_right = points.iter().filter(|(x, y)| *x > 0.5 && *y > 0.5).count();
+        let bottom_left = points
+            .iter()
+            .filter(|(x, y)| *x < -0.5 && *y < -0.5)
+            .count();
+
+        assert_eq!(points.len(), top_right + bottom_left);
+        assert_approx_eq!(
+            2.0,
+            top_right as f64 / bottom_left as f64,
+            Margin::Absolute(0.2)
+        );
+    }
+
+    #[test]
+    fn image_aperture_needs_light() {
+        assert_eq!(None, ApertureImage::new(&Canvas::new(2, 2)));
+    }
+}
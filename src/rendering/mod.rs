@@ -0,0 +1,11 @@
+pub mod adaptive;
+pub mod bounds;
+pub mod camera;
+pub mod film;
+pub mod lens;
+pub mod motion;
+pub mod projection;
+pub mod random;
+pub mod ray;
+pub mod rig;
+pub mod sampler;
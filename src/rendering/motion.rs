@@ -0,0 +1,522 @@
+use std::f64::consts::PI;
+
+use crate::rendering::bounds::Bounds;
+use crate::rendering::ray::Ray;
+use crate::tuples::coordinates::Coordinates;
+use crate::tuples::point::Point;
+use crate::tuples::scalar::Scalar;
+use crate::tuples::vector::Vector;
+
+// Values that can be blended between two keyframes, with `t` in [0, 1].
+pub trait Interpolate {
+    fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Point {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Interpolate for Vector {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+// A unit quaternion describing a rotation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quaternion {
+    w: f64,
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Default for Quaternion {
+    // No rotation.
+    fn default() -> Self {
+        Quaternion::new(1.0, 0.0, 0.0, 0.0)
+    }
+}
+
+impl Quaternion {
+    pub fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        let length = (w * w + x * x + y * y + z * z).sqrt();
+        Quaternion {
+            w: w / length,
+            x: x / length,
+            y: y / length,
+            z: z / length,
+        }
+    }
+
+    // A rotation of `angle` radians anticlockwise around `axis`, looking
+    // back along it.
+    pub fn from_axis_angle(axis: Vector, angle: f64) -> Self {
+        let axis = axis.normalize();
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Quaternion::new(cos, axis.x() * sin, axis.y() * sin, axis.z() * sin)
+    }
+
+    // The rotation taking the x, y and z axes to an orthonormal, right
+    // handed basis.
+    pub fn from_basis(x: Vector, y: Vector, z: Vector) -> Self {
+        let trace = x.x() + y.y() + z.z();
+
+        if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quaternion::new(
+                s / 4.0,
+                (y.z() - z.y()) / s,
+                (z.x() - x.z()) / s,
+                (x.y() - y.x()) / s,
+            )
+        } else if x.x() > y.y() && x.x() > z.z() {
+            let s = (1.0 + x.x() - y.y() - z.z()).sqrt() * 2.0;
+            Quaternion::new(
+                (y.z() - z.y()) / s,
+                s / 4.0,
+                (y.x() + x.y()) / s,
+                (z.x() + x.z()) / s,
+            )
+        } else if y.y() > z.z() {
+            let s = (1.0 + y.y() - x.x() - z.z()).sqrt() * 2.0;
+            Quaternion::new(
+                (z.x() - x.z()) / s,
+                (y.x() + x.y()) / s,
+                s / 4.0,
+                (z.y() + y.z()) / s,
+            )
+        } else {
+            let s = (1.0 + z.z() - x.x() - y.y()).sqrt() * 2.0;
+            Quaternion::new(
+                (x.y() - y.x()) / s,
+                (z.x() + x.z()) / s,
+                (z.y() + y.z()) / s,
+                s / 4.0,
+            )
+        }
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Quaternion {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
+    pub fn rotate(&self, vector: Vector) -> Vector {
+        let axis = Vector::new(self.x, self.y, self.z);
+        let twice = axis.cross_product(vector) * 2.0;
+        vector + twice * self.w + axis.cross_product(twice)
+    }
+
+    // The smallest angle that turns one rotation into the other.
+    pub fn angle_to(&self, other: &Quaternion) -> f64 {
+        2.0 * self.dot(other).abs().min(1.0).acos()
+    }
+
+    fn dot(&self, other: &Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+}
+
+impl Interpolate for Quaternion {
+    // Spherical linear interpolation along the shorter arc, so the rotation
+    // turns at a constant rate.
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        let mut dot = self.dot(other);
+        let mut other = *other;
+        if dot < 0.0 {
+            dot = -dot;
+            other = Quaternion {
+                w: -other.w,
+                x: -other.x,
+                y: -other.y,
+                z: -other.z,
+            };
+        }
+
+        // Nearly equal rotations divide by almost zero, blend them directly.
+        let (a, b) = if dot > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let angle = dot.acos();
+            let sin = angle.sin();
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+
+        Quaternion::new(
+            a * self.w + b * other.w,
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+        )
+    }
+}
+
+// Places an object by scaling it along its own axes, rotating it about its
+// origin and then moving it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    translation: Vector,
+    rotation: Quaternion,
+    scale: Vector,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::new(
+            Vector::default(),
+            Quaternion::default(),
+            Vector::new(1.0, 1.0, 1.0),
+        )
+    }
+}
+
+impl Transform {
+    pub fn new(translation: Vector, rotation: Quaternion, scale: Vector) -> Self {
+        Transform {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn translation(&self) -> Vector {
+        self.translation
+    }
+
+    pub fn rotation(&self) -> Quaternion {
+        self.rotation
+    }
+
+    pub fn scale(&self) -> Vector {
+        self.scale
+    }
+
+    pub fn apply_point(&self, point: Point) -> Point {
+        let local = point - Point::default();
+        Point::default() + self.apply_vector(local) + self.translation
+    }
+
+    pub fn apply_vector(&self, vector: Vector) -> Vector {
+        self.rotation.rotate(multiply(vector, self.scale))
+    }
+
+    pub fn inverse_point(&self, point: Point) -> Point {
+        Point::default() + self.inverse_vector(point - Point::default() - self.translation)
+    }
+
+    pub fn inverse_vector(&self, vector: Vector) -> Vector {
+        let rotated = self.rotation.conjugate().rotate(vector);
+        Vector::new(
+            rotated.x() / self.scale.x(),
+            rotated.y() / self.scale.y(),
+            rotated.z() / self.scale.z(),
+        )
+    }
+
+    // The ray in the object's own space. The direction is not normalised,
+    // so distances along it still match the ray in world space.
+    pub fn ray_to_local(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            self.inverse_point(ray.origin()),
+            self.inverse_vector(ray.direction()),
+        )
+        .with_time(ray.time())
+    }
+
+    pub fn bounds(&self, local: &Bounds) -> Bounds {
+        Bounds::around(local.corners().map(|corner| self.apply_point(corner))).unwrap()
+    }
+}
+
+impl Interpolate for Transform {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Transform {
+            translation: self.translation.interpolate(&other.translation, t),
+            rotation: self.rotation.interpolate(&other.rotation, t),
+            scale: self.scale.interpolate(&other.scale, t),
+        }
+    }
+}
+
+fn multiply(a: Vector, b: Vector) -> Vector {
+    Vector::new(a.x() * b.x(), a.y() * b.y(), a.z() * b.z())
+}
+
+// Values set at moments in time and interpolated between them. Before the
+// first keyframe and after the last the value holds still.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframes<T> {
+    // Sorted by time, with no two at the same moment.
+    frames: Vec<(f64, T)>,
+}
+
+impl<T: Interpolate + Clone> Keyframes<T> {
+    pub fn new(time: f64, value: T) -> Self {
+        Keyframes {
+            frames: vec![(time, value)],
+        }
+    }
+
+    // Adds a keyframe, replacing any at the same time.
+    pub fn insert(&mut self, time: f64, value: T) {
+        let index = self.frames.partition_point(|(at, _)| *at < time);
+        match self.frames.get_mut(index) {
+            Some(frame) if frame.0 == time => frame.1 = value,
+            _ => self.frames.insert(index, (time, value)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn frames(&self) -> &[(f64, T)] {
+        &self.frames
+    }
+
+    pub fn at(&self, time: f64) -> T {
+        let index = self.frames.partition_point(|(at, _)| *at <= time);
+        if index == 0 {
+            return self.frames[0].1.clone();
+        }
+        if index == self.frames.len() {
+            return self.frames[index - 1].1.clone();
+        }
+
+        let (start, ref from) = self.frames[index - 1];
+        let (end, ref to) = self.frames[index];
+        from.interpolate(to, (time - start) / (end - start))
+    }
+}
+
+impl Keyframes<Transform> {
+    pub fn ray_to_local(&self, ray: &Ray) -> Ray {
+        self.at(ray.time()).ray_to_local(ray)
+    }
+
+    // A box around the object wherever it is over time. Each span between
+    // keyframes is sampled finely enough that no step turns further than
+    // `MAX_STEP`, and the box grows by how far a corner swinging through
+    // one step can bulge beyond its end points.
+    pub fn bounds(&self, local: &Bounds) -> Bounds {
+        const MAX_STEP: f64 = PI / 32.0;
+
+        let reach = local
+            .corners()
+            .iter()
+            .map(|corner| *corner - Point::default())
+            .fold(0.0_f64, |reach, corner| {
+                self.frames.iter().fold(reach, |reach, (_, transform)| {
+                    reach.max(multiply(corner, transform.scale).magnitude())
+                })
+            });
+
+        let mut bounds = self.frames[0].1.bounds(local);
+        for pair in self.frames.windows(2) {
+            let (from, to) = (&pair[0].1, &pair[1].1);
+            let angle = from.rotation.angle_to(&to.rotation);
+            let steps = (angle / MAX_STEP).ceil().max(1.0) as usize;
+            let half_step = angle / steps as f64 / 2.0;
+            let bulge = reach * (1.0 / half_step.cos() - half_step.cos());
+
+            for step in 1..=steps {
+                let transform = from.interpolate(to, step as f64 / steps as f64);
+                bounds = bounds.union(&transform.bounds(local).expand(bulge));
+            }
+        }
+
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn quaternion_rotates_around_axis() {
+        let rotation = Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 1.0), FRAC_PI_2);
+
+        assert_approx_eq!(
+            Vector::new(0.0, 1.0, 0.0),
+            rotation.rotate(Vector::new(1.0, 0.0, 0.0))
+        );
+        assert_approx_eq!(
+            Vector::new(1.0, 0.0, 0.0),
+            rotation.conjugate().rotate(Vector::new(0.0, 1.0, 0.0))
+        );
+        assert_approx_eq!(FRAC_PI_2, Quaternion::default().angle_to(&rotation));
+    }
+
+    #[test]
+    fn quaternion_from_basis() {
+        let bases = [
+            (
+                Vector::new(0.0, 1.0, 0.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ),
+            (
+                Vector::new(-1.0, 0.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+                Vector::new(0.0, 0.0, -1.0),
+            ),
+            (
+                Vector::new(0.0, 0.0, 1.0),
+                Vector::new(1.0, 0.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            (
+                Vector::new(1.0, 0.0, 0.0),
+                Vector::new(0.0, -1.0, 0.0),
+                Vector::new(0.0, 0.0, -1.0),
+            ),
+        ];
+
+        for (x, y, z) in bases {
+            let rotation = Quaternion::from_basis(x, y, z);
+
+            assert_approx_eq!(x, rotation.rotate(Vector::new(1.0, 0.0, 0.0)));
+            assert_approx_eq!(y, rotation.rotate(Vector::new(0.0, 1.0, 0.0)));
+            assert_approx_eq!(z, rotation.rotate(Vector::new(0.0, 0.0, 1.0)));
+        }
+    }
+
+    #[test]
+    fn slerp_turns_at_constant_rate() {
+        let axis = Vector::new(0.0, 1.0, 0.0);
+        let from = Quaternion::from_axis_angle(axis, 0.0);
+        let to = Quaternion::from_axis_angle(axis, 2.0);
+
+        for t in [0.0, 0.25, 0.5, 1.0] {
+            let rotation = from.interpolate(&to, t);
+            assert_approx_eq!(2.0 * t, from.angle_to(&rotation), Margin::Absolute(1e-6));
+        }
+        // Goes the short way round, even when the signs disagree.
+        let negated = Quaternion::new(-to.w, -to.x, -to.y, -to.z);
+        assert_approx_eq!(1.0, from.angle_to(&from.interpolate(&negated, 0.5)));
+    }
+
+    #[test]
+    fn transform_round_trip() {
+        let transform = Transform::new(
+            Vector::new(1.0, 2.0, 3.0),
+            Quaternion::from_axis_angle(Vector::new(1.0, 1.0, 0.0), 0.7),
+            Vector::new(2.0, 0.5, 1.0),
+        );
+        let point = Point::new(-0.3, 0.8, 1.2);
+
+        assert_approx_eq!(point, transform.inverse_point(transform.apply_point(point)));
+        assert_approx_eq!(
+            Point::new(1.0, 2.0, 3.0),
+            transform.apply_point(Point::default())
+        );
+    }
+
+    #[test]
+    fn ray_to_local_keeps_distances() {
+        let transform = Transform::new(
+            Vector::new(0.0, 0.0, 5.0),
+            Quaternion::default(),
+            Vector::new(2.0, 2.0, 2.0),
+        );
+        let ray = Ray::new(Point::default(), Vector::new(0.0, 0.0, 1.0)).with_time(0.5);
+
+        let local = transform.ray_to_local(&ray);
+
+        assert_approx_eq!(Point::new(0.0, 0.0, -2.5), local.origin());
+        assert_approx_eq!(
+            transform.apply_point(local.position(3.0)),
+            ray.position(3.0)
+        );
+        assert_eq!(0.5, local.time());
+    }
+
+    #[test]
+    fn keyframes_interpolate_and_clamp() {
+        let mut keyframes = Keyframes::new(1.0, 10.0);
+        keyframes.insert(0.0, 0.0);
+        keyframes.insert(2.0, 30.0);
+        keyframes.insert(2.0, 20.0);
+
+        assert_eq!(3, keyframes.len());
+        assert_eq!(0.0, keyframes.at(-1.0));
+        assert_eq!(5.0, keyframes.at(0.5));
+        assert_eq!(10.0, keyframes.at(1.0));
+        assert_eq!(15.0, keyframes.at(1.5));
+        assert_eq!(20.0, keyframes.at(3.0));
+    }
+
+    #[test]
+    fn moving_ray_to_local() {
+        let mut motion = Keyframes::new(0.0, Transform::default());
+        motion.insert(
+            1.0,
+            Transform::new(
+                Vector::new(4.0, 0.0, 0.0),
+                Quaternion::default(),
+                Vector::new(1.0, 1.0, 1.0),
+            ),
+        );
+        let ray = Ray::new(Point::new(1.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert_approx_eq!(
+            Point::new(1.0, 0.0, 0.0),
+            motion.ray_to_local(&ray).origin()
+        );
+        assert_approx_eq!(
+            Point::new(-1.0, 0.0, 0.0),
+            motion.ray_to_local(&ray.with_time(0.5)).origin()
+        );
+    }
+
+    #[test]
+    fn motion_bounds_cover_every_moment() {
+        let local = Bounds::new(Point::new(-1.0, -0.5, -0.5), Point::new(1.0, 0.5, 0.5));
+        let mut motion = Keyframes::new(0.0, Transform::default());
+        motion.insert(
+            1.0,
+            Transform::new(
+                Vector::new(3.0, 0.0, 0.0),
+                Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 1.0), PI),
+                Vector::new(1.5, 1.0, 1.0),
+            ),
+        );
+
+        let bounds = motion.bounds(&local);
+        let mut highest = f64::NEG_INFINITY;
+
+        for step in 0..=1000 {
+            let transform = motion.at(step as f64 / 1000.0);
+            for corner in local.corners() {
+                let point = transform.apply_point(corner);
+                assert!(bounds.contains(point));
+                highest = highest.max(point.y());
+            }
+        }
+        // The corners swing well above where they start or end, and the box
+        // follows them closely.
+        assert!(highest > 1.3);
+        assert_approx_eq!(highest, bounds.max().y(), Margin::Absolute(0.01));
+    }
+}
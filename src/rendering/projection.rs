@@ -0,0 +1,179 @@
+use std::f64::consts::PI;
+
+// How the fisheye projection spaces angles from the view direction out
+// towards the edge of the image circle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FisheyeMapping {
+    // Distance from the centre grows linearly with the angle.
+    #[default]
+    Equidistant,
+    // Equal solid angles cover equal areas of the image.
+    Equisolid,
+}
+
+// How positions on the image map to rays leaving the camera.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Projection {
+    // A pinhole or thin lens, with the field of view across the longer side.
+    #[default]
+    Perspective,
+    // Parallel rays, with `size` the world extent of the longer side.
+    Orthographic {
+        size: f64,
+    },
+    // A circular image inscribed in the shorter side, covering the field of
+    // view across its diameter. Positions outside the circle have no ray.
+    Fisheye(FisheyeMapping),
+    // The full sphere as longitude across and latitude down the image,
+    // centred on the view direction. Ignores the field of view.
+    Equirectangular,
+}
+
+impl Projection {
+    // The ray through a position in pixels on a `width` by `height` image, in
+    // the camera's frame with x to the right, y up and z forwards. Returns
+    // the origin relative to the eye and an unnormalised direction, which
+    // for perspective reaches the image plane one unit ahead.
+    pub fn local_ray(
+        &self,
+        x: f64,
+        y: f64,
+        width: usize,
+        height: usize,
+        field_of_view: f64,
+    ) -> Option<([f64; 3], [f64; 3])> {
+        let (width, height) = (width as f64, height as f64);
+        // Offsets from the image centre, in half lengths of the longer side.
+        let half = width.max(height) / 2.0;
+        let (u, v) = ((x - width / 2.0) / half, (height / 2.0 - y) / half);
+
+        match *self {
+            Projection::Perspective => {
+                let half_view = (field_of_view / 2.0).tan();
+                Some(([0.0; 3], [u * half_view, v * half_view, 1.0]))
+            }
+            Projection::Orthographic { size } => {
+                Some(([u * size / 2.0, v * size / 2.0, 0.0], [0.0, 0.0, 1.0]))
+            }
+            Projection::Fisheye(mapping) => {
+                // Rescale so the image circle has a radius of one.
+                let scale = width.max(height) / width.min(height);
+                let (u, v) = (u * scale, v * scale);
+                let radius = u.hypot(v);
+                if radius > 1.0 {
+                    return None;
+                }
+
+                let theta = match mapping {
+                    FisheyeMapping::Equidistant => radius * field_of_view / 2.0,
+                    FisheyeMapping::Equisolid => {
+                        2.0 * (radius * (field_of_view / 4.0).sin()).asin()
+                    }
+                };
+                let (sin, cos) = theta.sin_cos();
+                let (u, v) = if radius > 0.0 {
+                    (u / radius, v / radius)
+                } else {
+                    (0.0, 0.0)
+                };
+
+                Some(([0.0; 3], [u * sin, v * sin, cos]))
+            }
+            Projection::Equirectangular => {
+                let longitude = (x / width - 0.5) * 2.0 * PI;
+                let latitude = (0.5 - y / height) * PI;
+                let (sin_longitude, cos_longitude) = longitude.sin_cos();
+                let (sin_latitude, cos_latitude) = latitude.sin_cos();
+
+                Some((
+                    [0.0; 3],
+                    [
+                        cos_latitude * sin_longitude,
+                        sin_latitude,
+                        cos_latitude * cos_longitude,
+                    ],
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn normalize(vector: [f64; 3]) -> [f64; 3] {
+        let length = (vector[0].powi(2) + vector[1].powi(2) + vector[2].powi(2)).sqrt();
+        vector.map(|value| value / length)
+    }
+
+    #[test]
+    fn perspective_reaches_image_plane() {
+        let (origin, direction) = Projection::Perspective
+            .local_ray(0.0, 50.0, 200, 100, FRAC_PI_2)
+            .unwrap();
+
+        assert_eq!([0.0; 3], origin);
+        assert_approx_eq!([-1.0, 0.0, 1.0], direction);
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let projection = Projection::Orthographic { size: 10.0 };
+        let (corner, corner_direction) = projection.local_ray(0.0, 0.0, 200, 100, 1.0).unwrap();
+        let (centre, centre_direction) = projection.local_ray(100.0, 50.0, 200, 100, 1.0).unwrap();
+
+        assert_approx_eq!([-5.0, 2.5, 0.0], corner);
+        assert_approx_eq!([0.0, 0.0, 0.0], centre);
+        assert_eq!(corner_direction, centre_direction);
+        assert_eq!([0.0, 0.0, 1.0], centre_direction);
+    }
+
+    #[test]
+    fn fisheye_equidistant() {
+        let projection = Projection::Fisheye(FisheyeMapping::Equidistant);
+        let ray = |x, y| {
+            projection
+                .local_ray(x, y, 200, 100, PI)
+                .map(|ray| normalize(ray.1))
+        };
+
+        assert_approx_eq!([0.0, 0.0, 1.0], ray(100.0, 50.0).unwrap());
+        // The edge of the circle is a right angle away.
+        assert_approx_eq!([1.0, 0.0, 0.0], ray(150.0, 50.0).unwrap());
+        assert_approx_eq!([0.0, -1.0, 0.0], ray(100.0, 100.0).unwrap());
+        // Halfway out is half the angle.
+        let halfway = ray(125.0, 50.0).unwrap();
+        assert_approx_eq!((PI / 4.0).cos(), halfway[2]);
+        assert_eq!(None, ray(0.0, 0.0));
+        assert_eq!(None, ray(160.0, 50.0));
+    }
+
+    #[test]
+    fn fisheye_equisolid() {
+        let projection = Projection::Fisheye(FisheyeMapping::Equisolid);
+        let ray = |x| normalize(projection.local_ray(x, 50.0, 100, 100, PI).unwrap().1);
+
+        assert_approx_eq!([0.0, 0.0, 1.0], ray(50.0));
+        assert_approx_eq!([1.0, 0.0, 0.0], ray(100.0), Margin::Absolute(1e-9));
+        // Halfway out, r = sin(θ / 2) / sin(π / 4) gives θ of about 41.4°.
+        let theta = 2.0 * (0.5 * (PI / 4.0).sin()).asin();
+        assert_approx_eq!(theta.cos(), ray(75.0)[2]);
+    }
+
+    #[test]
+    fn equirectangular_covers_sphere() {
+        let projection = Projection::Equirectangular;
+        let ray = |x, y| normalize(projection.local_ray(x, y, 360, 180, 0.0).unwrap().1);
+
+        assert_approx_eq!([0.0, 0.0, 1.0], ray(180.0, 90.0));
+        assert_approx_eq!([1.0, 0.0, 0.0], ray(270.0, 90.0));
+        assert_approx_eq!([-1.0, 0.0, 0.0], ray(90.0, 90.0));
+        assert_approx_eq!([0.0, 0.0, -1.0], ray(0.0, 90.0), Margin::Absolute(1e-9));
+        assert_approx_eq!([0.0, 1.0, 0.0], ray(123.0, 0.0), Margin::Absolute(1e-9));
+        assert_approx_eq!([0.0, -1.0, 0.0], ray(45.0, 180.0), Margin::Absolute(1e-9));
+    }
+}
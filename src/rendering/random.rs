@@ -0,0 +1,121 @@
+// Small, fast and reproducible pseudo random numbers, a PCG32 generator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Random {
+    state: u64,
+    increment: u64,
+}
+
+const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random::with_stream(seed, 0)
+    }
+
+    // Generators with the same seed but different streams are independent.
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut random = Random {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        random.next_u32();
+        random.state = random.state.wrapping_add(seed);
+        random.next_u32();
+
+        random
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.state = state.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+
+        let xorshifted = (((state >> 18) ^ state) >> 27) as u32;
+        xorshifted.rotate_right((state >> 59) as u32)
+    }
+
+    // Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        let high = (self.next_u32() as u64) << 21;
+        let low = (self.next_u32() >> 11) as u64;
+        (high | low) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in [0, bound), for a non-zero bound.
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        (self.next_f64() * bound as f64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.next_below(i + 1));
+        }
+    }
+}
+
+// Mixes a seed with further values into a well distributed seed, for example
+// to give each pixel its own deterministic generator.
+pub fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0x9e37_79b9_7f4a_7c15, |hash, value| {
+        let mut z = (hash ^ value).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_is_reproducible() {
+        let mut first = Random::new(42);
+        let mut second = Random::new(42);
+        let mut other = Random::new(43);
+
+        let values: Vec<u32> = (0..8).map(|_| first.next_u32()).collect();
+
+        assert_eq!(
+            values,
+            (0..8).map(|_| second.next_u32()).collect::<Vec<_>>()
+        );
+        assert_ne!(values, (0..8).map(|_| other.next_u32()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn random_streams_differ() {
+        let mut first = Random::with_stream(7, 1);
+        let mut second = Random::with_stream(7, 2);
+
+        assert_ne!(first.next_u32(), second.next_u32());
+    }
+
+    #[test]
+    fn next_f64_range() {
+        let mut random = Random::new(1);
+        let values: Vec<f64> = (0..10_000).map(|_| random.next_f64()).collect();
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+
+        assert!(values.iter().all(|value| (0.0..1.0).contains(value)));
+        assert!((mean - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn shuffle_permutes() {
+        let mut values: Vec<usize> = (0..16).collect();
+        Random::new(3).shuffle(&mut values);
+
+        let mut sorted = values.clone();
+        sorted.sort();
+
+        assert_ne!((0..16).collect::<Vec<_>>(), values);
+        assert_eq!((0..16).collect::<Vec<_>>(), sorted);
+    }
+
+    #[test]
+    fn hash_mixes_values() {
+        assert_eq!(hash(&[1, 2, 3]), hash(&[1, 2, 3]));
+        assert_ne!(hash(&[1, 2, 3]), hash(&[1, 3, 2]));
+        assert_ne!(hash(&[0]), hash(&[1]));
+    }
+}
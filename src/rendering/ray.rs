@@ -0,0 +1,74 @@
+use crate::tuples::point::Point;
+use crate::tuples::vector::Vector;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    origin: Point,
+    direction: Vector,
+    // When the ray was cast, for scenes that move while the shutter is open.
+    time: f64,
+}
+
+impl Ray {
+    pub fn new(origin: Point, direction: Vector) -> Self {
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+
+    pub fn with_time(self, time: f64) -> Self {
+        Ray { time, ..self }
+    }
+
+    pub fn origin(&self) -> Point {
+        self.origin
+    }
+
+    pub fn direction(&self) -> Vector {
+        self.direction
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn position(&self, distance: f64) -> Point {
+        self.origin + self.direction * distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_ray_constructor() {
+        let origin = Point::new(1.0, 2.0, 3.0);
+        let direction = Vector::new(4.0, 5.0, 6.0);
+        let ray = Ray::new(origin, direction);
+
+        assert_eq!(origin, ray.origin());
+        assert_eq!(direction, ray.direction());
+        assert_eq!(0.0, ray.time());
+    }
+
+    #[test]
+    fn ray_with_time() {
+        let ray = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0)).with_time(0.25);
+
+        assert_eq!(0.25, ray.time());
+        assert_eq!(Point::new(1.0, 4.0, 3.0), ray.position(2.0));
+    }
+
+    #[test]
+    fn ray_position() {
+        let ray = Ray::new(Point::new(2.0, 3.0, 4.0), Vector::new(1.0, 0.0, 0.0));
+
+        assert_eq!(Point::new(2.0, 3.0, 4.0), ray.position(0.0));
+        assert_eq!(Point::new(3.0, 3.0, 4.0), ray.position(1.0));
+        assert_eq!(Point::new(1.0, 3.0, 4.0), ray.position(-1.0));
+        assert_eq!(Point::new(4.5, 3.0, 4.0), ray.position(2.5));
+    }
+}
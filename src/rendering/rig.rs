@@ -0,0 +1,475 @@
+use std::f64::consts::FRAC_PI_2;
+
+use crate::pixels::canvas::Canvas;
+use crate::pixels::color::Color;
+use crate::rendering::camera::Camera;
+use crate::rendering::ray::Ray;
+use crate::tuples::point::Point;
+use crate::tuples::vector::Vector;
+
+// How the two views of a stereo pair share one canvas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StereoLayout {
+    // Left eye on the left half, right eye on the right half.
+    #[default]
+    SideBySide,
+    // Left eye on the top half, right eye on the bottom half.
+    OverUnder,
+}
+
+// A pair of eyes either side of a centre camera, `interaxial` apart and
+// looking the same way. Their image planes are shifted towards each other so
+// the views line up at `convergence` along the view direction, without the
+// vertical parallax toeing in would cause. An infinite convergence leaves
+// the images unshifted, as does one that is zero, negative or NaN.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StereoRig {
+    camera: Camera,
+    interaxial: f64,
+    convergence: f64,
+    layout: StereoLayout,
+}
+
+impl StereoRig {
+    pub fn new(camera: Camera, interaxial: f64, convergence: f64) -> Self {
+        StereoRig {
+            camera,
+            interaxial,
+            convergence: if convergence > 0.0 {
+                convergence
+            } else {
+                f64::INFINITY
+            },
+            layout: StereoLayout::default(),
+        }
+    }
+
+    pub fn layout(&self) -> StereoLayout {
+        self.layout
+    }
+
+    pub fn set_layout(&mut self, layout: StereoLayout) {
+        self.layout = layout;
+    }
+
+    // The left and right eye cameras, sharing every other setting with the
+    // centre camera. The eyes are offset in the camera's own axes, so they
+    // follow its keyframes too.
+    pub fn eyes(&self) -> [Camera; 2] {
+        let centre = &self.camera;
+        let (shift_x, shift_y) = centre.image_shift();
+
+        [-0.5, 0.5].map(|side| {
+            let mut eye = centre.clone();
+            let offset = side * self.interaxial;
+            eye.set_eye_offset(centre.eye_offset() + Vector::new(offset, 0.0, 0.0));
+            eye.set_image_shift(shift_x - offset / self.convergence, shift_y);
+            eye
+        })
+    }
+
+    // Panics unless both views have the same size.
+    pub fn pack(&self, left: &Canvas, right: &Canvas) -> Canvas {
+        let (width, height) = (left.width(), left.height());
+        assert_eq!(
+            (width, height),
+            (right.width(), right.height()),
+            "the left and right views differ in size"
+        );
+
+        match self.layout {
+            StereoLayout::SideBySide => {
+                let mut canvas = Canvas::new(width * 2, height);
+                paste(&mut canvas, left, 0, 0);
+                paste(&mut canvas, right, width, 0);
+                canvas
+            }
+            StereoLayout::OverUnder => {
+                let mut canvas = Canvas::new(width, height * 2);
+                paste(&mut canvas, left, 0, 0);
+                paste(&mut canvas, right, 0, height);
+                canvas
+            }
+        }
+    }
+
+    pub fn render(&self, radiance: impl Fn(&Ray) -> Option<Color>) -> Canvas {
+        let [left, right] = self.eyes().map(|eye| eye.render(&radiance));
+        self.pack(&left, &right)
+    }
+}
+
+// The faces of a cube map, in the order of the strip layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubeFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::PositiveX,
+        CubeFace::NegativeX,
+        CubeFace::PositiveY,
+        CubeFace::NegativeY,
+        CubeFace::PositiveZ,
+        CubeFace::NegativeZ,
+    ];
+
+    pub fn direction(&self) -> Vector {
+        match self {
+            CubeFace::PositiveX => Vector::new(1.0, 0.0, 0.0),
+            CubeFace::NegativeX => Vector::new(-1.0, 0.0, 0.0),
+            CubeFace::PositiveY => Vector::new(0.0, 1.0, 0.0),
+            CubeFace::NegativeY => Vector::new(0.0, -1.0, 0.0),
+            CubeFace::PositiveZ => Vector::new(0.0, 0.0, 1.0),
+            CubeFace::NegativeZ => Vector::new(0.0, 0.0, -1.0),
+        }
+    }
+
+    // Upwards in the face's image. The sides keep positive y up, while the
+    // top and bottom faces are turned so their edges meet negative z, the
+    // centre of the cross.
+    pub fn up(&self) -> Vector {
+        match self {
+            CubeFace::PositiveY => Vector::new(0.0, 0.0, 1.0),
+            CubeFace::NegativeY => Vector::new(0.0, 0.0, -1.0),
+            _ => Vector::new(0.0, 1.0, 0.0),
+        }
+    }
+
+    // Column and row of the face in a horizontal cross, four faces wide and
+    // three tall.
+    fn cross_cell(&self) -> (usize, usize) {
+        match self {
+            CubeFace::PositiveY => (1, 0),
+            CubeFace::NegativeX => (0, 1),
+            CubeFace::NegativeZ => (1, 1),
+            CubeFace::PositiveX => (2, 1),
+            CubeFace::PositiveZ => (3, 1),
+            CubeFace::NegativeY => (1, 2),
+        }
+    }
+}
+
+// How the six faces of a cube map share one canvas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CubeMapLayout {
+    // A horizontal cross, with the unused cells left transparent.
+    #[default]
+    Cross,
+    // One row in the order of `CubeFace::ALL`.
+    Strip,
+}
+
+// Six square cameras at one point, each covering a right angle, that
+// together see in every direction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubeMapRig {
+    origin: Point,
+    size: usize,
+    samples: usize,
+    layout: CubeMapLayout,
+}
+
+impl CubeMapRig {
+    // `size` is the width and height of every face in pixels.
+    pub fn new(origin: Point, size: usize) -> Self {
+        CubeMapRig {
+            origin,
+            size,
+            samples: 1,
+            layout: CubeMapLayout::default(),
+        }
+    }
+
+    pub fn layout(&self) -> CubeMapLayout {
+        self.layout
+    }
+
+    pub fn set_layout(&mut self, layout: CubeMapLayout) {
+        self.layout = layout;
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    pub fn set_samples(&mut self, samples: usize) {
+        self.samples = samples.max(1);
+    }
+
+    pub fn camera(&self, face: CubeFace) -> Camera {
+        let mut camera = Camera::new(self.size, self.size, FRAC_PI_2);
+        camera.set_samples(self.samples);
+        camera.look_at(self.origin, self.origin + face.direction(), face.up());
+        camera
+    }
+
+    // Packs faces given in the order of `CubeFace::ALL`. Panics unless every
+    // face is `size` pixels square.
+    pub fn pack(&self, faces: &[Canvas; 6]) -> Canvas {
+        let size = self.size;
+        for (face, image) in CubeFace::ALL.iter().zip(faces) {
+            assert_eq!(
+                (size, size),
+                (image.width(), image.height()),
+                "the {face:?} face is not {size} pixels square"
+            );
+        }
+
+        match self.layout {
+            CubeMapLayout::Cross => {
+                let mut canvas = Canvas::new(size * 4, size * 3);
+                for (face, image) in CubeFace::ALL.iter().zip(faces) {
+                    let (column, row) = face.cross_cell();
+                    paste(&mut canvas, image, column * size, row * size);
+                }
+                canvas
+            }
+            CubeMapLayout::Strip => {
+                let mut canvas = Canvas::new(size * 6, size);
+                for (index, image) in faces.iter().enumerate() {
+                    paste(&mut canvas, image, index * size, 0);
+                }
+                canvas
+            }
+        }
+    }
+
+    pub fn render(&self, radiance: impl Fn(&Ray) -> Option<Color>) -> Canvas {
+        let faces = CubeFace::ALL.map(|face| self.camera(face).render(&radiance));
+        self.pack(&faces)
+    }
+}
+
+// Copies `source` into `target` with its top left corner at (x, y).
+fn paste(target: &mut Canvas, source: &Canvas, x: usize, y: usize) {
+    for (column, row, color) in source.enumerate_pixels() {
+        let alpha = source.get_alpha(column, row);
+        target.set_pixel_with_alpha(x + column, y + row, *color, alpha);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::Margin;
+    use crate::assert_approx_eq;
+    use crate::pixels::rgb::Rgb;
+    use crate::tuples::coordinates::Coordinates;
+    use crate::tuples::scalar::Scalar;
+
+    fn stereo_rig(convergence: f64) -> StereoRig {
+        let mut camera = Camera::new(8, 6, FRAC_PI_2);
+        camera.look_at(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(0.0, 1.0, -1.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        StereoRig::new(camera, 0.064, convergence)
+    }
+
+    #[test]
+    fn stereo_eyes_converge() {
+        let [left, right] = stereo_rig(2.0).eyes();
+        let target = Point::new(0.0, 1.0, -2.0);
+        let centre = |eye: &Camera| eye.ray_for_position(4.0, 3.0).unwrap().direction();
+
+        assert_approx_eq!(Point::new(-0.032, 1.0, 0.0), left.origin());
+        assert_approx_eq!(Point::new(0.032, 1.0, 0.0), right.origin());
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), left.forward());
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), right.forward());
+        assert_approx_eq!((target - left.origin()).normalize(), centre(&left));
+        assert_approx_eq!((target - right.origin()).normalize(), centre(&right));
+        assert_eq!(8, left.width());
+    }
+
+    #[test]
+    fn stereo_eyes_have_no_vertical_parallax() {
+        // A point off to the side lands on the same row in both views.
+        let [left, right] = stereo_rig(2.0).eyes();
+        let point = Point::new(1.5, 2.0, -3.0);
+        let height = |eye: &Camera| {
+            let direction = point - eye.origin();
+            direction.dot_product(eye.up()) / direction.dot_product(eye.forward())
+        };
+
+        assert_approx_eq!(height(&left), height(&right));
+    }
+
+    #[test]
+    fn stereo_eyes_parallel() {
+        let [left, right] = stereo_rig(f64::INFINITY).eyes();
+
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), left.forward());
+        assert_approx_eq!(Vector::new(0.0, 0.0, -1.0), right.forward());
+        assert_eq!((0.0, 0.0), left.image_shift());
+    }
+
+    #[test]
+    fn stereo_eyes_follow_keyframes() {
+        // The rig swings from looking down negative z to looking along x.
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let mut camera = Camera::new(8, 6, FRAC_PI_2);
+        camera.add_keyframe(0.0, Point::default(), Point::new(0.0, 0.0, -1.0), up);
+        camera.add_keyframe(1.0, Point::default(), Point::new(1.0, 0.0, 0.0), up);
+        let [left, right] = StereoRig::new(camera, 0.064, 2.0).eyes();
+        let ray = |eye: &Camera, time| eye.ray_at_time(4.0, 3.0, (0.5, 0.5), time).unwrap();
+
+        assert_approx_eq!(Point::new(-0.032, 0.0, 0.0), left.origin());
+        assert_approx_eq!(Point::new(0.032, 0.0, 0.0), ray(&right, 0.0).origin());
+        assert_approx_eq!(Point::new(0.0, 0.0, -0.032), ray(&left, 1.0).origin());
+        assert_approx_eq!(Point::new(0.0, 0.0, 0.032), ray(&right, 1.0).origin());
+
+        let target = Point::new(2.0, 0.0, 0.0);
+        for eye in [&left, &right] {
+            let ray = ray(eye, 1.0);
+            assert_approx_eq!((target - ray.origin()).normalize(), ray.direction());
+        }
+    }
+
+    #[test]
+    fn stereo_eyes_without_convergence_are_parallel() {
+        for convergence in [0.0, -2.0, f64::NAN] {
+            let [left, right] = stereo_rig(convergence).eyes();
+
+            for eye in [left, right] {
+                assert_eq!((0.0, 0.0), eye.image_shift());
+                assert_approx_eq!(
+                    Vector::new(0.0, 0.0, -1.0),
+                    eye.ray_for_position(4.0, 3.0).unwrap().direction()
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the left and right views differ in size")]
+    fn stereo_pack_checks_sizes() {
+        stereo_rig(2.0).pack(&Canvas::new(8, 6), &Canvas::new(6, 8));
+    }
+
+    #[test]
+    fn stereo_layouts() {
+        // Each eye sees its own side of the world.
+        let radiance = |ray: &Ray| {
+            if ray.origin().x() < 0.0 {
+                Some(Color::new(1.0, 0.0, 0.0))
+            } else {
+                Some(Color::new(0.0, 0.0, 1.0))
+            }
+        };
+
+        let mut rig = stereo_rig(2.0);
+        let side_by_side = rig.render(radiance);
+        rig.set_layout(StereoLayout::OverUnder);
+        let over_under = rig.render(radiance);
+
+        assert_eq!((16, 6), (side_by_side.width(), side_by_side.height()));
+        assert_eq!(1.0, side_by_side.get_pixel(7, 5).r());
+        assert_eq!(1.0, side_by_side.get_pixel(8, 0).b());
+        assert_eq!((8, 12), (over_under.width(), over_under.height()));
+        assert_eq!(1.0, over_under.get_pixel(7, 5).r());
+        assert_eq!(1.0, over_under.get_pixel(0, 6).b());
+    }
+
+    #[test]
+    fn cube_faces_are_orthogonal() {
+        for face in CubeFace::ALL {
+            let camera = CubeMapRig::new(Point::default(), 4).camera(face);
+
+            assert_approx_eq!(face.direction(), camera.forward());
+            assert_approx_eq!(face.up(), camera.up());
+            assert_approx_eq!(0.0, face.direction().dot_product(face.up()));
+        }
+    }
+
+    #[test]
+    fn cube_faces_meet_at_edges() {
+        // The top edge of the front face continues into the bottom edge of
+        // the top face, and its right edge into the left edge of the right.
+        let rig = CubeMapRig::new(Point::default(), 8);
+        let ray = |face, x, y| rig.camera(face).ray_for_position(x, y).unwrap().direction();
+
+        assert_approx_eq!(
+            ray(CubeFace::NegativeZ, 2.5, 0.0),
+            ray(CubeFace::PositiveY, 2.5, 8.0)
+        );
+        assert_approx_eq!(
+            ray(CubeFace::NegativeZ, 8.0, 3.5),
+            ray(CubeFace::PositiveX, 0.0, 3.5)
+        );
+        assert_approx_eq!(
+            ray(CubeFace::NegativeZ, 5.5, 8.0),
+            ray(CubeFace::NegativeY, 5.5, 0.0)
+        );
+        assert_approx_eq!(
+            ray(CubeFace::PositiveZ, 8.0, 1.5),
+            ray(CubeFace::NegativeX, 0.0, 1.5)
+        );
+    }
+
+    // Colours each direction by its dominant axis, brighter for positive.
+    fn axis_color(ray: &Ray) -> Option<Color> {
+        let direction = ray.direction();
+        let values = [direction.x(), direction.y(), direction.z()];
+        let axis = (0..3)
+            .max_by(|a, b| values[*a].abs().total_cmp(&values[*b].abs()))
+            .unwrap();
+        let value = if values[axis] > 0.0 { 1.0 } else { 0.5 };
+
+        let mut channels = [0.0; 3];
+        channels[axis] = value;
+        Some(Color::new(channels[0], channels[1], channels[2]))
+    }
+
+    #[test]
+    fn cube_map_cross() {
+        let canvas = CubeMapRig::new(Point::default(), 4).render(axis_color);
+
+        assert_eq!((16, 12), (canvas.width(), canvas.height()));
+        assert_eq!(Color::new(0.0, 1.0, 0.0), canvas.get_pixel(6, 2));
+        assert_eq!(Color::new(0.5, 0.0, 0.0), canvas.get_pixel(2, 6));
+        assert_eq!(Color::new(0.0, 0.0, 0.5), canvas.get_pixel(6, 6));
+        assert_eq!(Color::new(1.0, 0.0, 0.0), canvas.get_pixel(10, 6));
+        assert_eq!(Color::new(0.0, 0.0, 1.0), canvas.get_pixel(14, 6));
+        assert_eq!(Color::new(0.0, 0.5, 0.0), canvas.get_pixel(6, 10));
+        assert_eq!(0.0, canvas.get_alpha(1, 1));
+        assert_eq!(1.0, canvas.get_alpha(5, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "the NegativeY face is not 4 pixels square")]
+    fn cube_map_pack_checks_sizes() {
+        let mut faces = [(); 6].map(|_| Canvas::new(4, 4));
+        faces[3] = Canvas::new(4, 2);
+
+        CubeMapRig::new(Point::default(), 4).pack(&faces);
+    }
+
+    #[test]
+    fn cube_map_strip() {
+        let mut rig = CubeMapRig::new(Point::default(), 4);
+        rig.set_layout(CubeMapLayout::Strip);
+        rig.set_samples(4);
+
+        let canvas = rig.render(axis_color);
+
+        assert_eq!((24, 4), (canvas.width(), canvas.height()));
+        for (index, face) in CubeFace::ALL.iter().enumerate() {
+            let direction = face.direction();
+            let ray = Ray::new(Point::default(), direction);
+
+            assert_approx_eq!(
+                axis_color(&ray).unwrap(),
+                canvas.get_pixel(index * 4 + 2, 2),
+                Margin::Absolute(1e-12)
+            );
+        }
+    }
+}
@@ -0,0 +1,633 @@
+use std::collections::HashMap;
+
+use crate::rendering::random::{hash, Random};
+
+// Streams of sample values in [0, 1) for each sample of each pixel. Every
+// call to `get_1d` or `get_2d` moves on to the next dimensions, so the values
+// for the same pixel, sample index and dimension are always the same.
+pub trait Sampler {
+    fn samples_per_pixel(&self) -> usize;
+
+    // Restarts the stream at the first dimension of a sample within a pixel.
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize);
+
+    fn get_1d(&mut self) -> f64;
+
+    fn get_2d(&mut self) -> (f64, f64);
+}
+
+// Where in the sample streams a sampler currently is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Position {
+    x: usize,
+    y: usize,
+    index: usize,
+    dimension: usize,
+}
+
+impl Position {
+    fn start(&mut self, x: usize, y: usize, index: usize) {
+        *self = Position {
+            x,
+            y,
+            index,
+            dimension: 0,
+        };
+    }
+
+    // A seed unique to the pixel and the next dimension, which is consumed.
+    fn next_seed(&mut self, seed: u64, dimensions: usize) -> u64 {
+        let dimension = self.dimension;
+        self.dimension += dimensions;
+        hash(&[seed, self.x as u64, self.y as u64, dimension as u64])
+    }
+}
+
+// Uncorrelated pseudo random values.
+#[derive(Clone, Debug)]
+pub struct IndependentSampler {
+    samples: usize,
+    seed: u64,
+    random: Random,
+}
+
+impl IndependentSampler {
+    pub fn new(samples: usize, seed: u64) -> Self {
+        IndependentSampler {
+            samples,
+            seed,
+            random: Random::new(seed),
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn samples_per_pixel(&self) -> usize {
+        self.samples
+    }
+
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize) {
+        self.random = Random::new(hash(&[self.seed, x as u64, y as u64, index as u64]));
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.random.next_f64()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.random.next_f64(), self.random.next_f64())
+    }
+}
+
+// Jittered samples with one per stratum of an x by y grid, visited in a
+// different random order for each pixel and dimension.
+#[derive(Clone, Debug)]
+pub struct StratifiedSampler {
+    x_strata: usize,
+    y_strata: usize,
+    seed: u64,
+    position: Position,
+    // The stratum order of each dimension of the current pixel, built on
+    // first use so later samples of the pixel only look it up.
+    permutations: HashMap<usize, Vec<usize>>,
+}
+
+impl StratifiedSampler {
+    pub fn new(x_strata: usize, y_strata: usize, seed: u64) -> Self {
+        StratifiedSampler {
+            x_strata: x_strata.max(1),
+            y_strata: y_strata.max(1),
+            seed,
+            position: Position::default(),
+            permutations: HashMap::new(),
+        }
+    }
+
+    // The stratum this sample falls in and a generator to jitter within it.
+    fn stratum(&mut self, dimensions: usize) -> (usize, Random) {
+        let dimension = self.position.dimension;
+        let seed = self.position.next_seed(self.seed, dimensions);
+        let samples = self.samples_per_pixel();
+        let strata = self.permutations.entry(dimension).or_insert_with(|| {
+            let mut strata: Vec<usize> = (0..samples).collect();
+            Random::new(seed).shuffle(&mut strata);
+            strata
+        });
+        let index = self.position.index;
+
+        (
+            strata[index % samples],
+            Random::new(hash(&[seed, index as u64])),
+        )
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn samples_per_pixel(&self) -> usize {
+        self.x_strata * self.y_strata
+    }
+
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize) {
+        if (x, y) != (self.position.x, self.position.y) {
+            self.permutations.clear();
+        }
+        self.position.start(x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let (stratum, mut random) = self.stratum(1);
+        (stratum as f64 + random.next_f64()) / self.samples_per_pixel() as f64
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let (stratum, mut random) = self.stratum(2);
+        let x = (stratum % self.x_strata) as f64 + random.next_f64();
+        let y = (stratum / self.x_strata) as f64 + random.next_f64();
+
+        (x / self.x_strata as f64, y / self.y_strata as f64)
+    }
+}
+
+const PRIMES: [u64; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131,
+];
+
+// The digits of `index` in `base` mirrored around the radix point.
+pub fn radical_inverse(base: u64, mut index: u64) -> f64 {
+    let inverse_base = 1.0 / base as f64;
+    let mut reversed = 0;
+    let mut scale = 1.0;
+
+    while index > 0 {
+        reversed = reversed * base + index % base;
+        scale *= inverse_base;
+        index /= base;
+    }
+
+    (reversed as f64 * scale).min(1.0 - f64::EPSILON / 2.0)
+}
+
+// The Halton sequence, using the next prime as base for each dimension and
+// decorrelated between pixels by a random toroidal shift. Dimensions beyond
+// the prime table fall back to independent values.
+#[derive(Clone, Debug)]
+pub struct HaltonSampler {
+    samples: usize,
+    seed: u64,
+    position: Position,
+}
+
+impl HaltonSampler {
+    pub fn new(samples: usize, seed: u64) -> Self {
+        HaltonSampler {
+            samples,
+            seed,
+            position: Position::default(),
+        }
+    }
+
+    fn sample(&mut self) -> f64 {
+        let dimension = self.position.dimension;
+        let seed = self.position.next_seed(self.seed, 1);
+
+        match PRIMES.get(dimension) {
+            Some(base) => {
+                let shift = Random::new(seed).next_f64();
+                (radical_inverse(*base, self.position.index as u64) + shift).fract()
+            }
+            None => Random::new(hash(&[seed, self.position.index as u64])).next_f64(),
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn samples_per_pixel(&self) -> usize {
+        self.samples
+    }
+
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize) {
+        self.position.start(x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.sample()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.sample(), self.sample())
+    }
+}
+
+// The first two dimensions of the Sobol sequence, with Owen scrambling
+// following Burley's hash based approach. Each pair of dimensions shuffles
+// the sample order differently, which pads the sequence to any dimension.
+#[derive(Clone, Debug)]
+pub struct SobolSampler {
+    samples: usize,
+    seed: u64,
+    position: Position,
+}
+
+impl SobolSampler {
+    pub fn new(samples: usize, seed: u64) -> Self {
+        SobolSampler {
+            samples,
+            seed,
+            position: Position::default(),
+        }
+    }
+
+    fn sample(&mut self, dimensions: usize) -> [f64; 2] {
+        let seed = self.position.next_seed(self.seed, dimensions) as u32;
+        let index = nested_uniform_scramble(self.position.index as u32, seed);
+
+        [0, 1].map(|dimension| {
+            let seed = hash(&[seed as u64, dimension]) as u32;
+            let value = nested_uniform_scramble(sobol(index, dimension as usize), seed);
+            value as f64 / (1u64 << 32) as f64
+        })
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn samples_per_pixel(&self) -> usize {
+        self.samples
+    }
+
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize) {
+        self.position.start(x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.sample(1)[0]
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let [x, y] = self.sample(2);
+        (x, y)
+    }
+}
+
+// Unscrambled Sobol points as 32 bit fractions, for dimension 0 or 1.
+pub fn sobol(index: u32, dimension: usize) -> u32 {
+    let mut value = 0;
+    let mut direction = 1 << 31;
+
+    for bit in 0..32 {
+        if (index >> bit) & 1 == 1 {
+            value ^= if dimension == 0 {
+                1 << (31 - bit)
+            } else {
+                direction
+            };
+        }
+        direction ^= direction >> 1;
+    }
+
+    value
+}
+
+fn laine_karras_permutation(mut value: u32, seed: u32) -> u32 {
+    value = value.wrapping_add(seed);
+    value ^= value.wrapping_mul(0x6c50_b47c);
+    value ^= value.wrapping_mul(0xb82f_1e52);
+    value ^= value.wrapping_mul(0xc7af_e638);
+    value ^= value.wrapping_mul(0x8d22_f6e6);
+    value
+}
+
+fn nested_uniform_scramble(value: u32, seed: u32) -> u32 {
+    laine_karras_permutation(value.reverse_bits(), seed).reverse_bits()
+}
+
+const BLUE_NOISE_SIZE: usize = 32;
+
+// Values that are blue noise across the image, so the error of neighbouring
+// pixels differs and reads as fine grain, and that progress as a rank one
+// lattice over the samples of each pixel. The noise tile is grown with
+// void and cluster from the seed, and offset for every dimension.
+#[derive(Clone, Debug)]
+pub struct BlueNoiseSampler {
+    samples: usize,
+    seed: u64,
+    tile: Vec<f64>,
+    position: Position,
+}
+
+impl BlueNoiseSampler {
+    pub fn new(samples: usize, seed: u64) -> Self {
+        BlueNoiseSampler {
+            samples,
+            seed,
+            tile: blue_noise(BLUE_NOISE_SIZE, seed),
+            position: Position::default(),
+        }
+    }
+
+    // The tile value under the pixel, shifted differently for each dimension.
+    fn noise(&mut self) -> f64 {
+        let dimension = self.position.dimension;
+        self.position.dimension += 1;
+
+        let mut random = Random::new(hash(&[self.seed, dimension as u64]));
+        let x = (self.position.x + random.next_below(BLUE_NOISE_SIZE)) % BLUE_NOISE_SIZE;
+        let y = (self.position.y + random.next_below(BLUE_NOISE_SIZE)) % BLUE_NOISE_SIZE;
+
+        self.tile[y * BLUE_NOISE_SIZE + x]
+    }
+}
+
+impl Sampler for BlueNoiseSampler {
+    fn samples_per_pixel(&self) -> usize {
+        self.samples
+    }
+
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize) {
+        self.position.start(x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        // The golden ratio gives the most even one dimensional lattice.
+        let step = 0.618_033_988_749_894_9;
+        (self.noise() + self.position.index as f64 * step).fract()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        // Roberts' R2 sequence, based on the plastic number.
+        let steps = (0.754_877_666_246_692_7, 0.569_840_290_998_053_3);
+        let index = self.position.index as f64;
+
+        (
+            (self.noise() + index * steps.0).fract(),
+            (self.noise() + index * steps.1).fract(),
+        )
+    }
+}
+
+// A square tile of blue noise with `size` sides and values in [0, 1), made by
+// ranking pixels with the void and cluster method on a torus.
+fn blue_noise(size: usize, seed: u64) -> Vec<f64> {
+    let count = size * size;
+    let sigma: f64 = 1.5;
+
+    // Gaussian weights by toroidal offset.
+    let kernel: Vec<f64> = (0..count)
+        .map(|i| {
+            let dx = (i % size).min(size - i % size) as f64;
+            let dy = (i / size).min(size - i / size) as f64;
+            (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+
+    let mut energy = vec![0.0; count];
+    let mut filled = vec![false; count];
+    let toggle = |energy: &mut Vec<f64>, filled: &mut Vec<bool>, pixel: usize| {
+        let sign = if filled[pixel] { -1.0 } else { 1.0 };
+        filled[pixel] = !filled[pixel];
+        let (px, py) = (pixel % size, pixel / size);
+        for (i, value) in energy.iter_mut().enumerate() {
+            let dx = (i % size + size - px) % size;
+            let dy = (i / size + size - py) % size;
+            *value += sign * kernel[dy * size + dx];
+        }
+    };
+    // The filled pixel in the tightest cluster, or the empty one in the
+    // largest void.
+    let extreme = |energy: &[f64], filled: &[bool], cluster: bool| {
+        (0..count)
+            .filter(|i| filled[*i] == cluster)
+            .max_by(|a, b| {
+                let ordering = energy[*a].total_cmp(&energy[*b]);
+                if cluster {
+                    ordering
+                } else {
+                    ordering.reverse()
+                }
+            })
+            .unwrap()
+    };
+
+    // Seed a tenth of the pixels, then even them out by moving the tightest
+    // cluster into the largest void until that changes nothing.
+    let mut random = Random::new(seed);
+    let mut pixels: Vec<usize> = (0..count).collect();
+    random.shuffle(&mut pixels);
+    let initial = count / 10;
+    pixels[..initial]
+        .iter()
+        .for_each(|pixel| toggle(&mut energy, &mut filled, *pixel));
+
+    for _ in 0..count {
+        let cluster = extreme(&energy, &filled, true);
+        toggle(&mut energy, &mut filled, cluster);
+        let void = extreme(&energy, &filled, false);
+        if void == cluster {
+            toggle(&mut energy, &mut filled, cluster);
+            break;
+        }
+        toggle(&mut energy, &mut filled, void);
+    }
+
+    let mut rank = vec![0; count];
+
+    // Rank the initial pixels by removing the tightest clusters first.
+    let (mut phase_energy, mut phase_filled) = (energy.clone(), filled.clone());
+    for order in (0..initial).rev() {
+        let cluster = extreme(&phase_energy, &phase_filled, true);
+        toggle(&mut phase_energy, &mut phase_filled, cluster);
+        rank[cluster] = order;
+    }
+
+    // Then the remaining pixels by filling the largest voids.
+    for order in initial..count {
+        let void = extreme(&energy, &filled, false);
+        toggle(&mut energy, &mut filled, void);
+        rank[void] = order;
+    }
+
+    rank.iter()
+        .map(|rank| (*rank as f64 + 0.5) / count as f64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samplers(samples: usize, seed: u64) -> Vec<Box<dyn Sampler>> {
+        vec![
+            Box::new(IndependentSampler::new(samples, seed)),
+            Box::new(StratifiedSampler::new(4, samples / 4, seed)),
+            Box::new(HaltonSampler::new(samples, seed)),
+            Box::new(SobolSampler::new(samples, seed)),
+            Box::new(BlueNoiseSampler::new(samples, seed)),
+        ]
+    }
+
+    fn stream(sampler: &mut dyn Sampler, x: usize, y: usize) -> Vec<f64> {
+        (0..sampler.samples_per_pixel())
+            .flat_map(|index| {
+                sampler.start_pixel_sample(x, y, index);
+                let (u, v) = sampler.get_2d();
+                let w = sampler.get_1d();
+                [u, v, w]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn samplers_are_reproducible() {
+        for (mut first, mut second) in samplers(16, 7).into_iter().zip(samplers(16, 7)) {
+            let values = stream(first.as_mut(), 3, 5);
+
+            assert_eq!(16, first.samples_per_pixel());
+            assert_eq!(values, stream(second.as_mut(), 3, 5));
+            assert_eq!(values, stream(first.as_mut(), 3, 5));
+            assert!(values.iter().all(|value| (0.0..1.0).contains(value)));
+        }
+    }
+
+    #[test]
+    fn samplers_depend_on_seed_and_pixel() {
+        for (mut first, mut second) in samplers(16, 1).into_iter().zip(samplers(16, 2)) {
+            let values = stream(first.as_mut(), 0, 0);
+
+            assert_ne!(values, stream(second.as_mut(), 0, 0));
+            assert_ne!(values, stream(first.as_mut(), 1, 0));
+        }
+    }
+
+    #[test]
+    fn samplers_are_uniform() {
+        for mut sampler in samplers(1024, 3) {
+            let values = stream(sampler.as_mut(), 2, 2);
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+
+            assert!((mean - 0.5).abs() < 0.02, "mean {mean}");
+        }
+    }
+
+    // Checks that the first 2D samples of a pixel fall one per cell of a grid.
+    fn assert_stratified(sampler: &mut dyn Sampler, side: usize) {
+        let mut cells: Vec<usize> = (0..side * side)
+            .map(|index| {
+                sampler.start_pixel_sample(4, 9, index);
+                let (u, v) = sampler.get_2d();
+                (v * side as f64) as usize * side + (u * side as f64) as usize
+            })
+            .collect();
+        cells.sort();
+
+        assert_eq!((0..side * side).collect::<Vec<_>>(), cells);
+    }
+
+    #[test]
+    fn stratified_sampler_covers_strata() {
+        let mut sampler = StratifiedSampler::new(4, 4, 11);
+        assert_stratified(&mut sampler, 4);
+
+        sampler.start_pixel_sample(0, 0, 0);
+        sampler.get_2d();
+        let mut strata: Vec<usize> = (0..16)
+            .map(|index| {
+                sampler.start_pixel_sample(0, 0, index);
+                sampler.get_2d();
+                (sampler.get_1d() * 16.0) as usize
+            })
+            .collect();
+        strata.sort();
+
+        assert_eq!((0..16).collect::<Vec<_>>(), strata);
+    }
+
+    #[test]
+    fn stratified_sampler_jitters_each_sample() {
+        let mut sampler = StratifiedSampler::new(4, 4, 11);
+        let jitter: Vec<f64> = (0..16)
+            .map(|index| {
+                sampler.start_pixel_sample(2, 3, index);
+                (sampler.get_1d() * 16.0).fract()
+            })
+            .collect();
+
+        assert!(jitter
+            .iter()
+            .any(|offset| (offset - jitter[0]).abs() > 1e-3));
+
+        // Coming back to a pixel after another gives the same values again.
+        sampler.start_pixel_sample(5, 5, 0);
+        sampler.get_1d();
+        sampler.start_pixel_sample(2, 3, 7);
+        assert_eq!(jitter[7], (sampler.get_1d() * 16.0).fract());
+    }
+
+    #[test]
+    fn radical_inverse_digits() {
+        assert_eq!(0.0, radical_inverse(2, 0));
+        assert_eq!(0.5, radical_inverse(2, 1));
+        assert_eq!(0.25, radical_inverse(2, 2));
+        assert_eq!(0.75, radical_inverse(2, 3));
+        assert_eq!(1.0 / 3.0, radical_inverse(3, 1));
+        assert_eq!(1.0 / 9.0 + 2.0 / 3.0, radical_inverse(3, 5));
+    }
+
+    #[test]
+    fn halton_sampler_is_stratified() {
+        // Shifted Halton points stay well spread in one dimension.
+        let mut sampler = HaltonSampler::new(8, 5);
+        let mut values: Vec<f64> = (0..8)
+            .map(|index| {
+                sampler.start_pixel_sample(1, 1, index);
+                sampler.get_1d()
+            })
+            .collect();
+        values.sort_by(f64::total_cmp);
+
+        assert!(values.windows(2).all(|pair| pair[1] - pair[0] > 0.124));
+    }
+
+    #[test]
+    fn sobol_points() {
+        let points: Vec<(u32, u32)> = (0..4).map(|i| (sobol(i, 0), sobol(i, 1))).collect();
+
+        assert_eq!(
+            vec![
+                (0, 0),
+                (1 << 31, 1 << 31),
+                (1 << 30, 3 << 30),
+                (3 << 30, 1 << 30)
+            ],
+            points
+        );
+    }
+
+    #[test]
+    fn sobol_sampler_is_stratified() {
+        assert_stratified(&mut SobolSampler::new(16, 2), 4);
+        assert_stratified(&mut SobolSampler::new(64, 9), 8);
+    }
+
+    #[test]
+    fn blue_noise_tile_ranks() {
+        let tile = blue_noise(16, 4);
+        let mut ranks: Vec<usize> = tile.iter().map(|value| (value * 256.0) as usize).collect();
+        ranks.sort();
+
+        assert_eq!((0..256).collect::<Vec<_>>(), ranks);
+    }
+
+    #[test]
+    fn blue_noise_tile_is_high_pass() {
+        // Neighbouring values of blue noise differ far more than in white
+        // noise, where the mean absolute difference is a third.
+        let tile = blue_noise(BLUE_NOISE_SIZE, 1);
+        let size = BLUE_NOISE_SIZE;
+        let difference = (0..size * size)
+            .map(|i| (tile[i] - tile[(i / size) * size + (i + 1) % size]).abs())
+            .sum::<f64>()
+            / (size * size) as f64;
+
+        assert!(difference > 0.4, "difference {difference}");
+    }
+}
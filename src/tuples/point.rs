@@ -1,3 +1,4 @@
+use crate::approx::{ApproxEq, Margin};
 use crate::tuples::coordinates::Coordinates;
 use crate::tuples::vector::Vector;
 
@@ -28,6 +29,20 @@ impl PartialEq for Point {
     }
 }
 
+impl ApproxEq for Point {
+    fn approx_eq(&self, other: &Self, margin: Margin) -> bool {
+        self.x().approx_eq(&other.x(), margin)
+            && self.y().approx_eq(&other.y(), margin)
+            && self.z().approx_eq(&other.z(), margin)
+    }
+
+    fn exact_eq(&self, other: &Self) -> bool {
+        self.x().exact_eq(&other.x())
+            && self.y().exact_eq(&other.y())
+            && self.z().exact_eq(&other.z())
+    }
+}
+
 impl Coordinates for Point {
     fn x(&self) -> f64 {
         self.x
@@ -169,4 +184,18 @@ mod tests {
         let desired_result = Point::new(0.5, -1.0, 1.5);
         assert_eq!(desired_result, point / 2.0);
     }
+
+    #[test]
+    fn approx_eq_point() {
+        let point = Point::new(0.1 + 0.2, 1.0, -2.0);
+
+        assert!(point.approx_eq(&Point::new(0.3, 1.0, -2.0), Margin::default()));
+        assert!(!point.approx_eq(&Point::new(0.3, 1.1, -2.0), Margin::default()));
+        assert!(!point.exact_eq(&Point::new(0.3, 1.0, -2.0)));
+        crate::assert_approx_eq!(
+            Point::new(1000.0, 0.0, 0.0),
+            Point::new(1001.0, 0.0, 0.0),
+            Margin::Relative(0.01)
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use crate::approx::{ApproxEq, Margin};
 use crate::tuples::coordinates::Coordinates;
 use crate::tuples::point::Point;
 use crate::tuples::scalar::Scalar;
@@ -29,6 +30,20 @@ impl PartialEq for Vector {
     }
 }
 
+impl ApproxEq for Vector {
+    fn approx_eq(&self, other: &Self, margin: Margin) -> bool {
+        self.x().approx_eq(&other.x(), margin)
+            && self.y().approx_eq(&other.y(), margin)
+            && self.z().approx_eq(&other.z(), margin)
+    }
+
+    fn exact_eq(&self, other: &Self) -> bool {
+        self.x().exact_eq(&other.x())
+            && self.y().exact_eq(&other.y())
+            && self.z().exact_eq(&other.z())
+    }
+}
+
 impl Coordinates for Vector {
     fn x(&self) -> f64 {
         self.x
@@ -295,4 +310,15 @@ mod tests {
         assert_eq!(desired_result, vector_a.cross_product(vector_b));
         assert_eq!(-desired_result, vector_b.cross_product(vector_a));
     }
+
+    #[test]
+    fn approx_eq_normalized_vector() {
+        let normalized = Vector::new(1.0, 1.0, 1.0).normalize();
+        let third = f64::sqrt(1.0 / 3.0);
+
+        crate::assert_approx_eq!(1.0, normalized.magnitude(), Margin::Ulps(4));
+        crate::assert_approx_eq!(Vector::new(third, third, third), normalized);
+        assert!(normalized.exact_eq(&normalized));
+        assert!(!Vector::new(0.0, 0.0, 0.0).exact_eq(&Vector::new(-0.0, 0.0, 0.0)));
+    }
 }
@@ -0,0 +1,335 @@
+// Renders a catalogue of reference scenes and compares them against the
+// goldens checked in under `tests/golden`. Failing scenes leave the render and
+// a diff image in the target directory. Run with `YART_BLESS=1` to write the
+// current renders as the new goldens instead.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use yart::pixels::canvas::Canvas;
+use yart::pixels::color::Color;
+use yart::pixels::compare::Tolerance;
+use yart::pixels::compositing::Operator;
+use yart::pixels::error::ImageError;
+use yart::pixels::png::PNG;
+use yart::pixels::ppm::PPM;
+use yart::pixels::rect::Rect;
+use yart::pixels::tone_mapping::ToneMapper;
+use yart::pixels::transform::Filter;
+use yart::rendering::camera::Camera;
+use yart::rendering::ray::Ray;
+use yart::tuples::coordinates::Coordinates;
+use yart::tuples::point::Point;
+use yart::tuples::scalar::Scalar;
+use yart::tuples::vector::Vector;
+
+struct Scene {
+    // File name of the golden, whose extension picks the image format.
+    golden: &'static str,
+    render: fn() -> Canvas,
+}
+
+const SCENES: &[Scene] = &[
+    Scene {
+        golden: "shapes.png",
+        render: shapes,
+    },
+    Scene {
+        golden: "text.ppm",
+        render: text,
+    },
+    Scene {
+        golden: "tone_mapping.png",
+        render: tone_mapping,
+    },
+    Scene {
+        golden: "compositing.png",
+        render: compositing,
+    },
+    Scene {
+        golden: "resampling.png",
+        render: resampling,
+    },
+    Scene {
+        golden: "supersampling.png",
+        render: supersampling,
+    },
+];
+
+fn background(width: usize, height: usize) -> Canvas {
+    let mut canvas = Canvas::new(width, height);
+    canvas.fill_rect(Rect::new(0, 0, width, height), Color::new(0.05, 0.05, 0.08));
+    canvas
+}
+
+fn shapes() -> Canvas {
+    let mut canvas = background(64, 64);
+
+    canvas.fill_rect(Rect::new(4, 4, 20, 12), Color::new(0.8, 0.2, 0.1));
+    canvas.fill_circle(44, 14, 10, Color::new(0.1, 0.6, 0.2));
+    canvas.draw_circle(44, 14, 12, Color::new(1.0, 1.0, 1.0));
+    canvas.fill_polygon(
+        &[(8.0, 60.0), (20.0, 30.0), (32.0, 60.0), (20.0, 50.0)],
+        Color::new(0.2, 0.3, 0.9),
+    );
+    canvas.draw_line(36, 34, 60, 60, Color::new(1.0, 0.9, 0.2));
+    canvas.draw_line_antialiased(36.0, 60.0, 60.5, 36.5, Color::new(0.9, 0.4, 0.9));
+
+    canvas
+}
+
+fn text() -> Canvas {
+    let mut canvas = background(96, 32);
+
+    canvas.draw_text(2, 2, "yart 0.1\nframe 0042", Color::new(1.0, 1.0, 1.0));
+    canvas.draw_text_scaled(2, 20, "AB", Color::new(1.0, 0.5, 0.0), 1);
+    canvas.draw_text_scaled(24, 16, "x2", Color::new(0.3, 0.8, 1.0), 2);
+
+    canvas
+}
+
+fn tone_mapping() -> Canvas {
+    let mut hdr = Canvas::new(64, 16);
+    for (x, y, color, alpha) in hdr.enumerate_pixels_mut() {
+        // Radiance rising exponentially from left to right, tinted per row.
+        let radiance = (x as f64 / 8.0).exp2() / 16.0;
+        let tint = y as f64 / 15.0;
+        *color = Color::new(radiance, radiance * (1.0 - 0.5 * tint), radiance * tint);
+        *alpha = 1.0;
+    }
+
+    let mut canvas = Canvas::new(64, 48);
+    let bands = [
+        ToneMapper::Reinhard,
+        ToneMapper::Aces,
+        ToneMapper::Hable { white_point: 11.2 },
+    ];
+    for (band, mapper) in bands.iter().enumerate() {
+        let mapped = mapper.apply(&hdr, 0.0);
+        for (x, y, color) in mapped.enumerate_pixels() {
+            canvas.set_pixel(x, y + band * 16, *color);
+        }
+    }
+
+    canvas
+}
+
+fn compositing() -> Canvas {
+    let mut source = Canvas::new(48, 48);
+    source.fill_circle(18, 18, 14, Color::new(0.9, 0.1, 0.1));
+
+    let mut destination = Canvas::new(48, 48);
+    destination.fill_rect(Rect::new(16, 16, 28, 28), Color::new(0.1, 0.2, 0.9));
+
+    let composite = Operator::Xor.apply(&source, &destination);
+    Operator::Over.apply(&composite, &background(48, 48))
+}
+
+fn resampling() -> Canvas {
+    let mut canvas = background(128, 128);
+    for i in 0..16 {
+        let angle = i as f64 * std::f64::consts::PI / 16.0;
+        let (dx, dy) = (angle.cos() * 60.0, angle.sin() * 60.0);
+        canvas.draw_line(
+            (64.0 - dx) as isize,
+            (64.0 - dy) as isize,
+            (64.0 + dx) as isize,
+            (64.0 + dy) as isize,
+            Color::new(1.0, 1.0, 1.0),
+        );
+    }
+
+    canvas.resize(32, 32, Filter::Lanczos)
+}
+
+// Two spheres on a checkered floor under a sky, lit by a distant light,
+// traced with sixteen jittered samples per pixel.
+fn supersampling() -> Canvas {
+    let mut camera = Camera::new(64, 48, std::f64::consts::FRAC_PI_3);
+    camera.look_at(
+        Point::new(0.0, 1.5, 5.0),
+        Point::new(0.0, 0.5, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    camera.set_samples(16);
+    camera.set_seed(7);
+
+    camera.render(trace)
+}
+
+fn spheres() -> [(Point, f64, Color); 2] {
+    [
+        (Point::new(-0.8, 0.7, 0.0), 0.7, Color::new(0.9, 0.2, 0.1)),
+        (Point::new(0.9, 0.5, 0.6), 0.5, Color::new(0.2, 0.4, 0.9)),
+    ]
+}
+
+// The distance along `ray` to the nearest surface, with its normal and
+// colour.
+fn hit(ray: &Ray) -> Option<(f64, Vector, Color)> {
+    let direction = ray.direction();
+    let mut nearest = None;
+
+    for (centre, radius, color) in spheres() {
+        let offset = ray.origin() - centre;
+        let half_b = offset.dot_product(direction);
+        let c = offset.dot_product(offset) - radius * radius;
+        let discriminant = half_b * half_b - c;
+        if discriminant < 0.0 {
+            continue;
+        }
+
+        let distance = -half_b - discriminant.sqrt();
+        if distance > 1e-6 && nearest.is_none_or(|(nearest, _, _)| distance < nearest) {
+            let normal = (ray.position(distance) - centre) / radius;
+            nearest = Some((distance, normal, color));
+        }
+    }
+
+    let floor = -ray.origin().y() / direction.y();
+    if floor > 1e-6 && nearest.is_none_or(|(nearest, _, _)| floor < nearest) {
+        let point = ray.position(floor);
+        let checker = (point.x().floor() + point.z().floor()).rem_euclid(2.0);
+        let color = if checker == 0.0 {
+            Color::new(0.8, 0.8, 0.8)
+        } else {
+            Color::new(0.2, 0.2, 0.2)
+        };
+        nearest = Some((floor, Vector::new(0.0, 1.0, 0.0), color));
+    }
+
+    nearest
+}
+
+fn trace(ray: &Ray) -> Option<Color> {
+    let Some((distance, normal, color)) = hit(ray) else {
+        let height = ray.direction().y().max(0.0);
+        return Some(Color::new(0.5, 0.7, 1.0) * (1.0 - 0.5 * height));
+    };
+
+    let light = Vector::new(-0.5, 1.0, 0.7).normalize();
+    let shadow = Ray::new(ray.position(distance) + normal * 1e-6, light);
+    let direct = if hit(&shadow).is_some() {
+        0.0
+    } else {
+        normal.dot_product(light).max(0.0)
+    };
+
+    Some(color * (0.15 + 0.85 * direct))
+}
+
+fn golden_directory() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+}
+
+fn failure_directory() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+fn blessing() -> bool {
+    std::env::var("YART_BLESS").is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+fn encode(name: &str, canvas: &Canvas) -> Vec<u8> {
+    if name.ends_with(".ppm") {
+        PPM::new(canvas).get()
+    } else {
+        PNG::new(canvas).get()
+    }
+}
+
+fn decode(name: &str, bytes: &[u8]) -> Result<Canvas, ImageError> {
+    if name.ends_with(".ppm") {
+        PPM::read(bytes)
+    } else {
+        PNG::read(bytes)
+    }
+}
+
+// Renders are compared as they would be stored, so the tolerance only has to
+// absorb a channel rounding the other way, such as from a different
+// floating point library.
+fn tolerance() -> Tolerance {
+    Tolerance::default()
+        .max_error(1.5 / 255.0)
+        .min_psnr(50.0)
+        .min_ssim(0.999)
+        .max_flip(0.001)
+}
+
+fn check(scene: &Scene) -> Result<(), String> {
+    let canvas = (scene.render)();
+    let golden_path = golden_directory().join(scene.golden);
+
+    if blessing() {
+        fs::create_dir_all(golden_directory()).map_err(|error| error.to_string())?;
+        return fs::write(&golden_path, encode(scene.golden, &canvas))
+            .map_err(|error| format!("{}: {}", scene.golden, error));
+    }
+
+    let bytes = fs::read(&golden_path).map_err(|error| {
+        format!(
+            "{}: {}, run with YART_BLESS=1 to create it",
+            scene.golden, error
+        )
+    })?;
+    let golden =
+        decode(scene.golden, &bytes).map_err(|error| format!("{}: {}", scene.golden, error))?;
+
+    if (golden.width(), golden.height()) != (canvas.width(), canvas.height()) {
+        return Err(format!(
+            "{}: rendered {}x{} but the golden is {}x{}",
+            scene.golden,
+            canvas.width(),
+            canvas.height(),
+            golden.width(),
+            golden.height()
+        ));
+    }
+
+    let stored = decode(scene.golden, &encode(scene.golden, &canvas))
+        .map_err(|error| format!("{}: {}", scene.golden, error))?;
+    let comparison = stored.compare(&golden);
+    if comparison.within(&tolerance()) {
+        return Ok(());
+    }
+
+    let directory = failure_directory();
+    let stem = Path::new(scene.golden)
+        .file_stem()
+        .unwrap()
+        .to_string_lossy();
+    let actual_path = directory.join(format!("{}.actual.png", stem));
+    let diff_path = directory.join(format!("{}.diff.png", stem));
+    fs::create_dir_all(&directory).map_err(|error| error.to_string())?;
+    fs::write(&actual_path, PNG::new(&stored).get()).map_err(|error| error.to_string())?;
+    fs::write(&diff_path, PNG::new(&stored.diff(&golden)).get())
+        .map_err(|error| error.to_string())?;
+
+    Err(format!(
+        "{}: max error {:.4}, PSNR {:.2} dB, SSIM {:.4}, FLIP {:.4}; wrote {} and {}",
+        scene.golden,
+        comparison.max_error(),
+        comparison.psnr(),
+        comparison.ssim(),
+        comparison.flip(),
+        actual_path.display(),
+        diff_path.display()
+    ))
+}
+
+#[test]
+fn golden_scenes() {
+    let failures: Vec<String> = SCENES
+        .iter()
+        .filter_map(|scene| check(scene).err())
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "golden images differ:\n{}",
+        failures.join("\n")
+    );
+}